@@ -1,57 +1,56 @@
 insert into registrations (
     tournament_id,
     player_id,
-    floats,
     status,
     rating
 ) values
-    (1, 1, 0, 'active', 2840),
-    (1, 2, 0, 'active', 2810),
-    (1, 3, 0, 'active', 2795),
-    (1, 4, 0, 'active', 2776),
-    (1, 5, 0, 'active', 2775),
-    (1, 6, 0, 'active', 2760),
-    (1, 7, 0, 'active', 2759),
-    (1, 8, 0, 'active', 2758),
-    (1, 9, 0, 'active', 2754),
-    (1, 10, 0, 'active', 2754),
-    (1, 11, 0, 'active', 2753),
-    (1, 12, 0, 'active', 2751),
-    (1, 13, 0, 'active', 2743),
-    (1, 14, 0, 'active', 2738),
-    (1, 15, 0, 'active', 2738),
-    (1, 16, 0, 'active', 2734),
-    (1, 17, 0, 'active', 2731),
-    (1, 18, 0, 'active', 2731),
-    (1, 19, 0, 'active', 2730),
-    (1, 20, 0, 'active', 2729),
-    (1, 21, 0, 'active', 2726),
-    (1, 22, 0, 'active', 2725),
-    (1, 23, 0, 'active', 2723),
-    (1, 24, 0, 'active', 2717),
-    (1, 25, 0, 'active', 2716),
-    (1, 26, 0, 'active', 2713),
-    (1, 27, 0, 'active', 2710),
-    (1, 28, 0, 'active', 2708),
-    (1, 29, 0, 'active', 2708),
-    (1, 30, 0, 'active', 2705),
-    (1, 31, 0, 'active', 2703),
-    (1, 32, 0, 'active', 2700),
-    (1, 33, 0, 'active', 2700),
-    (1, 34, 0, 'active', 2698),
-    (1, 35, 0, 'active', 2692),
-    (1, 36, 0, 'active', 2691),
-    (1, 37, 0, 'active', 2688),
-    (1, 38, 0, 'active', 2687),
-    (1, 39, 0, 'active', 2686),
-    (1, 40, 0, 'active', 2682),
-    (1, 41, 0, 'active', 2682),
-    (1, 42, 0, 'active', 2679),
-    (1, 43, 0, 'active', 2679),
-    (1, 44, 0, 'active', 2676),
-    (1, 45, 0, 'active', 2672),
-    (1, 46, 0, 'active', 2668),
-    (1, 47, 0, 'active', 2667),
-    (1, 48, 0, 'active', 2667),
-    (1, 49, 0, 'active', 2665),
-    (1, 50, 0, 'active', 2665)
+    (1, 1, 'active', 2840),
+    (1, 2, 'active', 2810),
+    (1, 3, 'active', 2795),
+    (1, 4, 'active', 2776),
+    (1, 5, 'active', 2775),
+    (1, 6, 'active', 2760),
+    (1, 7, 'active', 2759),
+    (1, 8, 'active', 2758),
+    (1, 9, 'active', 2754),
+    (1, 10, 'active', 2754),
+    (1, 11, 'active', 2753),
+    (1, 12, 'active', 2751),
+    (1, 13, 'active', 2743),
+    (1, 14, 'active', 2738),
+    (1, 15, 'active', 2738),
+    (1, 16, 'active', 2734),
+    (1, 17, 'active', 2731),
+    (1, 18, 'active', 2731),
+    (1, 19, 'active', 2730),
+    (1, 20, 'active', 2729),
+    (1, 21, 'active', 2726),
+    (1, 22, 'active', 2725),
+    (1, 23, 'active', 2723),
+    (1, 24, 'active', 2717),
+    (1, 25, 'active', 2716),
+    (1, 26, 'active', 2713),
+    (1, 27, 'active', 2710),
+    (1, 28, 'active', 2708),
+    (1, 29, 'active', 2708),
+    (1, 30, 'active', 2705),
+    (1, 31, 'active', 2703),
+    (1, 32, 'active', 2700),
+    (1, 33, 'active', 2700),
+    (1, 34, 'active', 2698),
+    (1, 35, 'active', 2692),
+    (1, 36, 'active', 2691),
+    (1, 37, 'active', 2688),
+    (1, 38, 'active', 2687),
+    (1, 39, 'active', 2686),
+    (1, 40, 'active', 2682),
+    (1, 41, 'active', 2682),
+    (1, 42, 'active', 2679),
+    (1, 43, 'active', 2679),
+    (1, 44, 'active', 2676),
+    (1, 45, 'active', 2672),
+    (1, 46, 'active', 2668),
+    (1, 47, 'active', 2667),
+    (1, 48, 'active', 2667),
+    (1, 49, 'active', 2665),
+    (1, 50, 'active', 2665)
@@ -0,0 +1,8 @@
+create table tournament_organizers (
+    tournament_id integer not null,
+    user_id integer not null,
+    created_at integer default (unixepoch()) not null,
+    constraint fk_organizer_tournament foreign key (tournament_id) references tournaments(id),
+    constraint fk_organizer_user foreign key (user_id) references users(id),
+    constraint pk_tournament_organizer primary key (tournament_id, user_id)
+);
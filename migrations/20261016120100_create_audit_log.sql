@@ -0,0 +1,12 @@
+create table audit_log (
+    id integer not null primary key autoincrement,
+    user_id integer not null,
+    tournament_id integer not null,
+    action text not null,
+    detail text not null,
+    created_at integer default (unixepoch()) not null,
+    constraint fk_audit_user foreign key (user_id) references users(id),
+    constraint fk_audit_tournament foreign key (tournament_id) references tournaments(id)
+);
+
+create index idx_audit_log_tournament on audit_log (tournament_id);
@@ -0,0 +1 @@
+alter table tournaments add column max_float_gap integer;
@@ -0,0 +1 @@
+alter table tournaments add column registration_open boolean not null default true;
@@ -0,0 +1 @@
+alter table tournaments add column late_entry_default_score integer not null default 0;
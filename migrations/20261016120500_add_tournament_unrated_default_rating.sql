@@ -0,0 +1 @@
+alter table tournaments add column unrated_default_rating integer;
@@ -0,0 +1,7 @@
+alter table users add column email_verified boolean not null default 0;
+alter table users add column verification_token text;
+
+create unique index idx_users_verification_token on users(verification_token);
+
+-- Accounts created before verification existed keep working
+update users set email_verified = 1;
@@ -0,0 +1 @@
+alter table tournaments add column board_offset integer not null default 0;
@@ -0,0 +1,10 @@
+create table sections (
+    id integer not null primary key autoincrement,
+    tournament_id integer not null,
+    name text not null,
+    created_at integer default (unixepoch()) not null,
+    constraint fk_section_tournament foreign key (tournament_id) references tournaments(id),
+    constraint uq_section_name unique (tournament_id, name)
+);
+
+alter table registrations add column section_id integer references sections(id);
@@ -0,0 +1,14 @@
+-- Tiebreak games among tied leaders, kept apart from pairings so they never count for
+-- scores, buchholz or ratings
+create table playoff_games (
+    id integer not null primary key autoincrement,
+    tournament_id integer not null,
+    round_number integer not null,
+    white_id integer not null,
+    black_id integer not null,
+    result text,
+    created_at integer default (unixepoch()) not null,
+    constraint fk_playoff_tournament foreign key (tournament_id) references tournaments(id),
+    constraint fk_playoff_white foreign key (white_id) references registrations(id),
+    constraint fk_playoff_black foreign key (black_id) references registrations(id)
+);
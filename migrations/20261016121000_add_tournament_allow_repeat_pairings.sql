@@ -0,0 +1 @@
+alter table tournaments add column allow_repeat_pairings boolean not null default 0;
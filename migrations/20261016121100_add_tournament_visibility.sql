@@ -0,0 +1 @@
+alter table tournaments add column visibility text not null default 'public';
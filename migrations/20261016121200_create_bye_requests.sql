@@ -0,0 +1,9 @@
+create table bye_requests (
+    id integer not null primary key autoincrement,
+    registration_id integer not null,
+    round_number integer not null,
+    unique (registration_id, round_number),
+    constraint fk_bye_request_registration foreign key (registration_id) references registrations(id) on delete cascade
+);
+
+alter table tournaments add column requested_bye_score integer not null default 1;
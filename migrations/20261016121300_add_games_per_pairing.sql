@@ -0,0 +1,4 @@
+alter table tournaments add column games_per_pairing integer not null default 1;
+
+-- Comma separated results of the individual games of a mini-match, null for single games
+alter table pairings add column games text;
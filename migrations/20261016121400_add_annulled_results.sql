@@ -0,0 +1,3 @@
+-- Result of the game before it was annulled, and the registration whose results were annulled
+alter table pairings add column annulled_result text;
+alter table pairings add column annulled_registration_id integer;
@@ -0,0 +1,2 @@
+-- Comma separated tiebreaks applied in order after the score
+alter table tournaments add column tiebreaks text not null default 'median_buchholz,cut_one_buchholz,buchholz,progressive';
@@ -0,0 +1 @@
+alter table tournaments add column max_players integer;
@@ -0,0 +1 @@
+alter table pairings add column agreed_draw boolean not null default false;
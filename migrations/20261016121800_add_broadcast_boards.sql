@@ -0,0 +1 @@
+alter table tournaments add column broadcast_boards integer not null default 0;
@@ -0,0 +1,3 @@
+alter table tournaments add column self_registration boolean not null default false;
+alter table registrations add column user_id integer references users(id);
+create unique index uq_registration_user on registrations (tournament_id, user_id);
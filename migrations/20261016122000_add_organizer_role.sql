@@ -0,0 +1,2 @@
+-- Existing co-organizers keep full access
+alter table tournament_organizers add column role text not null default 'owner';
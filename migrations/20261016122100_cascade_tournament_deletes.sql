@@ -0,0 +1,130 @@
+-- SQLite cannot alter foreign keys, every table owned by a tournament is rebuilt with
+-- `on delete cascade` so deleting a tournament removes everything attached to it.
+-- Foreign keys have to be off while the tables are swapped (dropping a table deletes its
+-- rows, cascading into the other tables). The pragma cannot change inside the migration
+-- transaction, `config::migrate` turns them off before running the migrations
+
+create table new_registrations (
+    id integer not null primary key autoincrement,
+    player_id integer not null,
+    tournament_id integer not null,
+    floats integer not null,
+    status text not null,
+    rating integer not null,
+    section_id integer,
+    user_id integer,
+    constraint fk_registration_player foreign key (player_id) references players(id),
+    constraint fk_registration_tournament foreign key (tournament_id) references tournaments(id) on delete cascade,
+    constraint fk_registration_section foreign key (section_id) references sections(id),
+    constraint fk_registration_user foreign key (user_id) references users(id),
+    constraint uq_registration unique (player_id, tournament_id)
+);
+insert into new_registrations (id, player_id, tournament_id, floats, status, rating, section_id, user_id)
+    select id, player_id, tournament_id, floats, status, rating, section_id, user_id from registrations;
+drop table registrations;
+alter table new_registrations rename to registrations;
+create unique index uq_registration_user on registrations (tournament_id, user_id);
+
+create table new_pairings (
+    id integer not null primary key autoincrement,
+    tournament_id integer not null,
+    round_number integer not null,
+    board_number integer not null,
+    white_id integer not null,
+    black_id integer not null,
+    result text,
+    pgn text,
+    games text,
+    annulled_result text,
+    annulled_registration_id integer,
+    agreed_draw boolean not null default false,
+    constraint fk_pairing_tournament foreign key (tournament_id) references tournaments(id) on delete cascade,
+    constraint fk_pairing_white foreign key (white_id) references registrations(id) on delete cascade,
+    constraint fk_pairing_black foreign key (black_id) references registrations(id) on delete cascade,
+    constraint ck_white_diff_black check (white_id != black_id),
+    constraint uq_pairing unique (tournament_id, round_number, board_number, white_id, black_id)
+);
+insert into new_pairings
+    select id, tournament_id, round_number, board_number, white_id, black_id, result, pgn, games,
+        annulled_result, annulled_registration_id, agreed_draw
+    from pairings;
+drop table pairings;
+alter table new_pairings rename to pairings;
+
+create table new_pairing_gaps (
+    id integer not null primary key autoincrement,
+    player_id integer not null,
+    tournament_id integer not null,
+    round_id integer not null,
+    score integer not null,
+    is_bye boolean not null,
+    constraint fk_pgap_player foreign key (player_id) references registrations(id) on delete cascade,
+    constraint fk_pgap_tournament foreign key (tournament_id) references tournaments(id) on delete cascade
+);
+insert into new_pairing_gaps select id, player_id, tournament_id, round_id, score, is_bye from pairing_gaps;
+drop table pairing_gaps;
+alter table new_pairing_gaps rename to pairing_gaps;
+
+create table new_sections (
+    id integer not null primary key autoincrement,
+    tournament_id integer not null,
+    name text not null,
+    created_at integer default (unixepoch()) not null,
+    constraint fk_section_tournament foreign key (tournament_id) references tournaments(id) on delete cascade,
+    constraint uq_section_name unique (tournament_id, name)
+);
+insert into new_sections select id, tournament_id, name, created_at from sections;
+drop table sections;
+alter table new_sections rename to sections;
+
+create table new_playoff_games (
+    id integer not null primary key autoincrement,
+    tournament_id integer not null,
+    round_number integer not null,
+    white_id integer not null,
+    black_id integer not null,
+    result text,
+    created_at integer default (unixepoch()) not null,
+    constraint fk_playoff_tournament foreign key (tournament_id) references tournaments(id) on delete cascade,
+    constraint fk_playoff_white foreign key (white_id) references registrations(id) on delete cascade,
+    constraint fk_playoff_black foreign key (black_id) references registrations(id) on delete cascade
+);
+insert into new_playoff_games
+    select id, tournament_id, round_number, white_id, black_id, result, created_at from playoff_games;
+drop table playoff_games;
+alter table new_playoff_games rename to playoff_games;
+
+create table new_tournament_organizers (
+    tournament_id integer not null,
+    user_id integer not null,
+    created_at integer default (unixepoch()) not null,
+    role text not null default 'owner',
+    constraint fk_organizer_tournament foreign key (tournament_id) references tournaments(id) on delete cascade,
+    constraint fk_organizer_user foreign key (user_id) references users(id),
+    constraint pk_tournament_organizer primary key (tournament_id, user_id)
+);
+insert into new_tournament_organizers
+    select tournament_id, user_id, created_at, role from tournament_organizers;
+drop table tournament_organizers;
+alter table new_tournament_organizers rename to tournament_organizers;
+
+create table new_audit_log (
+    id integer not null primary key autoincrement,
+    user_id integer not null,
+    tournament_id integer not null,
+    action text not null,
+    detail text not null,
+    created_at integer default (unixepoch()) not null,
+    constraint fk_audit_user foreign key (user_id) references users(id),
+    constraint fk_audit_tournament foreign key (tournament_id) references tournaments(id) on delete cascade
+);
+insert into new_audit_log select id, user_id, tournament_id, action, detail, created_at from audit_log;
+drop table audit_log;
+alter table new_audit_log rename to audit_log;
+create index idx_audit_log_tournament on audit_log (tournament_id);
+
+-- The copied rows must still satisfy every foreign key, the check constraint aborts the
+-- migration otherwise
+create temp table foreign_key_violations (violations integer not null check (violations = 0));
+insert into foreign_key_violations select count(*) from pragma_foreign_key_check;
+drop table foreign_key_violations;
@@ -0,0 +1 @@
+alter table tournaments add column allow_three_same_colors boolean not null default false;
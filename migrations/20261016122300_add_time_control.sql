@@ -0,0 +1 @@
+alter table tournaments add column time_control text;
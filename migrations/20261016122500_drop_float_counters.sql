@@ -0,0 +1,2 @@
+-- Floats are computed from the pairing history, the counter goes
+alter table registrations drop column floats;
@@ -0,0 +1 @@
+alter table tournaments add column avoid_recent_opponents boolean not null default false;
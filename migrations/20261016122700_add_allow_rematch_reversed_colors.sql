@@ -0,0 +1 @@
+alter table tournaments add column allow_rematch_reversed_colors boolean not null default false;
@@ -0,0 +1 @@
+alter table tournaments add column avoid_last_round_contender_bye boolean not null default false;
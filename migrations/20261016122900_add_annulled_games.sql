@@ -0,0 +1,3 @@
+-- Games and agreed draw flag of a board before its result was annulled
+alter table pairings add column annulled_games text;
+alter table pairings add column annulled_agreed_draw boolean;
@@ -0,0 +1,3 @@
+-- User account a player belongs to, set when the user registers themselves with that player
+alter table players add column user_id integer references users(id) on delete set null;
+create unique index uq_player_user on players (user_id);
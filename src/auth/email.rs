@@ -0,0 +1,24 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+
+use crate::errors::AppError;
+
+// Sends emails to users, swapped for a mock in tests
+pub trait EmailSender: Send + Sync {
+    fn send_verification(&self, email: &str, token: &str) -> Result<(), AppError>;
+}
+
+// No mail server is configured yet, the verification link is only logged
+pub struct LogEmailSender;
+
+impl EmailSender for LogEmailSender {
+    fn send_verification(&self, email: &str, token: &str) -> Result<(), AppError> {
+        tracing::info!("verification link for {}: /verify?token={}", email, token);
+        Ok(())
+    }
+}
+
+pub fn generate_verification_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -1,6 +1,8 @@
 use axum::{
-    extract::FromRequestParts,
+    extract::{FromRequestParts, Request},
     http::{StatusCode, header, request::Parts},
+    middleware::Next,
+    response::Response,
 };
 
 use crate::errors::AppError;
@@ -36,6 +38,32 @@ where
     }
 }
 
+// Like CurrentUser, but anonymous requests and invalid tokens are let through as None,
+// for routes that show more to signed in users
+#[derive(Clone)]
+pub struct OptionalCurrentUser(pub Option<Claims>);
+
+impl<S> FromRequestParts<S> for OptionalCurrentUser
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let claims = CurrentUser::from_request_parts(parts, state)
+            .await
+            .ok()
+            .map(|CurrentUser(claims)| claims);
+        Ok(OptionalCurrentUser(claims))
+    }
+}
+
+// Middleware rejecting anonymous requests before the layers under it, e.g. rate limiters that
+// should not be used up by requests failing authentication
+pub async fn require_user(_: CurrentUser, req: Request, next: Next) -> Response {
+    next.run(req).await
+}
+
 pub async fn require_admin(
     CurrentUser(claims): CurrentUser,
 ) -> Result<(), (StatusCode, &'static str)> {
@@ -6,7 +6,32 @@ use jsonwebtoken::{
 };
 use serde::{Deserialize, Serialize};
 
-static JWT_SECRET: LazyLock<String> = LazyLock::new(|| env::var("JWT_SECRET").unwrap());
+// Tests run without the env, they get a fixed secret
+static JWT_SECRET: LazyLock<String> = LazyLock::new(|| match env::var("JWT_SECRET") {
+    Ok(secret) => secret,
+    Err(_) if cfg!(test) => String::from("test-secret"),
+    Err(e) => panic!("JWT_SECRET: {}", e),
+});
+
+const DEFAULT_TOKEN_HOURS: i64 = 24;
+
+// How long a login stays valid, ADMIN_TOKEN_HOURS for admins and USER_TOKEN_HOURS for everyone
+// else, 24 hours when unset or invalid
+pub fn token_duration(role: &str) -> Duration {
+    let name = if role == "admin" {
+        "ADMIN_TOKEN_HOURS"
+    } else {
+        "USER_TOKEN_HOURS"
+    };
+    duration_from_hours(env::var(name).ok())
+}
+
+fn duration_from_hours(hours: Option<String>) -> Duration {
+    hours
+        .and_then(|h| h.parse().ok())
+        .filter(|h| *h > 0)
+        .map_or(Duration::hours(DEFAULT_TOKEN_HOURS), Duration::hours)
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
@@ -44,3 +69,37 @@ pub fn validate_token(token: &str) -> Result<Claims, JwtError> {
     )
     .map(|data| data.claims)
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::{create_token, duration_from_hours, validate_token};
+
+    #[test]
+    fn test_token_duration() {
+        assert_eq!(duration_from_hours(None), Duration::hours(24));
+        assert_eq!(
+            duration_from_hours(Some("8".to_string())),
+            Duration::hours(8)
+        );
+        assert_eq!(
+            duration_from_hours(Some("0".to_string())),
+            Duration::hours(24)
+        );
+        assert_eq!(
+            duration_from_hours(Some("a day".to_string())),
+            Duration::hours(24)
+        );
+    }
+
+    #[test]
+    fn test_expired_token() {
+        let token = |duration| {
+            create_token(1, "user".to_string(), "standard".to_string(), duration).unwrap()
+        };
+        assert!(validate_token(&token(Duration::hours(1))).is_ok());
+        // Beyond the default one minute leeway
+        assert!(validate_token(&token(Duration::minutes(-2))).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    env,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+const DEFAULT_MAX_FAILURES: usize = 5;
+const DEFAULT_LOCKOUT_MINUTES: u64 = 15;
+
+// Failed login attempts per username, the account is locked once `max_failures`
+// happen within `window`
+#[derive(Debug)]
+pub struct LoginAttempts {
+    max_failures: usize,
+    window: Duration,
+    failures: Mutex<HashMap<String, Vec<Instant>>>,
+}
+
+impl Default for LoginAttempts {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_MAX_FAILURES,
+            Duration::from_secs(DEFAULT_LOCKOUT_MINUTES * 60),
+        )
+    }
+}
+
+impl LoginAttempts {
+    pub fn new(max_failures: usize, window: Duration) -> Self {
+        Self {
+            max_failures,
+            window,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // LOGIN_MAX_FAILURES (default 5) and LOGIN_LOCKOUT_MINUTES (default 15)
+    pub fn from_env() -> Self {
+        let max_failures = env::var("LOGIN_MAX_FAILURES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_FAILURES);
+        let minutes = env::var("LOGIN_LOCKOUT_MINUTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_LOCKOUT_MINUTES);
+        Self::new(max_failures, Duration::from_secs(minutes * 60))
+    }
+
+    // Time left until the account can log in again, None if not locked
+    pub fn locked_for(&self, username: &str) -> Option<Duration> {
+        self.locked_for_at(username, Instant::now())
+    }
+
+    pub fn record_failure(&self, username: &str) {
+        self.record_failure_at(username, Instant::now());
+    }
+
+    pub fn reset(&self, username: &str) {
+        self.failures.lock().unwrap().remove(username);
+    }
+
+    fn locked_for_at(&self, username: &str, now: Instant) -> Option<Duration> {
+        let mut failures = self.failures.lock().unwrap();
+        let attempts = failures.get_mut(username)?;
+        attempts.retain(|t| now.duration_since(*t) < self.window);
+        if attempts.is_empty() {
+            failures.remove(username);
+            return None;
+        }
+        if self.max_failures == 0 || attempts.len() < self.max_failures {
+            return None;
+        }
+        // Locked until enough failures fall out of the window
+        let unlock_at = attempts[attempts.len() - self.max_failures] + self.window;
+        Some(unlock_at.duration_since(now))
+    }
+
+    // Usernames whose failures all left the window are dropped, so guessed usernames that are
+    // never tried again do not stay in memory
+    fn record_failure_at(&self, username: &str, now: Instant) {
+        let mut failures = self.failures.lock().unwrap();
+        failures.retain(|_, attempts| {
+            attempts.retain(|t| now.duration_since(*t) < self.window);
+            !attempts.is_empty()
+        });
+        failures.entry(username.to_string()).or_default().push(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::LoginAttempts;
+
+    #[test]
+    fn test_lockout() {
+        let attempts = LoginAttempts::new(3, Duration::from_secs(60));
+        let start = Instant::now();
+        for i in 0..2 {
+            attempts.record_failure_at("user", start + Duration::from_secs(i * 10));
+        }
+        assert_eq!(attempts.locked_for_at("user", start), None);
+        attempts.record_failure_at("user", start + Duration::from_secs(20));
+        assert_eq!(
+            attempts.locked_for_at("user", start + Duration::from_secs(30)),
+            Some(Duration::from_secs(30))
+        );
+        // Other accounts are not affected
+        assert_eq!(attempts.locked_for_at("other", start), None);
+        // Unlocked once the first failure leaves the window
+        assert_eq!(
+            attempts.locked_for_at("user", start + Duration::from_secs(60)),
+            None
+        );
+        attempts.record_failure_at("user", start + Duration::from_secs(61));
+        assert!(
+            attempts
+                .locked_for_at("user", start + Duration::from_secs(62))
+                .is_some()
+        );
+        attempts.reset("user");
+        assert_eq!(
+            attempts.locked_for_at("user", start + Duration::from_secs(62)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_expired_failures_pruned() {
+        let attempts = LoginAttempts::new(3, Duration::from_secs(60));
+        let start = Instant::now();
+        for username in ["a", "b", "c"] {
+            attempts.record_failure_at(username, start);
+        }
+        assert_eq!(attempts.failures.lock().unwrap().len(), 3);
+        // Any later failure drops the usernames whose failures expired
+        attempts.record_failure_at("d", start + Duration::from_secs(60));
+        let usernames: Vec<String> = attempts.failures.lock().unwrap().keys().cloned().collect();
+        assert_eq!(usernames, vec!["d"]);
+        assert_eq!(
+            attempts.locked_for_at("d", start + Duration::from_secs(120)),
+            None
+        );
+        assert!(attempts.failures.lock().unwrap().is_empty());
+    }
+}
@@ -1,4 +1,6 @@
 pub mod admin;
+pub mod email;
 pub mod extractor;
 pub mod hasher;
 pub mod jwt;
+pub mod lockout;
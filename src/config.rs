@@ -0,0 +1,275 @@
+use std::{env, str::FromStr, time::Duration};
+
+use axum::http::{HeaderValue, Method, header};
+use sqlx::{
+    SqlitePool,
+    migrate::MigrateError,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+// Which cross-origin requests are accepted
+#[derive(Debug, PartialEq)]
+pub enum CorsPolicy {
+    // Anything goes, credentials included, only for local development
+    Dev,
+    // Any origin, without credentials
+    Wildcard,
+    // Only the listed origins, with credentials
+    Origins(Vec<HeaderValue>),
+    // No allowlist configured, browsers only allow same-origin requests
+    SameOrigin,
+}
+
+impl CorsPolicy {
+    fn from_vars(allowed_origins: Option<String>, dev: Option<String>) -> Self {
+        if dev.as_deref() == Some("1") {
+            return Self::Dev;
+        }
+        let origins: Vec<&str> = allowed_origins
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|o| !o.is_empty())
+            .collect();
+        if origins.contains(&"*") {
+            CorsPolicy::Wildcard
+        } else {
+            let origins: Vec<HeaderValue> = origins
+                .into_iter()
+                .filter_map(|o| match HeaderValue::from_str(o) {
+                    Ok(origin) => Some(origin),
+                    Err(_) => {
+                        tracing::warn!("ignoring invalid CORS origin: {}", o);
+                        None
+                    }
+                })
+                .collect();
+            if origins.is_empty() {
+                CorsPolicy::SameOrigin
+            } else {
+                CorsPolicy::Origins(origins)
+            }
+        }
+    }
+}
+
+// SQLite pool settings, every connection runs in WAL mode with foreign keys enforced so
+// concurrent result entry does not serialize on the database lock
+#[derive(Debug, PartialEq)]
+pub struct DatabaseConfig {
+    pub max_connections: u32,
+    // How long a write waits for the lock before failing with SQLITE_BUSY
+    pub busy_timeout: Duration,
+}
+
+const DEFAULT_MAX_CONNECTIONS: u32 = 8;
+const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5000;
+
+impl DatabaseConfig {
+    fn from_vars(max_connections: Option<String>, busy_timeout_ms: Option<String>) -> Self {
+        Self {
+            max_connections: max_connections
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(DEFAULT_MAX_CONNECTIONS),
+            busy_timeout: Duration::from_millis(
+                busy_timeout_ms
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(DEFAULT_BUSY_TIMEOUT_MS),
+            ),
+        }
+    }
+
+    // The database file is created when missing, `migrate` then sets up the schema
+    pub async fn connect(&self, url: &str) -> sqlx::Result<SqlitePool> {
+        let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        let busy_timeout = self.busy_timeout.as_millis();
+        SqlitePoolOptions::new()
+            .max_connections(self.max_connections)
+            .after_connect(move |conn, _| {
+                Box::pin(async move {
+                    sqlx::query("pragma journal_mode = wal")
+                        .execute(&mut *conn)
+                        .await?;
+                    sqlx::query("pragma foreign_keys = on")
+                        .execute(&mut *conn)
+                        .await?;
+                    sqlx::query(&format!("pragma busy_timeout = {}", busy_timeout))
+                        .execute(&mut *conn)
+                        .await?;
+                    Ok(())
+                })
+            })
+            .connect_with(options)
+            .await
+    }
+}
+
+// Applies the pending migrations, the same ones the tests run against. They run on a single
+// connection with foreign keys off: rebuilding a table drops the old one, which would cascade
+// into the tables referencing it, and the pragma cannot change inside the transaction of a
+// migration. Migrations that rebuild tables check the foreign keys themselves
+pub async fn migrate(pool: &SqlitePool) -> Result<(), MigrateError> {
+    let mut conn = pool.acquire().await?;
+    sqlx::query("pragma foreign_keys = off")
+        .execute(&mut *conn)
+        .await?;
+    let result = sqlx::migrate!().run(&mut *conn).await;
+    sqlx::query("pragma foreign_keys = on")
+        .execute(&mut *conn)
+        .await?;
+    result
+}
+
+pub struct Config {
+    pub cors: CorsPolicy,
+    pub database: DatabaseConfig,
+    // Error responses sent with status 200 as before, until every client reads the real status
+    pub legacy_error_status: bool,
+}
+
+impl Config {
+    // CORS_DEV=1 for the permissive development setup, otherwise CORS_ALLOWED_ORIGINS holds a
+    // comma separated list of origins, or `*` to allow any origin without credentials
+    // DB_MAX_CONNECTIONS and DB_BUSY_TIMEOUT_MS size the database pool
+    // LEGACY_ERROR_STATUS=0 sends errors with their real status, 200 is kept by default
+    pub fn from_env() -> Self {
+        Self {
+            cors: CorsPolicy::from_vars(
+                env::var("CORS_ALLOWED_ORIGINS").ok(),
+                env::var("CORS_DEV").ok(),
+            ),
+            database: DatabaseConfig::from_vars(
+                env::var("DB_MAX_CONNECTIONS").ok(),
+                env::var("DB_BUSY_TIMEOUT_MS").ok(),
+            ),
+            legacy_error_status: env::var("LEGACY_ERROR_STATUS")
+                .ok()
+                .is_none_or(|value| value != "0"),
+        }
+    }
+
+    pub fn cors_layer(&self) -> CorsLayer {
+        let layer = CorsLayer::new()
+            .allow_methods([
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ])
+            .allow_headers([
+                header::AUTHORIZATION,
+                header::CONTENT_TYPE,
+                header::IF_NONE_MATCH,
+                header::IF_MODIFIED_SINCE,
+            ])
+            .expose_headers([header::ETAG, header::RETRY_AFTER]);
+        match &self.cors {
+            CorsPolicy::Dev => CorsLayer::very_permissive(),
+            CorsPolicy::Wildcard => layer.allow_origin(Any),
+            CorsPolicy::Origins(origins) => layer
+                .allow_origin(AllowOrigin::list(origins.iter().cloned()))
+                .allow_credentials(true),
+            CorsPolicy::SameOrigin => layer,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::http::HeaderValue;
+
+    use super::{CorsPolicy, DatabaseConfig, migrate};
+
+    #[test]
+    fn test_cors_policy() {
+        let cors = |origins: Option<&str>, dev: Option<&str>| {
+            CorsPolicy::from_vars(origins.map(String::from), dev.map(String::from))
+        };
+        assert_eq!(cors(None, None), CorsPolicy::SameOrigin);
+        assert_eq!(cors(Some(" , "), None), CorsPolicy::SameOrigin);
+        assert_eq!(cors(Some("*"), None), CorsPolicy::Wildcard);
+        assert_eq!(
+            cors(Some("https://a.org, https://b.org"), None),
+            CorsPolicy::Origins(vec![
+                HeaderValue::from_static("https://a.org"),
+                HeaderValue::from_static("https://b.org"),
+            ])
+        );
+        // Dev mode has to be explicit and overrides the allowlist
+        assert_eq!(cors(Some("https://a.org"), Some("1")), CorsPolicy::Dev);
+        assert_eq!(cors(None, Some("true")), CorsPolicy::SameOrigin);
+    }
+
+    #[test]
+    fn test_database_config() {
+        let config = DatabaseConfig::from_vars(None, None);
+        assert_eq!(config.max_connections, 8);
+        assert_eq!(config.busy_timeout, Duration::from_secs(5));
+        let config = DatabaseConfig::from_vars(Some("16".to_string()), Some("250".to_string()));
+        assert_eq!(config.max_connections, 16);
+        assert_eq!(config.busy_timeout, Duration::from_millis(250));
+        // A pool without connections would hang every request
+        let config = DatabaseConfig::from_vars(Some("0".to_string()), Some("soon".to_string()));
+        assert_eq!(config.max_connections, 8);
+        assert_eq!(config.busy_timeout, Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_database_pragmas() {
+        let pool = DatabaseConfig::from_vars(None, Some("1234".to_string()))
+            .connect("sqlite::memory:")
+            .await
+            .expect("failed to connect");
+        let foreign_keys: i64 = sqlx::query_scalar("pragma foreign_keys")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(foreign_keys, 1);
+        let busy_timeout: i64 = sqlx::query_scalar("pragma busy_timeout")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(busy_timeout, 1234);
+    }
+
+    #[tokio::test]
+    async fn test_migrate() {
+        // Every connection to an in-memory database gets its own database
+        let pool = DatabaseConfig::from_vars(Some("1".to_string()), None)
+            .connect("sqlite::memory:")
+            .await
+            .expect("failed to connect");
+        migrate(&pool).await.expect("failed to migrate");
+        let tables: Vec<String> = sqlx::query_scalar(
+            "select name from sqlite_master where type = 'table' and name in ('users', 'players', 'tournaments', 'registrations', 'pairings', 'pairing_gaps') order by name",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            tables,
+            [
+                "pairing_gaps",
+                "pairings",
+                "players",
+                "registrations",
+                "tournaments",
+                "users"
+            ]
+        );
+        // Running them again is a no-op
+        migrate(&pool).await.expect("failed to migrate twice");
+        // The connection used for the migrations is back to enforcing foreign keys
+        let foreign_keys: i64 = sqlx::query_scalar("pragma foreign_keys")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(foreign_keys, 1);
+    }
+}
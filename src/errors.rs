@@ -1,8 +1,9 @@
+use axum::http::StatusCode;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum AppError {
-    #[error("The authenticaton header is missing or invalid")]
+    #[error("The authentication header is missing or invalid")]
     InvalidAuthHeader,
     #[error("The provided jwt is invalid or has expired, please reauthenticate")]
     TokenInvalid,
@@ -58,45 +59,788 @@ pub enum AppError {
     TournamentNotFound,
     #[error("Invalid action for round `{0}`")]
     InvalidRound(usize),
+    #[error("Board numbers must be unique and contiguous starting at 0")]
+    InvalidBoardNumbering,
+    #[error("Player with id `{0}` appears more than once in the fixed pairings")]
+    DuplicateFixedPlayer(u32),
+    #[error("Cannot fix pairing {white:?} - {black:?}, players have already played each other")]
+    FixedPairingAlreadyPlayed { white: u32, black: u32 },
+    #[error("Registration for this tournament is closed")]
+    RegistrationClosed,
+    #[error("Cannot reopen registration after the first round has been paired")]
+    CannotReopenRegistration,
+    #[error("Late entry score `{0}` is not valid, must be between 0 and 2 half points")]
+    InvalidLateEntryScore(u32),
+    #[error(
+        "Cannot remove a registration after the tournament has started, set the player inactive instead"
+    )]
+    CannotUnregisterAfterStart,
+    #[error("Registration with id `{0}` does not exist")]
+    RegistrationNotFound(u32),
+    #[error("User `{0}` does not exist")]
+    UserNotFound(String),
+    #[error("User with id `{0}` is not an organizer of this tournament")]
+    OrganizerNotFound(u32),
+    #[error("Too many requests, please try again later")]
+    RateLimited,
+    #[error("Too many failed login attempts, try again in `{0}` seconds")]
+    AccountLocked(u64),
+    #[error("Email address must be verified to perform this action")]
+    EmailNotVerified,
+    #[error("Verification token is invalid or has already been used")]
+    InvalidVerificationToken,
+    #[error("Section with id `{0}` does not exist in this tournament")]
+    SectionNotFound(u32),
+    #[error("Playoff games can only be created once every round has been played")]
+    PlayoffNotAvailable,
+    #[error("There is no tie for first place, no playoff is needed")]
+    NoPlayoffNeeded,
+    #[error("The playoff already has a winner")]
+    PlayoffFinished,
+    #[error("Playoff game with id `{0}` does not exist")]
+    PlayoffGameNotFound(u32),
+    #[error("Pairing failed, {reason}, unpaired players: {unpaired:?}")]
+    PairingFailed { reason: String, unpaired: Vec<u32> },
+    #[error("Visibility `{0}` is not valid, possible values are: public, unlisted and private")]
+    InvalidVisibility(String),
+    #[error("Requested bye score `{0}` is not valid, must be between 0 and 2 half points")]
+    InvalidByeScore(u32),
+    #[error("Cannot request a bye for round {0}, it is already paired or beyond the last round")]
+    InvalidByeRequest(u32),
+    #[error(
+        "Start date `{0}` is not valid, must be a unix timestamp in seconds before the end of the tournament"
+    )]
+    InvalidStartDate(u32),
+    #[error("Games per pairing `{0}` is not valid, must be between 1 and 10")]
+    InvalidGamesPerPairing(u32),
+    #[error("Game number {0} does not exist on this board")]
+    InvalidGameNumber(u32),
+    #[error("The result of this board has been changed by someone else in the meantime")]
+    ResultConflict,
+    #[error("Color `{0}` is not valid, possible values are: white and black")]
+    InvalidColor(String),
+    #[error("Cannot override the color of player {0}, the player is not on a board this round")]
+    ColorOverrideNotPaired(u32),
+    #[error("Tiebreak `{0}` is not valid")]
+    InvalidTiebreak(String),
+    #[error("Invalid TRF file at line {line}: {message}")]
+    InvalidTrf { line: usize, message: String },
+    #[error("Maximum number of players `{0}` is not valid, must be between 2 and 1000")]
+    InvalidMaxPlayers(u32),
+    #[error("The tournament is full, at most {0} players can register")]
+    TournamentFull(u32),
+    #[error("Only draws can be marked as agreed, got `{0}`")]
+    InvalidAgreedDraw(String),
+    #[error("Players cannot register themselves in this tournament")]
+    SelfRegistrationDisabled,
+    #[error("You are already registered in this tournament")]
+    AlreadyRegistered,
+    #[error("No player with FIDE id `{0}` is stored")]
+    FidePlayerNotFound(i64),
+    #[error("Cannot merge player `{0}` into itself")]
+    MergeSamePlayer(u32),
+    #[error(
+        "Both players are registered in tournament `{0}`, remove one of the registrations before merging"
+    )]
+    MergeRegistrationConflict(u32),
+    #[error("Tournament role `{0}` is not valid, possible values are: owner, arbiter and viewer")]
+    InvalidTournamentRole(String),
+    #[error("`{0}` is too large")]
+    PayloadTooLarge(String),
+    #[error(
+        "Time control `{0}` is not valid, expected base+increment in minutes and seconds, e.g. 90+30 or 40/90+30:30+30"
+    )]
+    InvalidTimeControl(String),
+    #[error("Cannot regenerate round {0}, results have already been entered")]
+    RoundHasResults(u32),
+    #[error(
+        "Generated pairings do not cover every registered player exactly once, missing: {missing:?}, duplicated: {duplicated:?}"
+    )]
+    PairingInvariantViolated {
+        missing: Vec<u32>,
+        duplicated: Vec<u32>,
+    },
+    #[error("Player `{0}` has no FIDE id to refresh from")]
+    PlayerWithoutFideId(u32),
+    #[error("Board `{0}` does not exist in this round")]
+    BoardNotFound(u32),
+    #[error(
+        "Bye given to player {player_id} while the lower scored players {lower:?} could take it"
+    )]
+    ByeNotFromBottom { player_id: u32, lower: Vec<u32> },
+    #[error("Result `{0}` is not valid, possible values are: 1-0, 1/2-1/2, 0-1, 0-0 and 0F-0F")]
+    InvalidResult(String),
+    #[error("Agreed draws cannot be marked on boards with several games")]
+    AgreedDrawInMatch,
+    #[error(
+        "Result of round {round} board {board} changed since it was annulled, it cannot be restored"
+    )]
+    AnnulledResultChanged { round: u32, board: u32 },
+    #[error("Player `{0}` belongs to another account, players can only register themselves")]
+    NotOwnPlayer(u32),
+    #[error("Tiebreak order must list at least one tiebreak, each one at most once")]
+    InvalidTiebreakOrder,
+    #[error("`{field}` lists more than {max} entries")]
+    TooManyItems { field: String, max: usize },
+    #[error("Cannot fix pairing {white:?} - {black:?}, players are in different sections")]
+    FixedPairingAcrossSections { white: u32, black: u32 },
     #[error(transparent)]
     Database(#[from] sqlx::Error),
     #[error("unknown error")]
     Unknown,
 }
 
+#[derive(Debug)]
+pub struct ErrorInfo {
+    pub code: &'static str,
+    pub status: StatusCode,
+}
+
+// Status of an error code, each variant has its own constant so `AppError::info`
+// is the only place the status of an error is decided
+const INVALID_AUTH_HEADER: ErrorInfo = ErrorInfo {
+    code: "InvalidAuthHeader",
+    status: StatusCode::UNAUTHORIZED,
+};
+const TOKEN_INVALID: ErrorInfo = ErrorInfo {
+    code: "TokenInvalid",
+    status: StatusCode::UNAUTHORIZED,
+};
+const CANNOT_END_TOURNAMENT: ErrorInfo = ErrorInfo {
+    code: "CannotEndTournament",
+    status: StatusCode::BAD_REQUEST,
+};
+const INSUFFICIENT_PERMISSIONS: ErrorInfo = ErrorInfo {
+    code: "InsufficientPermissions",
+    status: StatusCode::UNAUTHORIZED,
+};
+const USERNAME_TAKEN: ErrorInfo = ErrorInfo {
+    code: "UsernameTaken",
+    status: StatusCode::BAD_REQUEST,
+};
+const LOGIN_FAILED: ErrorInfo = ErrorInfo {
+    code: "LoginFailed",
+    status: StatusCode::UNAUTHORIZED,
+};
+const JSON_UNKNOWN_ERROR: ErrorInfo = ErrorInfo {
+    code: "JsonUnknownError",
+    status: StatusCode::BAD_REQUEST,
+};
+const MISSING_CONTENT_TYPE: ErrorInfo = ErrorInfo {
+    code: "MissingContentType",
+    status: StatusCode::UNSUPPORTED_MEDIA_TYPE,
+};
+const JSON_SYNTAX_ERROR: ErrorInfo = ErrorInfo {
+    code: "JsonSyntaxError",
+    status: StatusCode::BAD_REQUEST,
+};
+const JSON_DATA_ERROR: ErrorInfo = ErrorInfo {
+    code: "JsonDataError",
+    status: StatusCode::BAD_REQUEST,
+};
+const FIDE_SCRAPE_FAILED: ErrorInfo = ErrorInfo {
+    code: "FideScrapeFailed",
+    status: StatusCode::INTERNAL_SERVER_ERROR,
+};
+const INSUFFICIENT_PLAYERS: ErrorInfo = ErrorInfo {
+    code: "InsufficientPlayers",
+    status: StatusCode::BAD_REQUEST,
+};
+const EMPTY_PAIRINGS_GENERATED: ErrorInfo = ErrorInfo {
+    code: "EmptyPairingsGenerated",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_PLAYER_STATUS: ErrorInfo = ErrorInfo {
+    code: "InvalidPlayerStatus",
+    status: StatusCode::BAD_REQUEST,
+};
+const DUPLICATE_PLAYER_RESULT: ErrorInfo = ErrorInfo {
+    code: "DuplicatePlayerResult",
+    status: StatusCode::BAD_REQUEST,
+};
+const ROUND_NOT_DONE: ErrorInfo = ErrorInfo {
+    code: "RoundNotDone",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_PLAYER_ID: ErrorInfo = ErrorInfo {
+    code: "InvalidPlayerId",
+    status: StatusCode::NOT_FOUND,
+};
+const INVALID_PLAYER_SCORE: ErrorInfo = ErrorInfo {
+    code: "InvalidPlayerScore",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_TIME_CATEGORY: ErrorInfo = ErrorInfo {
+    code: "InvalidTimeCategory",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_NUMBER_OF_ROUNDS: ErrorInfo = ErrorInfo {
+    code: "InvalidNumberOfRounds",
+    status: StatusCode::BAD_REQUEST,
+};
+const ROUND_NOT_FOUND: ErrorInfo = ErrorInfo {
+    code: "RoundNotFound",
+    status: StatusCode::NOT_FOUND,
+};
+const GAME_NOT_FOUND: ErrorInfo = ErrorInfo {
+    code: "GameNotFound",
+    status: StatusCode::NOT_FOUND,
+};
+const PLAYER_NOT_FOUND: ErrorInfo = ErrorInfo {
+    code: "PlayerNotFound",
+    status: StatusCode::NOT_FOUND,
+};
+const INSERT_GAME_HISTORY_SKIPS_ROUND: ErrorInfo = ErrorInfo {
+    code: "InsertGameHistorySkipsRound",
+    status: StatusCode::BAD_REQUEST,
+};
+const TOURNAMENT_ENDED: ErrorInfo = ErrorInfo {
+    code: "TournamentEnded",
+    status: StatusCode::BAD_REQUEST,
+};
+const TOURNAMENT_NOT_STARTED: ErrorInfo = ErrorInfo {
+    code: "TournamentNotStarted",
+    status: StatusCode::BAD_REQUEST,
+};
+const TOURNAMENT_NOT_FOUND: ErrorInfo = ErrorInfo {
+    code: "TournamentNotFound",
+    status: StatusCode::NOT_FOUND,
+};
+const INVALID_ROUND: ErrorInfo = ErrorInfo {
+    code: "InvalidRound",
+    status: StatusCode::NOT_FOUND,
+};
+const INVALID_BOARD_NUMBERING: ErrorInfo = ErrorInfo {
+    code: "InvalidBoardNumbering",
+    status: StatusCode::BAD_REQUEST,
+};
+const DUPLICATE_FIXED_PLAYER: ErrorInfo = ErrorInfo {
+    code: "DuplicateFixedPlayer",
+    status: StatusCode::BAD_REQUEST,
+};
+const FIXED_PAIRING_ALREADY_PLAYED: ErrorInfo = ErrorInfo {
+    code: "FixedPairingAlreadyPlayed",
+    status: StatusCode::BAD_REQUEST,
+};
+const REGISTRATION_CLOSED: ErrorInfo = ErrorInfo {
+    code: "RegistrationClosed",
+    status: StatusCode::BAD_REQUEST,
+};
+const CANNOT_REOPEN_REGISTRATION: ErrorInfo = ErrorInfo {
+    code: "CannotReopenRegistration",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_LATE_ENTRY_SCORE: ErrorInfo = ErrorInfo {
+    code: "InvalidLateEntryScore",
+    status: StatusCode::BAD_REQUEST,
+};
+const CANNOT_UNREGISTER_AFTER_START: ErrorInfo = ErrorInfo {
+    code: "CannotUnregisterAfterStart",
+    status: StatusCode::BAD_REQUEST,
+};
+const REGISTRATION_NOT_FOUND: ErrorInfo = ErrorInfo {
+    code: "RegistrationNotFound",
+    status: StatusCode::NOT_FOUND,
+};
+const USER_NOT_FOUND: ErrorInfo = ErrorInfo {
+    code: "UserNotFound",
+    status: StatusCode::NOT_FOUND,
+};
+const ORGANIZER_NOT_FOUND: ErrorInfo = ErrorInfo {
+    code: "OrganizerNotFound",
+    status: StatusCode::NOT_FOUND,
+};
+const RATE_LIMITED: ErrorInfo = ErrorInfo {
+    code: "RateLimited",
+    status: StatusCode::TOO_MANY_REQUESTS,
+};
+const ACCOUNT_LOCKED: ErrorInfo = ErrorInfo {
+    code: "AccountLocked",
+    status: StatusCode::TOO_MANY_REQUESTS,
+};
+const EMAIL_NOT_VERIFIED: ErrorInfo = ErrorInfo {
+    code: "EmailNotVerified",
+    status: StatusCode::FORBIDDEN,
+};
+const INVALID_VERIFICATION_TOKEN: ErrorInfo = ErrorInfo {
+    code: "InvalidVerificationToken",
+    status: StatusCode::BAD_REQUEST,
+};
+const SECTION_NOT_FOUND: ErrorInfo = ErrorInfo {
+    code: "SectionNotFound",
+    status: StatusCode::NOT_FOUND,
+};
+const PLAYOFF_NOT_AVAILABLE: ErrorInfo = ErrorInfo {
+    code: "PlayoffNotAvailable",
+    status: StatusCode::BAD_REQUEST,
+};
+const NO_PLAYOFF_NEEDED: ErrorInfo = ErrorInfo {
+    code: "NoPlayoffNeeded",
+    status: StatusCode::BAD_REQUEST,
+};
+const PLAYOFF_FINISHED: ErrorInfo = ErrorInfo {
+    code: "PlayoffFinished",
+    status: StatusCode::BAD_REQUEST,
+};
+const PLAYOFF_GAME_NOT_FOUND: ErrorInfo = ErrorInfo {
+    code: "PlayoffGameNotFound",
+    status: StatusCode::NOT_FOUND,
+};
+const PAIRING_FAILED: ErrorInfo = ErrorInfo {
+    code: "PairingFailed",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_VISIBILITY: ErrorInfo = ErrorInfo {
+    code: "InvalidVisibility",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_BYE_SCORE: ErrorInfo = ErrorInfo {
+    code: "InvalidByeScore",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_BYE_REQUEST: ErrorInfo = ErrorInfo {
+    code: "InvalidByeRequest",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_START_DATE: ErrorInfo = ErrorInfo {
+    code: "InvalidStartDate",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_GAMES_PER_PAIRING: ErrorInfo = ErrorInfo {
+    code: "InvalidGamesPerPairing",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_GAME_NUMBER: ErrorInfo = ErrorInfo {
+    code: "InvalidGameNumber",
+    status: StatusCode::BAD_REQUEST,
+};
+const RESULT_CONFLICT: ErrorInfo = ErrorInfo {
+    code: "ResultConflict",
+    status: StatusCode::CONFLICT,
+};
+const INVALID_COLOR: ErrorInfo = ErrorInfo {
+    code: "InvalidColor",
+    status: StatusCode::BAD_REQUEST,
+};
+const COLOR_OVERRIDE_NOT_PAIRED: ErrorInfo = ErrorInfo {
+    code: "ColorOverrideNotPaired",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_TIEBREAK: ErrorInfo = ErrorInfo {
+    code: "InvalidTiebreak",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_TRF: ErrorInfo = ErrorInfo {
+    code: "InvalidTrf",
+    status: StatusCode::BAD_REQUEST,
+};
+const INVALID_MAX_PLAYERS: ErrorInfo = ErrorInfo {
+    code: "InvalidMaxPlayers",
+    status: StatusCode::BAD_REQUEST,
+};
+const TOURNAMENT_FULL: ErrorInfo = ErrorInfo {
+    code: "TournamentFull",
+    status: StatusCode::CONFLICT,
+};
+const INVALID_AGREED_DRAW: ErrorInfo = ErrorInfo {
+    code: "InvalidAgreedDraw",
+    status: StatusCode::BAD_REQUEST,
+};
+const SELF_REGISTRATION_DISABLED: ErrorInfo = ErrorInfo {
+    code: "SelfRegistrationDisabled",
+    status: StatusCode::FORBIDDEN,
+};
+const ALREADY_REGISTERED: ErrorInfo = ErrorInfo {
+    code: "AlreadyRegistered",
+    status: StatusCode::CONFLICT,
+};
+const FIDE_PLAYER_NOT_FOUND: ErrorInfo = ErrorInfo {
+    code: "FidePlayerNotFound",
+    status: StatusCode::NOT_FOUND,
+};
+const MERGE_SAME_PLAYER: ErrorInfo = ErrorInfo {
+    code: "MergeSamePlayer",
+    status: StatusCode::BAD_REQUEST,
+};
+const MERGE_REGISTRATION_CONFLICT: ErrorInfo = ErrorInfo {
+    code: "MergeRegistrationConflict",
+    status: StatusCode::CONFLICT,
+};
+const INVALID_TOURNAMENT_ROLE: ErrorInfo = ErrorInfo {
+    code: "InvalidTournamentRole",
+    status: StatusCode::BAD_REQUEST,
+};
+const PAYLOAD_TOO_LARGE: ErrorInfo = ErrorInfo {
+    code: "PayloadTooLarge",
+    status: StatusCode::PAYLOAD_TOO_LARGE,
+};
+const INVALID_TIME_CONTROL: ErrorInfo = ErrorInfo {
+    code: "InvalidTimeControl",
+    status: StatusCode::BAD_REQUEST,
+};
+const ROUND_HAS_RESULTS: ErrorInfo = ErrorInfo {
+    code: "RoundHasResults",
+    status: StatusCode::CONFLICT,
+};
+const PAIRING_INVARIANT_VIOLATED: ErrorInfo = ErrorInfo {
+    code: "PairingInvariantViolated",
+    status: StatusCode::INTERNAL_SERVER_ERROR,
+};
+const PLAYER_WITHOUT_FIDE_ID: ErrorInfo = ErrorInfo {
+    code: "PlayerWithoutFideId",
+    status: StatusCode::BAD_REQUEST,
+};
+const BOARD_NOT_FOUND: ErrorInfo = ErrorInfo {
+    code: "BoardNotFound",
+    status: StatusCode::NOT_FOUND,
+};
+const BYE_NOT_FROM_BOTTOM: ErrorInfo = ErrorInfo {
+    code: "ByeNotFromBottom",
+    status: StatusCode::INTERNAL_SERVER_ERROR,
+};
+const INVALID_RESULT: ErrorInfo = ErrorInfo {
+    code: "InvalidResult",
+    status: StatusCode::BAD_REQUEST,
+};
+const AGREED_DRAW_IN_MATCH: ErrorInfo = ErrorInfo {
+    code: "AgreedDrawInMatch",
+    status: StatusCode::BAD_REQUEST,
+};
+const ANNULLED_RESULT_CHANGED: ErrorInfo = ErrorInfo {
+    code: "AnnulledResultChanged",
+    status: StatusCode::CONFLICT,
+};
+const NOT_OWN_PLAYER: ErrorInfo = ErrorInfo {
+    code: "NotOwnPlayer",
+    status: StatusCode::FORBIDDEN,
+};
+const INVALID_TIEBREAK_ORDER: ErrorInfo = ErrorInfo {
+    code: "InvalidTiebreakOrder",
+    status: StatusCode::BAD_REQUEST,
+};
+const TOO_MANY_ITEMS: ErrorInfo = ErrorInfo {
+    code: "TooManyItems",
+    status: StatusCode::BAD_REQUEST,
+};
+const FIXED_PAIRING_ACROSS_SECTIONS: ErrorInfo = ErrorInfo {
+    code: "FixedPairingAcrossSections",
+    status: StatusCode::BAD_REQUEST,
+};
+const DATABASE_ERROR: ErrorInfo = ErrorInfo {
+    code: "DatabaseError",
+    status: StatusCode::INTERNAL_SERVER_ERROR,
+};
+const UNKNOWN: ErrorInfo = ErrorInfo {
+    code: "Unknown",
+    status: StatusCode::INTERNAL_SERVER_ERROR,
+};
+
 impl AppError {
+    // Code and status of the error, they always come from here so they cannot drift
+    pub fn info(&self) -> &'static ErrorInfo {
+        match self {
+            AppError::RoundNotFound(_) => &ROUND_NOT_FOUND,
+            AppError::GameNotFound { round: _, game: _ } => &GAME_NOT_FOUND,
+            AppError::PlayerNotFound(_) => &PLAYER_NOT_FOUND,
+            AppError::InsertGameHistorySkipsRound => &INSERT_GAME_HISTORY_SKIPS_ROUND,
+            AppError::TournamentEnded => &TOURNAMENT_ENDED,
+            AppError::InvalidRound(_) => &INVALID_ROUND,
+            AppError::Unknown => &UNKNOWN,
+            AppError::Database(_) => &DATABASE_ERROR,
+            AppError::InvalidTimeCategory(_) => &INVALID_TIME_CATEGORY,
+            AppError::InvalidNumberOfRounds(_) => &INVALID_NUMBER_OF_ROUNDS,
+            AppError::DuplicatePlayerResult(_) => &DUPLICATE_PLAYER_RESULT,
+            AppError::InvalidPlayerId(_) => &INVALID_PLAYER_ID,
+            AppError::InvalidPlayerScore(_) => &INVALID_PLAYER_SCORE,
+            AppError::TournamentNotStarted => &TOURNAMENT_NOT_STARTED,
+            AppError::RoundNotDone => &ROUND_NOT_DONE,
+            AppError::InvalidPlayerStatus(_) => &INVALID_PLAYER_STATUS,
+            AppError::EmptyPairingsGenerated => &EMPTY_PAIRINGS_GENERATED,
+            AppError::InsufficientPlayers => &INSUFFICIENT_PLAYERS,
+            AppError::FideScrapeFailed(_) => &FIDE_SCRAPE_FAILED,
+            AppError::MissingContentType => &MISSING_CONTENT_TYPE,
+            AppError::JsonSyntaxError(_) => &JSON_SYNTAX_ERROR,
+            AppError::JsonDataError => &JSON_DATA_ERROR,
+            AppError::JsonUnknownError => &JSON_UNKNOWN_ERROR,
+            AppError::LoginFailed(_) => &LOGIN_FAILED,
+            AppError::UsernameTaken(_) => &USERNAME_TAKEN,
+            AppError::TournamentNotFound => &TOURNAMENT_NOT_FOUND,
+            AppError::InsufficientPermissions => &INSUFFICIENT_PERMISSIONS,
+            AppError::CannotEndTournament => &CANNOT_END_TOURNAMENT,
+            AppError::TokenInvalid => &TOKEN_INVALID,
+            AppError::InvalidAuthHeader => &INVALID_AUTH_HEADER,
+            AppError::UserNotFound(_) => &USER_NOT_FOUND,
+            AppError::OrganizerNotFound(_) => &ORGANIZER_NOT_FOUND,
+            AppError::InvalidBoardNumbering => &INVALID_BOARD_NUMBERING,
+            AppError::DuplicateFixedPlayer(_) => &DUPLICATE_FIXED_PLAYER,
+            AppError::FixedPairingAlreadyPlayed { white: _, black: _ } => {
+                &FIXED_PAIRING_ALREADY_PLAYED
+            }
+            AppError::RegistrationClosed => &REGISTRATION_CLOSED,
+            AppError::CannotReopenRegistration => &CANNOT_REOPEN_REGISTRATION,
+            AppError::InvalidLateEntryScore(_) => &INVALID_LATE_ENTRY_SCORE,
+            AppError::CannotUnregisterAfterStart => &CANNOT_UNREGISTER_AFTER_START,
+            AppError::RegistrationNotFound(_) => &REGISTRATION_NOT_FOUND,
+            AppError::RateLimited => &RATE_LIMITED,
+            AppError::AccountLocked(_) => &ACCOUNT_LOCKED,
+            AppError::EmailNotVerified => &EMAIL_NOT_VERIFIED,
+            AppError::InvalidVerificationToken => &INVALID_VERIFICATION_TOKEN,
+            AppError::SectionNotFound(_) => &SECTION_NOT_FOUND,
+            AppError::PlayoffNotAvailable => &PLAYOFF_NOT_AVAILABLE,
+            AppError::NoPlayoffNeeded => &NO_PLAYOFF_NEEDED,
+            AppError::PlayoffFinished => &PLAYOFF_FINISHED,
+            AppError::PlayoffGameNotFound(_) => &PLAYOFF_GAME_NOT_FOUND,
+            AppError::PairingFailed {
+                reason: _,
+                unpaired: _,
+            } => &PAIRING_FAILED,
+            AppError::InvalidVisibility(_) => &INVALID_VISIBILITY,
+            AppError::InvalidByeScore(_) => &INVALID_BYE_SCORE,
+            AppError::InvalidByeRequest(_) => &INVALID_BYE_REQUEST,
+            AppError::InvalidStartDate(_) => &INVALID_START_DATE,
+            AppError::InvalidGamesPerPairing(_) => &INVALID_GAMES_PER_PAIRING,
+            AppError::InvalidGameNumber(_) => &INVALID_GAME_NUMBER,
+            AppError::ResultConflict => &RESULT_CONFLICT,
+            AppError::InvalidColor(_) => &INVALID_COLOR,
+            AppError::ColorOverrideNotPaired(_) => &COLOR_OVERRIDE_NOT_PAIRED,
+            AppError::InvalidTiebreak(_) => &INVALID_TIEBREAK,
+            AppError::InvalidTrf {
+                line: _,
+                message: _,
+            } => &INVALID_TRF,
+            AppError::InvalidMaxPlayers(_) => &INVALID_MAX_PLAYERS,
+            AppError::TournamentFull(_) => &TOURNAMENT_FULL,
+            AppError::InvalidAgreedDraw(_) => &INVALID_AGREED_DRAW,
+            AppError::SelfRegistrationDisabled => &SELF_REGISTRATION_DISABLED,
+            AppError::AlreadyRegistered => &ALREADY_REGISTERED,
+            AppError::FidePlayerNotFound(_) => &FIDE_PLAYER_NOT_FOUND,
+            AppError::MergeSamePlayer(_) => &MERGE_SAME_PLAYER,
+            AppError::MergeRegistrationConflict(_) => &MERGE_REGISTRATION_CONFLICT,
+            AppError::InvalidTournamentRole(_) => &INVALID_TOURNAMENT_ROLE,
+            AppError::PayloadTooLarge(_) => &PAYLOAD_TOO_LARGE,
+            AppError::InvalidTimeControl(_) => &INVALID_TIME_CONTROL,
+            AppError::RoundHasResults(_) => &ROUND_HAS_RESULTS,
+            AppError::PairingInvariantViolated { .. } => &PAIRING_INVARIANT_VIOLATED,
+            AppError::PlayerWithoutFideId(_) => &PLAYER_WITHOUT_FIDE_ID,
+            AppError::BoardNotFound(_) => &BOARD_NOT_FOUND,
+            AppError::ByeNotFromBottom { .. } => &BYE_NOT_FROM_BOTTOM,
+            AppError::InvalidResult(_) => &INVALID_RESULT,
+            AppError::AgreedDrawInMatch => &AGREED_DRAW_IN_MATCH,
+            AppError::AnnulledResultChanged { .. } => &ANNULLED_RESULT_CHANGED,
+            AppError::NotOwnPlayer(_) => &NOT_OWN_PLAYER,
+            AppError::InvalidTiebreakOrder => &INVALID_TIEBREAK_ORDER,
+            AppError::TooManyItems { .. } => &TOO_MANY_ITEMS,
+            AppError::FixedPairingAcrossSections { white: _, black: _ } => {
+                &FIXED_PAIRING_ACROSS_SECTIONS
+            }
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.info().status
+    }
+
     pub fn code(&self) -> String {
+        self.info().code.to_string()
+    }
+
+    // Fields clients act on, sent next to the message so they do not have to parse it
+    pub fn details(&self) -> Option<serde_json::Value> {
         match self {
-            AppError::RoundNotFound(_) => String::from("RoundNotFound"),
-            AppError::GameNotFound { round: _, game: _ } => String::from("GameNotFound"),
-            AppError::PlayerNotFound(_) => String::from("PlayerNotFound"),
-            AppError::InsertGameHistorySkipsRound => String::from("InsertGameHistorySkipsRound"),
-            AppError::TournamentEnded => String::from("TournamentEnded"),
-            AppError::InvalidRound(_) => String::from("InvalidRound"),
-            AppError::Unknown => String::from("Unknown"),
-            AppError::Database(_) => String::from("DatabaseError"),
-            AppError::InvalidTimeCategory(_) => String::from("InvalidTimeCategory"),
-            AppError::InvalidNumberOfRounds(_) => String::from("InvalidNumberOfRounds"),
-            AppError::DuplicatePlayerResult(_) => String::from("DuplicatePlayerResult"),
-            AppError::InvalidPlayerId(_) => String::from("InvalidPlayerId"),
-            AppError::InvalidPlayerScore(_) => String::from("InvalidPlayerScore"),
-            AppError::TournamentNotStarted => String::from("TournamentNotStarted"),
-            AppError::RoundNotDone => String::from("RoundNotDone"),
-            AppError::InvalidPlayerStatus(_) => String::from("InvalidPlayerStatus"),
-            AppError::EmptyPairingsGenerated => String::from("EmptyPairingsGenerated"),
-            AppError::InsufficientPlayers => String::from("InsufficientPlayers"),
-            AppError::FideScrapeFailed(_) => String::from("FideScrapeFailed"),
-            AppError::MissingContentType => String::from("MissingContentType"),
-            AppError::JsonSyntaxError(_) => String::from("JsonSyntaxErro"),
-            AppError::JsonDataError => String::from("JsonDataError"),
-            AppError::JsonUnknownError => String::from("JsonUnknownError"),
-            AppError::LoginFailed(_) => String::from("LoginFailed"),
-            AppError::UsernameTaken(_) => String::from("UsernameTaken"),
-            AppError::TournamentNotFound => String::from("TournamentNotFound"),
-            AppError::InsufficientPermissions => String::from("InsufficientPermissions"),
-            AppError::CannotEndTournament => String::from("CannotEndTournament"),
-            AppError::TokenInvalid => String::from("TokenInvalid"),
-            AppError::InvalidAuthHeader => String::from("InvalidAuthHeader"),
+            AppError::PairingFailed { reason, unpaired } => Some(serde_json::json!({
+                "reason": reason,
+                "unpaired": unpaired,
+            })),
+            AppError::PairingInvariantViolated {
+                missing,
+                duplicated,
+            } => Some(serde_json::json!({
+                "missing": missing,
+                "duplicated": duplicated,
+            })),
+            AppError::ByeNotFromBottom { player_id, lower } => Some(serde_json::json!({
+                "playerId": player_id,
+                "lower": lower,
+            })),
+            _ => None,
+        }
+    }
+
+    // One error of every variant, listed at `GET /errors` with their message. Text fields hold
+    // placeholders named after the field. Add new variants here so their codes are checked
+    pub fn catalog() -> Vec<AppError> {
+        vec![
+            AppError::InvalidAuthHeader,
+            AppError::TokenInvalid,
+            AppError::CannotEndTournament,
+            AppError::InsufficientPermissions,
+            AppError::UsernameTaken("{0}".to_string()),
+            AppError::LoginFailed("{0}".to_string()),
+            AppError::JsonUnknownError,
+            AppError::MissingContentType,
+            AppError::JsonSyntaxError("{0}".to_string()),
+            AppError::JsonDataError,
+            AppError::FideScrapeFailed("{0}".to_string()),
+            AppError::InsufficientPlayers,
+            AppError::EmptyPairingsGenerated,
+            AppError::InvalidPlayerStatus("{0}".to_string()),
+            AppError::DuplicatePlayerResult(0),
+            AppError::RoundNotDone,
+            AppError::InvalidPlayerId(0),
+            AppError::InvalidPlayerScore("{0}".to_string()),
+            AppError::InvalidTimeCategory("{0}".to_string()),
+            AppError::InvalidNumberOfRounds(0),
+            AppError::RoundNotFound(0),
+            AppError::GameNotFound { round: 0, game: 0 },
+            AppError::PlayerNotFound(0),
+            AppError::InsertGameHistorySkipsRound,
+            AppError::TournamentEnded,
+            AppError::TournamentNotStarted,
+            AppError::TournamentNotFound,
+            AppError::InvalidRound(0),
+            AppError::InvalidBoardNumbering,
+            AppError::DuplicateFixedPlayer(0),
+            AppError::FixedPairingAlreadyPlayed { white: 0, black: 0 },
+            AppError::RegistrationClosed,
+            AppError::CannotReopenRegistration,
+            AppError::InvalidLateEntryScore(0),
+            AppError::CannotUnregisterAfterStart,
+            AppError::RegistrationNotFound(0),
+            AppError::UserNotFound("{0}".to_string()),
+            AppError::OrganizerNotFound(0),
+            AppError::RateLimited,
+            AppError::AccountLocked(0),
+            AppError::EmailNotVerified,
+            AppError::InvalidVerificationToken,
+            AppError::SectionNotFound(0),
+            AppError::PlayoffNotAvailable,
+            AppError::NoPlayoffNeeded,
+            AppError::PlayoffFinished,
+            AppError::PlayoffGameNotFound(0),
+            AppError::PairingFailed {
+                reason: "{reason}".to_string(),
+                unpaired: vec![],
+            },
+            AppError::InvalidVisibility("{0}".to_string()),
+            AppError::InvalidByeScore(0),
+            AppError::InvalidByeRequest(0),
+            AppError::InvalidStartDate(0),
+            AppError::InvalidGamesPerPairing(0),
+            AppError::InvalidGameNumber(0),
+            AppError::ResultConflict,
+            AppError::InvalidColor("{0}".to_string()),
+            AppError::ColorOverrideNotPaired(0),
+            AppError::InvalidTiebreak("{0}".to_string()),
+            AppError::InvalidTrf {
+                line: 0,
+                message: "{message}".to_string(),
+            },
+            AppError::InvalidMaxPlayers(0),
+            AppError::TournamentFull(0),
+            AppError::InvalidAgreedDraw("{0}".to_string()),
+            AppError::SelfRegistrationDisabled,
+            AppError::AlreadyRegistered,
+            AppError::FidePlayerNotFound(0),
+            AppError::MergeSamePlayer(0),
+            AppError::MergeRegistrationConflict(0),
+            AppError::InvalidTournamentRole("{0}".to_string()),
+            AppError::PayloadTooLarge("{0}".to_string()),
+            AppError::InvalidTimeControl("{0}".to_string()),
+            AppError::RoundHasResults(0),
+            AppError::PairingInvariantViolated {
+                missing: vec![],
+                duplicated: vec![],
+            },
+            AppError::PlayerWithoutFideId(0),
+            AppError::BoardNotFound(0),
+            AppError::ByeNotFromBottom {
+                player_id: 0,
+                lower: vec![],
+            },
+            AppError::InvalidResult("{0}".to_string()),
+            AppError::AgreedDrawInMatch,
+            AppError::AnnulledResultChanged { round: 0, board: 0 },
+            AppError::NotOwnPlayer(0),
+            AppError::InvalidTiebreakOrder,
+            AppError::TooManyItems {
+                field: "{field}".to_string(),
+                max: 0,
+            },
+            AppError::FixedPairingAcrossSections { white: 0, black: 0 },
+            AppError::Database(sqlx::Error::RowNotFound),
+            AppError::Unknown,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::AppError;
+
+    #[test]
+    fn test_error_codes() {
+        let mut codes = HashSet::new();
+        for error in AppError::catalog() {
+            let code = error.code();
+            assert!(!code.is_empty());
+            // Codes are PascalCase and start with the variant name
+            assert!(code.chars().next().unwrap().is_ascii_uppercase());
+            assert!(code.chars().all(|c| c.is_ascii_alphanumeric()));
+            let debug = format!("{:?}", error);
+            let variant = debug
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .next()
+                .unwrap();
+            assert!(
+                code.starts_with(variant),
+                "code `{}` does not match variant `{}`",
+                code,
+                variant
+            );
+            assert!(codes.insert(code.clone()), "duplicate code `{}`", code);
+        }
+    }
+
+    #[test]
+    fn test_error_catalog() {
+        let messages: Vec<String> = AppError::catalog()
+            .iter()
+            .map(|error| error.to_string())
+            .collect();
+        assert!(messages.contains(&"Username already exists: {0}".to_string()));
+        assert!(messages.contains(&"Invalid TRF file at line 0: {message}".to_string()));
+        assert!(messages.contains(&"The authentication header is missing or invalid".to_string()));
+    }
+
+    #[test]
+    fn test_error_details() {
+        let error = AppError::PairingFailed {
+            reason: "no valid pairing".to_string(),
+            unpaired: vec![3, 7],
+        };
+        let details = error.details().unwrap();
+        assert_eq!(details["reason"], "no valid pairing");
+        assert_eq!(details["unpaired"], serde_json::json!([3, 7]));
+        assert!(AppError::TournamentNotFound.details().is_none());
+    }
+
+    #[test]
+    fn test_error_info() {
+        for error in AppError::catalog() {
+            if matches!(error, AppError::Unknown) {
+                continue;
+            }
+            let info = error.info();
+            // Only the unknown error itself maps to its constant
+            assert_ne!(
+                info.code, "Unknown",
+                "{:?} maps to the unknown error",
+                error
+            );
+            assert_eq!(error.code(), info.code);
+            assert_eq!(error.status(), info.status);
         }
     }
 }
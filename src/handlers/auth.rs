@@ -1,23 +1,46 @@
+use std::sync::Arc;
+
 use argon2::{Argon2, PasswordHash, PasswordVerifier};
-use axum::{Router, extract::State, response::IntoResponse, routing::post};
+use axum::{
+    Router,
+    extract::{Query, State},
+    middleware,
+    response::IntoResponse,
+    routing::{get, post},
+};
 use sqlx::SqlitePool;
 
 use crate::{
     AppState,
-    auth::{hasher::hash_password, jwt::create_token},
+    auth::{
+        email::EmailSender,
+        jwt::{create_token, token_duration},
+        lockout::LoginAttempts,
+    },
     errors::AppError,
-    payloads::{LoginPayload, NewUser},
-    repositories::auth_repo::{self, get_user},
+    payloads::{LoginPayload, NewUser, VerifyEmail},
+    rate_limit::ip_rate_limit,
+    repositories::auth_repo::get_user,
     responses::{AppResponse, Json, SuccessResponse},
+    services::auth_service,
 };
 
 async fn login(
     State(pool): State<SqlitePool>,
+    State(attempts): State<Arc<LoginAttempts>>,
     Json(payload): Json<LoginPayload>,
 ) -> impl IntoResponse {
+    if let Some(retry_after) = attempts.locked_for(&payload.username) {
+        return AppError::AccountLocked(retry_after.as_secs().max(1)).into_response();
+    }
     let user = match get_user(&pool, &payload.username).await {
         Ok(user) => user,
-        Err(e) => return e.into_response(),
+        Err(e) => {
+            if let AppError::LoginFailed(_) = e {
+                attempts.record_failure(&payload.username);
+            }
+            return e.into_response();
+        }
     };
     let parsed_hash = match PasswordHash::new(&user.password_hash) {
         Ok(hash) => hash,
@@ -27,13 +50,15 @@ async fn login(
         .verify_password(payload.password.as_bytes(), &parsed_hash)
         .is_err()
     {
+        attempts.record_failure(&payload.username);
         return AppError::LoginFailed("Invalid credentials".to_string()).into_response();
     }
+    attempts.reset(&payload.username);
     let token = match create_token(
         user.id,
         payload.username,
         user.role.clone(),
-        chrono::Duration::hours(24),
+        token_duration(&user.role),
     ) {
         Ok(t) => t,
         Err(_) => return AppError::Unknown.into_response(),
@@ -49,32 +74,41 @@ async fn login(
 
 async fn create_user(
     State(pool): State<SqlitePool>,
+    State(mailer): State<Arc<dyn EmailSender>>,
     Json(payload): Json<NewUser>,
 ) -> impl IntoResponse {
-    match get_user(&pool, &payload.username).await {
-        Err(AppError::LoginFailed(_)) => {
-            let password_hash = match hash_password(&payload.password) {
-                Ok(hash) => hash,
-                Err(e) => return e.into_response(),
-            };
-            match auth_repo::create_user(&pool, &payload.username, &password_hash, payload.email)
-                .await
-            {
-                Ok(id) => AppResponse::Success {
-                    payload: SuccessResponse::UserCreated { id },
-                }
-                .into_response(),
-                Err(_) => AppError::Unknown.into_response(),
-            }
+    match auth_service::create_user(&pool, mailer.as_ref(), payload).await {
+        Ok(id) => AppResponse::Success {
+            payload: SuccessResponse::UserCreated { id },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn verify_email(
+    State(pool): State<SqlitePool>,
+    Query(query): Query<VerifyEmail>,
+) -> impl IntoResponse {
+    match auth_service::verify_email(&pool, &query.token).await {
+        Ok(_) => AppResponse::Success {
+            payload: SuccessResponse::EmailVerified,
         }
-        Ok(_) => return AppError::UsernameTaken(payload.username).into_response(),
-        _ => return AppError::Unknown.into_response(),
+        .into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 pub fn routes(state: AppState) -> Router {
     Router::new()
-        .route("/login", post(login))
+        .route(
+            "/login",
+            post(login).route_layer(middleware::from_fn_with_state(
+                state.login_limiter.clone(),
+                ip_rate_limit,
+            )),
+        )
         .route("/register", post(create_user))
+        .route("/verify", get(verify_email))
         .with_state(state)
 }
@@ -0,0 +1,42 @@
+use axum::{Router, http::header, response::IntoResponse, routing::get};
+
+use crate::{
+    errors::AppError,
+    responses::{AppResponse, SuccessResponse},
+};
+
+// Hand written OpenAPI 3 document, keep it in sync with the routes
+const OPENAPI_DOCUMENT: &str = include_str!("../../openapi.json");
+
+async fn openapi() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        OPENAPI_DOCUMENT,
+    )
+}
+
+async fn errors() -> AppResponse {
+    AppResponse::Success {
+        payload: SuccessResponse::Errors {
+            errors: AppError::catalog().iter().map(Into::into).collect(),
+        },
+    }
+}
+
+pub fn routes() -> Router {
+    Router::new()
+        .route("/openapi.json", get(openapi))
+        .route("/errors", get(errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OPENAPI_DOCUMENT;
+
+    #[test]
+    fn test_openapi_document_is_valid_json() {
+        let doc: serde_json::Value =
+            serde_json::from_str(OPENAPI_DOCUMENT).expect("openapi.json is not valid json");
+        assert!(doc["paths"]["/tournaments/{id}/pair"].is_object());
+    }
+}
@@ -1,3 +1,4 @@
 pub mod auth;
+pub mod docs;
 pub mod players;
 pub mod tournaments;
@@ -1,19 +1,26 @@
 use crate::{
     AppState,
-    auth::extractor::CurrentUser,
+    auth::extractor::{CurrentUser, require_user},
     errors::AppError,
-    payloads::NewPlayer,
+    models::tournament::TimeCategory,
+    payloads::{FideLookup, MergePlayers, NewPlayer, Pagination},
+    rate_limit::{global_rate_limit, path_id_rate_limit},
     repositories::player_repo,
-    responses::{AppResponse, Json, SuccessResponse},
-    services::player_service::{self, check_fide_player_exists},
+    responses::{AppResponse, FidePlayer, Json, SuccessResponse},
+    services::{
+        player_service::{self, check_fide_player_exists},
+        rating_provider::RatingProvider,
+    },
 };
 use axum::{
     Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    middleware,
     response::IntoResponse,
     routing::{get, post},
 };
 use sqlx::SqlitePool;
+use std::sync::Arc;
 
 async fn create_player(
     State(pool): State<SqlitePool>,
@@ -49,10 +56,16 @@ async fn list_players(State(pool): State<SqlitePool>) -> impl IntoResponse {
 
 async fn get_fide_player(
     Path(fide_id): Path<i64>,
+    Query(lookup): Query<FideLookup>,
     State(pool): State<sqlx::Pool<sqlx::Sqlite>>,
-    State(client): State<reqwest::Client>,
+    State(provider): State<Arc<dyn RatingProvider>>,
 ) -> impl IntoResponse {
-    match check_fide_player_exists(&pool, fide_id, &client).await {
+    let category = match lookup.category.as_ref().map(TimeCategory::try_from) {
+        Some(Ok(category)) => Some(category),
+        Some(Err(e)) => return e.into_response(),
+        None => None,
+    };
+    match check_fide_player_exists(&pool, fide_id, provider.as_ref()).await {
         Ok(Some(player_service::FidePlayerCheck::Exists(id))) => AppResponse::Success {
             payload: SuccessResponse::PlayerExists { id, fide_id },
         }
@@ -62,10 +75,100 @@ async fn get_fide_player(
         }
         .into_response(),
         Err(e) => e.into_response(),
-        Ok(None) => match player_service::scrape_fide_player(&client, fide_id).await {
-            Ok(player) => Into::<AppResponse>::into(player).into_response(),
-            Err(e) => e.into_response(),
-        },
+        Ok(None) => {
+            let player = match provider.fetch(fide_id).await {
+                Ok(player) => player,
+                Err(e) => return e.into_response(),
+            };
+            match player_service::possible_duplicates(&pool, &player).await {
+                Ok(possible_duplicates) => AppResponse::Success {
+                    payload: SuccessResponse::FidePlayer {
+                        player: FidePlayer {
+                            rating: player.rating_for(category),
+                            ..player
+                        },
+                        possible_duplicates,
+                    },
+                }
+                .into_response(),
+                Err(e) => e.into_response(),
+            }
+        }
+    }
+}
+
+async fn refresh_fide_player(
+    Path(player_id): Path<u32>,
+    State(pool): State<SqlitePool>,
+    State(provider): State<Arc<dyn RatingProvider>>,
+    CurrentUser(_): CurrentUser,
+) -> impl IntoResponse {
+    match player_service::refresh_fide_player(&pool, player_id, provider.as_ref()).await {
+        Ok(player) => AppResponse::Success {
+            payload: SuccessResponse::PlayerUpdated { player },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_stored_fide_player(
+    Path(fide_id): Path<i64>,
+    State(pool): State<SqlitePool>,
+) -> impl IntoResponse {
+    match player_service::get_player_by_fide_id(&pool, fide_id).await {
+        Ok(player) => AppResponse::Success {
+            payload: SuccessResponse::Player { player },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn merge_players(
+    State(pool): State<SqlitePool>,
+    CurrentUser(claims): CurrentUser,
+    Json(payload): Json<MergePlayers>,
+) -> impl IntoResponse {
+    let (keep_id, remove_id) = (payload.keep_id, payload.remove_id);
+    match player_service::merge_players(&pool, &claims, keep_id, remove_id).await {
+        Ok(registrations) => AppResponse::Success {
+            payload: SuccessResponse::PlayersMerged {
+                keep_id,
+                remove_id,
+                registrations,
+            },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn list_player_games(
+    Path(player_id): Path<u32>,
+    Query(pagination): Query<Pagination>,
+    State(pool): State<SqlitePool>,
+) -> impl IntoResponse {
+    match player_service::list_player_games(&pool, player_id, &pagination).await {
+        Ok(games) => AppResponse::Success {
+            payload: SuccessResponse::PlayerGames {
+                games,
+                page: pagination.page(),
+                page_size: pagination.page_size(),
+            },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn head_to_head(
+    Path((player_id, opponent_id)): Path<(u32, u32)>,
+    State(pool): State<SqlitePool>,
+) -> impl IntoResponse {
+    match player_service::head_to_head(&pool, player_id, opponent_id).await {
+        Ok(h2h) => Into::<AppResponse>::into(h2h).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -73,6 +176,31 @@ pub fn routes(state: AppState) -> Router {
     Router::new()
         .route("/", post(create_player))
         .route("/", get(list_players))
-        .route("/fide/{fide_id}", get(get_fide_player))
+        .route(
+            "/fide/{fide_id}",
+            get(get_fide_player).route_layer(middleware::from_fn_with_state(
+                state.fide_limiter.clone(),
+                global_rate_limit,
+            )),
+        )
+        .route("/by-fide/{fide_id}", get(get_stored_fide_player))
+        // Each player once a minute, within the global FIDE limit. Authenticated first, anonymous
+        // requests do not count
+        .route(
+            "/{player_id}/refresh-fide",
+            post(refresh_fide_player)
+                .route_layer(middleware::from_fn_with_state(
+                    state.fide_limiter.clone(),
+                    global_rate_limit,
+                ))
+                .route_layer(middleware::from_fn_with_state(
+                    state.refresh_limiter.clone(),
+                    path_id_rate_limit,
+                ))
+                .route_layer(middleware::from_fn(require_user)),
+        )
+        .route("/{player_id}/games", get(list_player_games))
+        .route("/merge", post(merge_players))
+        .route("/{player_id}/vs/{opponent_id}", get(head_to_head))
         .with_state(state)
 }
@@ -1,18 +1,24 @@
 use axum::{
     Router,
-    extract::{Path, State},
-    response::IntoResponse,
-    routing::{get, post},
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{Html, IntoResponse},
+    routing::{delete, get, post},
 };
+use chrono::DateTime;
 use sqlx::SqlitePool;
 
 use crate::{
     AppState,
-    auth::extractor::CurrentUser,
+    auth::extractor::{CurrentUser, OptionalCurrentUser},
     errors::AppError,
     models::tournament::Tournament,
-    payloads::{NewRegistration, NewTournament, NextPairings, PlayerStatusPayload, RoundResult},
-    responses::{AppResponse, Json, SuccessResponse},
+    payloads::{
+        CloneQuery, NewOrganizer, NewRegistration, NewSection, NewTournament, NextPairings,
+        PlayerStatusPayload, PlayoffResult, RegistrationsQuery, RoundResult, SimulatedResults,
+        StandingsQuery, TimeControlPayload, VisibilityPayload,
+    },
+    responses::{AppResponse, Json, PlayerAgreedDraws, SuccessResponse},
     services::tournament_service,
 };
 
@@ -31,14 +37,65 @@ async fn register_player(
     }
 }
 
+async fn unregister_player(
+    State(pool): State<SqlitePool>,
+    Path((tournament_id, registration_id)): Path<(u32, u32)>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::unregister_player(&pool, tournament_id, claims, registration_id).await
+    {
+        Ok(()) => AppResponse::Success {
+            payload: SuccessResponse::PlayerUnregistered { registration_id },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
 async fn create_tournament(
     State(pool): State<SqlitePool>,
     CurrentUser(claims): CurrentUser,
     Json(payload): Json<NewTournament>,
 ) -> impl IntoResponse {
-    match tournament_service::create_tournament(&pool, claims.sub, payload).await {
+    match tournament_service::create_tournament(&pool, &claims, payload).await {
+        Ok((id, warnings)) => AppResponse::Success {
+            payload: SuccessResponse::TournamentCreated { id, warnings },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn clone_tournament(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    Query(query): Query<CloneQuery>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    let registrations = query.registrations.unwrap_or(false);
+    match tournament_service::clone_tournament(&pool, tournament_id, &claims, registrations).await {
+        Ok(id) => AppResponse::Success {
+            payload: SuccessResponse::TournamentCreated {
+                id,
+                warnings: Vec::new(),
+            },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn import_trf(
+    State(pool): State<SqlitePool>,
+    CurrentUser(claims): CurrentUser,
+    body: String,
+) -> impl IntoResponse {
+    match tournament_service::import_trf(&pool, &claims, &body).await {
         Ok(id) => AppResponse::Success {
-            payload: SuccessResponse::TournamentCreated { id },
+            payload: SuccessResponse::TournamentCreated {
+                id,
+                warnings: Vec::new(),
+            },
         }
         .into_response(),
         Err(e) => e.into_response(),
@@ -51,8 +108,9 @@ async fn generate_next_round_pairings(
     CurrentUser(claims): CurrentUser,
     Json(payload): Json<NextPairings>,
 ) -> impl IntoResponse {
+    let user_id = claims.sub;
     match tournament_service::generate_next_pairings(&pool, id, claims, payload).await {
-        Ok(pairings) => match pairings.commit(&pool).await {
+        Ok(pairings) => match pairings.commit(&pool, id, user_id).await {
             Ok(_) => Into::<AppResponse>::into(pairings).into_response(),
             Err(e) => Into::<AppError>::into(e).into_response(),
         },
@@ -60,17 +118,180 @@ async fn generate_next_round_pairings(
     }
 }
 
-async fn get_tournament(Path(id): Path<u32>, State(pool): State<SqlitePool>) -> impl IntoResponse {
-    match tournament_service::read_tournament(&pool, id).await {
+async fn regenerate_current_round(
+    State(pool): State<SqlitePool>,
+    Path(id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+    Json(payload): Json<NextPairings>,
+) -> impl IntoResponse {
+    let user_id = claims.sub;
+    match tournament_service::regenerate_round(&pool, id, claims, payload).await {
+        Ok(pairings) => match pairings.replace(&pool, id, user_id).await {
+            Ok(_) => Into::<AppResponse>::into(pairings).into_response(),
+            Err(e) => e.into_response(),
+        },
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_tournament(
+    Path(id): Path<u32>,
+    State(pool): State<SqlitePool>,
+    OptionalCurrentUser(claims): OptionalCurrentUser,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    match tournament_service::read_visible_tournament(&pool, id, claims.as_ref()).await {
         Ok(tdata) => {
+            let updated_at = tdata.tournament.updated_at;
+            // Organizers get a different body for the same ETag
+            let cache_headers = [
+                (header::ETAG, etag(updated_at)),
+                (header::LAST_MODIFIED, http_date(updated_at)),
+                (header::VARY, header::AUTHORIZATION.to_string()),
+            ];
+            if is_not_modified(&headers, updated_at) {
+                return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+            }
+            let organizer_data =
+                match tournament_service::read_organizer_data(&pool, id, claims.as_ref()).await {
+                    Ok(data) => data,
+                    Err(e) => return e.into_response(),
+                };
             let tournament: Tournament = tdata.into();
             let response: AppResponse = tournament.into();
-            response.into_response()
+            (cache_headers, response.with_organizer_data(organizer_data)).into_response()
         }
         Err(e) => Into::<AppError>::into(e).into_response(),
     }
 }
 
+fn etag(updated_at: u32) -> String {
+    format!("\"{}\"", updated_at)
+}
+
+fn http_date(updated_at: u32) -> String {
+    DateTime::from_timestamp(updated_at as i64, 0)
+        .unwrap_or_default()
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+// Polling clients send back the ETag or Last-Modified of their copy, If-None-Match wins
+// when both are present
+fn is_not_modified(headers: &HeaderMap, updated_at: u32) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        let Ok(if_none_match) = if_none_match.to_str() else {
+            return false;
+        };
+        let current = etag(updated_at);
+        return if_none_match
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == current);
+    }
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+        .is_some_and(|since| i64::from(updated_at) <= since.timestamp())
+}
+
+async fn get_current_round(
+    Path(id): Path<u32>,
+    State(pool): State<SqlitePool>,
+    OptionalCurrentUser(claims): OptionalCurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::current_round(&pool, id, claims.as_ref()).await {
+        Ok(round) => Into::<AppResponse>::into(round).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_standings(
+    Path(id): Path<u32>,
+    Query(query): Query<StandingsQuery>,
+    State(pool): State<SqlitePool>,
+    OptionalCurrentUser(claims): OptionalCurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::standings(&pool, id, query.round, claims.as_ref()).await {
+        Ok((round, standings, updated_at)) => AppResponse::Success {
+            payload: SuccessResponse::Standings {
+                round,
+                standings,
+                updated_at,
+            },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_registrations(
+    Path(id): Path<u32>,
+    Query(query): Query<RegistrationsQuery>,
+    State(pool): State<SqlitePool>,
+    OptionalCurrentUser(claims): OptionalCurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::registrations(&pool, id, query.active, claims.as_ref()).await {
+        Ok(registrations) => AppResponse::Success {
+            payload: SuccessResponse::Registrations { registrations },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn simulate_standings(
+    State(pool): State<SqlitePool>,
+    Path(id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+    Json(payload): Json<SimulatedResults>,
+) -> impl IntoResponse {
+    match tournament_service::simulate_standings(&pool, id, claims, payload).await {
+        Ok((round, standings)) => AppResponse::Success {
+            payload: SuccessResponse::SimulatedStandings { round, standings },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_standings_html(
+    Path(id): Path<u32>,
+    State(pool): State<SqlitePool>,
+    OptionalCurrentUser(claims): OptionalCurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::standings_html(&pool, id, claims.as_ref()).await {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_round_colors(
+    Path((id, round)): Path<(u32, u32)>,
+    State(pool): State<SqlitePool>,
+    OptionalCurrentUser(claims): OptionalCurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::color_decisions(&pool, id, round, claims.as_ref()).await {
+        Ok(boards) => AppResponse::Success {
+            payload: SuccessResponse::RoundColors { round, boards },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_pending_boards(
+    Path((id, round)): Path<(u32, u32)>,
+    State(pool): State<SqlitePool>,
+    OptionalCurrentUser(claims): OptionalCurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::pending_boards(&pool, id, round, claims.as_ref()).await {
+        Ok(boards) => Into::<AppResponse>::into(boards).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
 async fn list_tournaments(State(pool): State<SqlitePool>) -> impl IntoResponse {
     match tournament_service::list_tournaments(&pool).await {
         Ok(tournaments) => Into::<AppResponse>::into(tournaments).into_response(),
@@ -125,15 +346,342 @@ async fn end_tournament(
     }
 }
 
+async fn add_organizer(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+    Json(payload): Json<NewOrganizer>,
+) -> impl IntoResponse {
+    let username = payload.username.clone();
+    match tournament_service::add_organizer(&pool, tournament_id, claims, payload).await {
+        Ok(user_id) => AppResponse::Success {
+            payload: SuccessResponse::OrganizerAdded { user_id, username },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn remove_organizer(
+    State(pool): State<SqlitePool>,
+    Path((tournament_id, user_id)): Path<(u32, u32)>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::remove_organizer(&pool, tournament_id, claims, user_id).await {
+        Ok(()) => AppResponse::Success {
+            payload: SuccessResponse::OrganizerRemoved { user_id },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn annul_player_results(
+    State(pool): State<SqlitePool>,
+    Path((tournament_id, registration_id)): Path<(u32, u32)>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::annul_player_results(&pool, tournament_id, claims, registration_id)
+        .await
+    {
+        Ok(games) => AppResponse::Success {
+            payload: SuccessResponse::ResultsAnnulled {
+                registration_id,
+                games,
+            },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn restore_player_results(
+    State(pool): State<SqlitePool>,
+    Path((tournament_id, registration_id)): Path<(u32, u32)>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::restore_player_results(&pool, tournament_id, claims, registration_id)
+        .await
+    {
+        Ok(games) => AppResponse::Success {
+            payload: SuccessResponse::ResultsRestored {
+                registration_id,
+                games,
+            },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_audit_log(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::read_audit_log(&pool, tournament_id, claims).await {
+        Ok(entries) => AppResponse::Success {
+            payload: SuccessResponse::AuditLog { entries },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_stats(
+    Path(id): Path<u32>,
+    State(pool): State<SqlitePool>,
+    OptionalCurrentUser(claims): OptionalCurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::stats(&pool, id, claims.as_ref()).await {
+        Ok(stats) => Into::<AppResponse>::into(stats).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_agreed_draws(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::read_agreed_draws(&pool, tournament_id, claims).await {
+        Ok(draws) => AppResponse::Success {
+            payload: SuccessResponse::AgreedDraws {
+                players: draws.into_iter().map(PlayerAgreedDraws::from).collect(),
+            },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_pairing_constraints(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::pairing_constraints(&pool, tournament_id, claims).await {
+        Ok(constraints) => AppResponse::Success {
+            payload: SuccessResponse::PairingConstraints { constraints },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_pairing_graph(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::pairing_graph(&pool, tournament_id, claims).await {
+        Ok(graph) => AppResponse::Success {
+            payload: SuccessResponse::PairingGraph { graph },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn get_bye_prediction(
+    Path(id): Path<u32>,
+    State(pool): State<SqlitePool>,
+    OptionalCurrentUser(claims): OptionalCurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::bye_prediction(&pool, id, claims.as_ref()).await {
+        Ok((round, byes)) => AppResponse::Success {
+            payload: SuccessResponse::ByePrediction { round, byes },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn create_playoff_round(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::create_playoff_round(&pool, tournament_id, claims).await {
+        Ok((round, games)) => AppResponse::Success {
+            payload: SuccessResponse::PlayoffRoundCreated { round, games },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn update_playoff_result(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+    Json(payload): Json<PlayoffResult>,
+) -> impl IntoResponse {
+    match tournament_service::update_playoff_result(&pool, tournament_id, claims, &payload).await {
+        Ok(()) => AppResponse::Success {
+            payload: SuccessResponse::PlayoffResultUpdated {
+                game_id: payload.game_id,
+                game_result: payload.result,
+            },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn create_section(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+    Json(payload): Json<NewSection>,
+) -> impl IntoResponse {
+    match tournament_service::create_section(&pool, tournament_id, claims, payload).await {
+        Ok(id) => AppResponse::Success {
+            payload: SuccessResponse::SectionCreated { id },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn close_registration(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::set_registration_open(&pool, tournament_id, claims, false).await {
+        Ok(()) => AppResponse::Success {
+            payload: SuccessResponse::RegistrationOpenUpdated {
+                registration_open: false,
+            },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn open_registration(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+) -> impl IntoResponse {
+    match tournament_service::set_registration_open(&pool, tournament_id, claims, true).await {
+        Ok(()) => AppResponse::Success {
+            payload: SuccessResponse::RegistrationOpenUpdated {
+                registration_open: true,
+            },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn update_visibility(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+    Json(payload): Json<VisibilityPayload>,
+) -> impl IntoResponse {
+    match tournament_service::set_visibility(&pool, tournament_id, claims, &payload).await {
+        Ok(visibility) => AppResponse::Success {
+            payload: SuccessResponse::VisibilityUpdated {
+                visibility: visibility.to_string(),
+            },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn update_time_control(
+    State(pool): State<SqlitePool>,
+    Path(tournament_id): Path<u32>,
+    CurrentUser(claims): CurrentUser,
+    Json(payload): Json<TimeControlPayload>,
+) -> impl IntoResponse {
+    match tournament_service::set_time_control(&pool, tournament_id, claims, payload).await {
+        Ok(time_control) => AppResponse::Success {
+            payload: SuccessResponse::TimeControlUpdated { time_control },
+        }
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
 pub fn routes(state: AppState) -> Router {
     Router::new()
         .route("/", get(list_tournaments))
         .route("/", post(create_tournament))
+        .route("/import/trf", post(import_trf))
         .route("/{id}", get(get_tournament))
+        .route("/{id}/clone", post(clone_tournament))
+        .route("/{id}/rounds/current", get(get_current_round))
+        .route(
+            "/{id}/rounds/current/regenerate",
+            post(regenerate_current_round),
+        )
+        .route("/{id}/rounds/{round}/pending", get(get_pending_boards))
+        .route("/{id}/rounds/{round}/colors", get(get_round_colors))
+        .route("/{id}/standings", get(get_standings))
+        .route("/{id}/standings/simulate", post(simulate_standings))
+        .route("/{id}/standings.html", get(get_standings_html))
         .route("/{id}/pair", post(generate_next_round_pairings))
+        .route("/{id}/pair/constraints", get(get_pairing_constraints))
+        .route("/{id}/pair/graph", get(get_pairing_graph))
+        .route("/{id}/pair/bye-prediction", get(get_bye_prediction))
         .route("/{id}/register", post(register_player))
+        .route("/{id}/registrations", get(get_registrations))
+        .route(
+            "/{id}/registrations/{registration_id}",
+            delete(unregister_player),
+        )
         .route("/{id}/result", post(update_game_result))
         .route("/{id}/end", post(end_tournament))
         .route("/{id}/player-status", post(update_player_status))
+        .route("/{id}/organizers", post(add_organizer))
+        .route("/{id}/organizers/{user_id}", delete(remove_organizer))
+        .route("/{id}/audit", get(get_audit_log))
+        .route("/{id}/stats", get(get_stats))
+        .route("/{id}/stats/agreed-draws", get(get_agreed_draws))
+        .route("/{id}/annul/{registration_id}", post(annul_player_results))
+        .route(
+            "/{id}/annul/{registration_id}",
+            delete(restore_player_results),
+        )
+        .route("/{id}/sections", post(create_section))
+        .route("/{id}/playoff", post(create_playoff_round))
+        .route("/{id}/playoff/result", post(update_playoff_result))
+        .route("/{id}/close-registration", post(close_registration))
+        .route("/{id}/open-registration", post(open_registration))
+        .route("/{id}/visibility", post(update_visibility))
+        .route("/{id}/time-control", post(update_time_control))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue, header};
+
+    use super::{http_date, is_not_modified};
+
+    #[test]
+    fn test_conditional_get() {
+        let updated_at = 1_760_000_000;
+        let mut headers = HeaderMap::new();
+        assert!(!is_not_modified(&headers, updated_at));
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_str(&http_date(updated_at)).unwrap(),
+        );
+        assert!(is_not_modified(&headers, updated_at));
+        assert!(!is_not_modified(&headers, updated_at + 1));
+        // If-None-Match takes precedence over If-Modified-Since
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"1\""));
+        assert!(!is_not_modified(&headers, updated_at));
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_static("\"1\", W/\"1760000000\""),
+        );
+        assert!(is_not_modified(&headers, updated_at));
+    }
+}
@@ -1,30 +1,91 @@
-use std::{env, net::SocketAddr};
+use std::{
+    env,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
 
-use axum::{Router, extract::FromRef};
+use axum::{Router, extract::FromRef, middleware};
 use reqwest::Client;
-use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use sqlx::SqlitePool;
 use tokio::net::TcpListener;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tower_http::{compression::CompressionLayer, limit::RequestBodyLimitLayer, trace::TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::{
-    auth::admin::create_administrator,
+    auth::{
+        admin::create_administrator,
+        email::{EmailSender, LogEmailSender},
+        lockout::LoginAttempts,
+    },
+    config::{Config, migrate},
     handlers::{players, tournaments},
+    rate_limit::RateLimiter,
+    responses::{body_limit_error, legacy_error_status},
+    services::rating_provider::{FideScraper, RatingProvider},
 };
 
 mod auth;
+mod config;
 mod errors;
 mod handlers;
 mod models;
 mod payloads;
+mod rate_limit;
 mod repositories;
 mod responses;
 mod services;
 
 #[derive(Clone)]
-struct AppState {
+pub struct AppState {
     pool: SqlitePool,
-    client: reqwest::Client,
+    rating_provider: Arc<dyn RatingProvider>,
+    fide_limiter: Arc<RateLimiter<()>>,
+    // Forced FIDE refreshes, per player
+    refresh_limiter: Arc<RateLimiter<u32>>,
+    login_limiter: Arc<RateLimiter<IpAddr>>,
+    login_attempts: Arc<LoginAttempts>,
+    mailer: Arc<dyn EmailSender>,
+}
+
+const FIDE_RATE_LIMIT_PER_MINUTE: u32 = 30;
+const LOGIN_RATE_LIMIT_PER_MINUTE: u32 = 10;
+const FIDE_REFRESH_RATE_LIMIT_PER_MINUTE: u32 = 1;
+
+impl AppState {
+    // Default limits and no mail server, nothing is read from the env
+    pub fn new(pool: SqlitePool, rating_provider: Arc<dyn RatingProvider>) -> Self {
+        Self {
+            pool,
+            rating_provider,
+            fide_limiter: Arc::new(RateLimiter::per_minute(FIDE_RATE_LIMIT_PER_MINUTE)),
+            refresh_limiter: Arc::new(RateLimiter::per_minute(FIDE_REFRESH_RATE_LIMIT_PER_MINUTE)),
+            login_limiter: Arc::new(RateLimiter::per_minute(LOGIN_RATE_LIMIT_PER_MINUTE)),
+            login_attempts: Arc::new(LoginAttempts::default()),
+            mailer: Arc::new(LogEmailSender),
+        }
+    }
+
+    // Limits can be overridden with FIDE_RATE_LIMIT_PER_MINUTE,
+    // FIDE_REFRESH_RATE_LIMIT_PER_MINUTE, LOGIN_RATE_LIMIT_PER_MINUTE, LOGIN_MAX_FAILURES and
+    // LOGIN_LOCKOUT_MINUTES
+    pub fn from_env(pool: SqlitePool, rating_provider: Arc<dyn RatingProvider>) -> Self {
+        Self {
+            fide_limiter: Arc::new(RateLimiter::from_env(
+                "FIDE_RATE_LIMIT_PER_MINUTE",
+                FIDE_RATE_LIMIT_PER_MINUTE,
+            )),
+            refresh_limiter: Arc::new(RateLimiter::from_env(
+                "FIDE_REFRESH_RATE_LIMIT_PER_MINUTE",
+                FIDE_REFRESH_RATE_LIMIT_PER_MINUTE,
+            )),
+            login_limiter: Arc::new(RateLimiter::from_env(
+                "LOGIN_RATE_LIMIT_PER_MINUTE",
+                LOGIN_RATE_LIMIT_PER_MINUTE,
+            )),
+            login_attempts: Arc::new(LoginAttempts::from_env()),
+            ..Self::new(pool, rating_provider)
+        }
+    }
 }
 
 impl FromRef<AppState> for SqlitePool {
@@ -33,12 +94,43 @@ impl FromRef<AppState> for SqlitePool {
     }
 }
 
-impl FromRef<AppState> for reqwest::Client {
+impl FromRef<AppState> for Arc<LoginAttempts> {
+    fn from_ref(input: &AppState) -> Self {
+        input.login_attempts.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn EmailSender> {
+    fn from_ref(input: &AppState) -> Self {
+        input.mailer.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn RatingProvider> {
     fn from_ref(input: &AppState) -> Self {
-        input.client.clone()
+        input.rating_provider.clone()
     }
 }
 
+// Largest accepted request body, well above a TRF file of a full tournament
+pub const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+// Every route and the layers shared by all of them, CORS is left to the caller so the
+// router can also be nested under a prefix of another app
+pub fn app(state: AppState) -> Router {
+    Router::new()
+        .nest("/players", players::routes(state.clone()))
+        .nest("/tournaments", tournaments::routes(state.clone()))
+        .merge(handlers::auth::routes(state.clone()))
+        .merge(handlers::docs::routes())
+        // Only when the client sends Accept-Encoding, the default predicate leaves
+        // event streams, images and tiny bodies uncompressed
+        .layer(CompressionLayer::new().gzip(true).deflate(true))
+        .layer(RequestBodyLimitLayer::new(MAX_BODY_BYTES))
+        .layer(middleware::map_response(body_limit_error))
+        .layer(TraceLayer::new_for_http())
+}
+
 #[tokio::main]
 async fn main() {
     tracing_subscriber::registry()
@@ -49,22 +141,1082 @@ async fn main() {
         )
         .with(tracing_subscriber::fmt::layer())
         .init();
+    let config = Config::from_env();
     let db_url = env::var("DATABASE_URL").unwrap();
-    let pool = SqlitePoolOptions::new().connect(&db_url).await.unwrap();
+    let pool = config.database.connect(&db_url).await.unwrap();
+    migrate(&pool).await.unwrap();
     create_administrator(&pool).await;
     let client = Client::builder()
         .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36")
         .build()
         .unwrap();
-    let state = AppState { pool, client };
+    let state = AppState::from_env(pool, Arc::new(FideScraper::from_env(client)));
     let addr = SocketAddr::from(([127, 0, 0, 1], 3001));
     let listener = TcpListener::bind(addr).await.unwrap();
     tracing::info!("listening on {}", addr);
-    let app = Router::new()
-        .nest("/players", players::routes(state.clone()))
-        .nest("/tournaments", tournaments::routes(state.clone()))
-        .merge(handlers::auth::routes(state.clone()))
-        .layer(TraceLayer::new_for_http())
-        .layer(CorsLayer::very_permissive());
-    axum::serve(listener, app).await.unwrap();
+    let mut app = app(state);
+    if config.legacy_error_status {
+        app = app.layer(middleware::map_response(legacy_error_status));
+    }
+    let app = app.layer(config.cors_layer());
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, sync::Arc};
+
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        extract::connect_info::MockConnectInfo,
+        http::{Request, StatusCode, header},
+        middleware,
+    };
+    use serde_json::{Value, json};
+    use tower::ServiceExt;
+
+    use crate::{
+        AppState, app, auth::jwt::create_token, responses::legacy_error_status,
+        services::rating_provider::MockRatingProvider,
+    };
+
+    fn test_app(pool: sqlx::SqlitePool) -> Router {
+        let state = AppState::new(pool, Arc::new(MockRatingProvider { players: vec![] }));
+        // Stands in for the peer address the real server provides to the ip rate limiter
+        app(state).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))))
+    }
+
+    async fn send(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("create_players")))]
+    async fn test_http_tournament_flow(pool: sqlx::SqlitePool) {
+        let app = test_app(pool.clone());
+        let credentials = json!({ "username": "organizer", "password": "secret" });
+
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/register",
+            None,
+            Some(json!({ "username": "organizer", "password": "secret", "email": "o@mail.com" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "success");
+        assert_eq!(body["payload"]["type"], "userCreated");
+        let (token,): (String,) =
+            sqlx::query_as("select verification_token from users where username = 'organizer'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let (status, _) = send(&app, "GET", &format!("/verify?token={}", token), None, None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/login",
+            None,
+            Some(json!({ "username": "organizer", "password": "wrong" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["status"], "error");
+        assert_eq!(body["error"]["code"], "LoginFailed");
+        let (status, body) = send(&app, "POST", "/login", None, Some(credentials)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["type"], "loginSuccess");
+        let token = body["payload"]["token"].as_str().unwrap().to_string();
+
+        let tournament = json!({
+            "name": "Open",
+            "rounds": 5,
+            "timeCategory": "standard",
+            "startDate": 1769373667,
+            "federation": "BRA",
+            "broadcastBoards": 1,
+            "timeControl": " 90+30 ",
+        });
+        let (status, body) =
+            send(&app, "POST", "/tournaments", None, Some(tournament.clone())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "InvalidAuthHeader");
+        let mut no_date = tournament.clone();
+        no_date["startDate"] = json!(0);
+        let (status, body) = send(&app, "POST", "/tournaments", Some(&token), Some(no_date)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "InvalidStartDate");
+        let mut bad_time_control = tournament.clone();
+        bad_time_control["timeControl"] = json!("90 minutes");
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/tournaments",
+            Some(&token),
+            Some(bad_time_control),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "InvalidTimeControl");
+        for tiebreaks in [json!([]), json!(["buchholz", "progressive", "buchholz"])] {
+            let mut bad_tiebreaks = tournament.clone();
+            bad_tiebreaks["tiebreaks"] = tiebreaks;
+            let (status, body) = send(
+                &app,
+                "POST",
+                "/tournaments",
+                Some(&token),
+                Some(bad_tiebreaks),
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body["error"]["code"], "InvalidTiebreakOrder");
+        }
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/tournaments",
+            Some(&token),
+            Some(tournament.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["type"], "tournamentCreated");
+        assert!(body["payload"].get("warnings").is_none());
+        let id = body["payload"]["id"].as_u64().unwrap();
+        let (_, body) = send(&app, "GET", &format!("/tournaments/{}", id), None, None).await;
+        assert_eq!(body["payload"]["timeControl"], "90+30");
+        let uri = format!("/tournaments/{}/time-control", id);
+        let time_control = json!({ "timeControl": "40/90+30:30+30" });
+        let (status, body) = send(&app, "POST", &uri, Some(&token), Some(time_control)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["timeControl"], "40/90+30:30+30");
+        let (status, body) = send(&app, "POST", &uri, None, Some(json!({}))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "InvalidAuthHeader");
+
+        for player_id in 1..=4 {
+            let registration = json!({
+                "playerId": player_id,
+                "rating": 2800 - player_id * 10,
+                "status": "active",
+                "absentResults": [],
+            });
+            let uri = format!("/tournaments/{}/register", id);
+            let (status, _) = send(&app, "POST", &uri, Some(&token), Some(registration)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let pair = json!({ "inactiveScores": [] });
+        let uri = format!("/tournaments/{}/pair", id);
+        let (status, body) = send(&app, "POST", &uri, Some(&token), Some(pair.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["type"], "pairingGenerated");
+        assert_eq!(body["payload"]["pairings"].as_array().unwrap().len(), 2);
+        let (_, body) = send(&app, "GET", &format!("/tournaments/{}", id), None, None).await;
+        let boards = &body["payload"]["pairings"][0];
+        assert_eq!(boards[0]["broadcast"], true);
+        assert_eq!(boards[1]["broadcast"], false);
+        let pending = format!("/tournaments/{}/rounds/0/pending", id);
+        let (status, body) = send(&app, "GET", &pending, None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["boards"].as_array().unwrap().len(), 2);
+        let result = json!({ "roundId": 0, "boardId": 0, "result": "1-0" });
+        let uri_result = format!("/tournaments/{}/result", id);
+        let (status, _) = send(&app, "POST", &uri_result, Some(&token), Some(result)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = send(&app, "GET", "/tournaments", None, None).await;
+        let listed = &body["payload"]["tournaments"][0];
+        assert_eq!(listed["completedBoards"], 1);
+        assert_eq!(listed["totalBoards"], 2);
+        let result = json!({ "roundId": 0, "boardId": 1, "result": "W" });
+        let (status, body) = send(&app, "POST", &uri_result, Some(&token), Some(result)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "InvalidResult");
+        let result = json!({ "roundId": 0, "boardId": 7, "result": "1-0" });
+        let (status, body) = send(&app, "POST", &uri_result, Some(&token), Some(result)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "BoardNotFound");
+        let (_, body) = send(&app, "GET", &pending, None, None).await;
+        let boards = body["payload"]["boards"].as_array().unwrap();
+        assert_eq!(boards.len(), 1);
+        assert_eq!(boards[0]["boardNumber"], 1);
+        let standings = format!("/tournaments/{}/standings", id);
+        let (status, body) = send(&app, "GET", &standings, None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["round"], 0);
+        assert_eq!(body["payload"]["standings"].as_array().unwrap().len(), 4);
+        assert!(body["payload"]["updatedAt"].as_u64().unwrap() > 0);
+        let (status, body) = send(&app, "GET", &format!("{}?round=1", standings), None, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "RoundNotFound");
+        let uri_missing = format!("/tournaments/{}/rounds/1/pending", id);
+        let (status, body) = send(&app, "GET", &uri_missing, None, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "RoundNotFound");
+        // The round has no results yet
+        let (status, body) = send(&app, "POST", &uri, Some(&token), Some(pair)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "RoundNotDone");
+        // Draws are sent back in the canonical notation, whichever one was used
+        let result = json!({ "roundId": 0, "boardId": 1, "result": "=-=" });
+        let (status, body) = send(&app, "POST", &uri_result, Some(&token), Some(result)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["gameResult"], "1/2-1/2");
+        let (_, body) = send(&app, "GET", &format!("/tournaments/{}", id), None, None).await;
+        assert_eq!(body["payload"]["pairings"][0][1]["result"], "1/2-1/2");
+
+        // 5 rounds cannot be paired without rematches among 8 players
+        let mut small = tournament;
+        small["maxPlayers"] = json!(8);
+        let (status, body) = send(&app, "POST", "/tournaments", Some(&token), Some(small)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["warnings"].as_array().unwrap().len(), 1);
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_http_clone_tournament(pool: sqlx::SqlitePool) {
+        sqlx::query("update users set email_verified = 1 where id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("update tournaments set time_control = '90+30', tiebreaks = 'progressive,buchholz', current_round = 2 where id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let app = test_app(pool);
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        let registration = json!({
+            "playerId": 1,
+            "rating": 2000,
+            "status": "active",
+            "absentResults": [],
+        });
+        let register = "/tournaments/1/register";
+        let (status, _) = send(&app, "POST", register, Some(&token), Some(registration)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&app, "POST", "/tournaments/1/clone", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "InvalidAuthHeader");
+        let other = create_token(
+            2,
+            "other".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        let (status, body) = send(&app, "POST", "/tournaments/1/clone", Some(&other), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "InsufficientPermissions");
+        let (status, body) = send(&app, "POST", "/tournaments/1/clone", Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let id = body["payload"]["id"].as_i64().unwrap();
+        assert_ne!(id, 1);
+        let (status, body) = send(&app, "GET", &format!("/tournaments/{}", id), None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let tournament = &body["payload"];
+        assert_eq!(tournament["name"], "Test Tournament 2026");
+        assert_eq!(tournament["numRounds"], 9);
+        assert_eq!(tournament["federation"], "BRA");
+        assert_eq!(tournament["timeControl"], "90+30");
+        assert_eq!(tournament["tiebreaks"], json!(["progressive", "buchholz"]));
+        assert_eq!(tournament["currentRound"], 0);
+        let uri = format!("/tournaments/{}/registrations", id);
+        let (status, body) = send(&app, "GET", &uri, None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["registrations"], json!([]));
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_http_clone_registrations(pool: sqlx::SqlitePool) {
+        sqlx::query("update users set email_verified = 1 where id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("insert into users (username, email, password_hash, role, email_verified) values ('arbiter', 'a@mail.com', 'hash', 'standard', 1)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("insert into tournament_organizers (tournament_id, user_id, role) values (1, 2, 'arbiter')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let app = test_app(pool.clone());
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        for player_id in 1..=4 {
+            let registration = json!({
+                "playerId": player_id,
+                "rating": 2000 + player_id * 10,
+                "status": "active",
+                "absentResults": [],
+            });
+            let register = "/tournaments/1/register";
+            let (status, _) = send(&app, "POST", register, Some(&token), Some(registration)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let pair = json!({ "inactiveScores": [] });
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/tournaments/1/pair",
+            Some(&token),
+            Some(pair),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        sqlx::query("update registrations set status = 'inactive' where player_id = 2")
+            .execute(&pool)
+            .await
+            .unwrap();
+        // A co-organizer can copy the settings but not the roster
+        let arbiter = create_token(
+            2,
+            "arbiter".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        let uri = "/tournaments/1/clone?registrations=true";
+        let (status, _) = send(&app, "POST", uri, Some(&arbiter), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(&app, "POST", "/tournaments/1/clone", Some(&arbiter), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&app, "POST", uri, Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let id = body["payload"]["id"].as_i64().unwrap();
+        let uri = format!("/tournaments/{}/registrations", id);
+        let (status, body) = send(&app, "GET", &uri, None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let seeds: Vec<(u64, u64)> = body["payload"]["registrations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| (r["playerId"].as_u64().unwrap(), r["seed"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(seeds, vec![(1, 3), (3, 2), (4, 1)]);
+        // The active roster is copied, the rounds are not
+        let (_, body) = send(&app, "GET", &format!("/tournaments/{}", id), None, None).await;
+        assert_eq!(body["payload"]["currentRound"], 0);
+        assert_eq!(body["payload"]["pairings"], json!([]));
+        let (_, body) = send(&app, "GET", "/tournaments/1", None, None).await;
+        assert_eq!(body["payload"]["pairings"].as_array().unwrap().len(), 1);
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_http_bye_prediction(pool: sqlx::SqlitePool) {
+        let app = test_app(pool);
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        let uri = "/tournaments/1/pair/bye-prediction";
+        for player_id in 1..=3 {
+            let registration = json!({
+                "playerId": player_id,
+                "rating": 2000 - player_id * 10,
+                "status": "active",
+                "absentResults": [],
+            });
+            let register = "/tournaments/1/register";
+            let (status, _) = send(&app, "POST", register, Some(&token), Some(registration)).await;
+            assert_eq!(status, StatusCode::OK);
+            let (status, body) = send(&app, "GET", uri, None, None).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body["payload"]["round"], 0);
+            let expected = if player_id == 2 {
+                json!([])
+            } else {
+                json!([player_id])
+            };
+            assert_eq!(body["payload"]["byes"], expected);
+        }
+        let pair = json!({ "inactiveScores": [] });
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/tournaments/1/pair",
+            Some(&token),
+            Some(pair),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["byes"], json!([3]));
+        let (status, body) = send(&app, "GET", uri, None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["round"], 1);
+        assert_eq!(body["payload"]["byes"].as_array().unwrap().len(), 1);
+        assert_ne!(body["payload"]["byes"][0], 3);
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_http_simulate_standings(pool: sqlx::SqlitePool) {
+        let app = test_app(pool);
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        for player_id in 1..=4 {
+            let registration = json!({
+                "playerId": player_id,
+                "rating": 2000 - player_id * 10,
+                "status": "active",
+                "absentResults": [],
+            });
+            let register = "/tournaments/1/register";
+            let (status, _) = send(&app, "POST", register, Some(&token), Some(registration)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let uri = "/tournaments/1/standings/simulate";
+        let simulated = json!({ "results": [{ "roundId": 0, "boardId": 0, "result": "0-1" }] });
+        let (status, body) = send(&app, "POST", uri, None, Some(simulated.clone())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "InvalidAuthHeader");
+        let pair = json!({ "inactiveScores": [], "fixedPairings": [[1, 2], [3, 4]] });
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/tournaments/1/pair",
+            Some(&token),
+            Some(pair),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&app, "POST", uri, Some(&token), Some(simulated)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["round"], 0);
+        let standings = &body["payload"]["standings"];
+        assert_eq!(standings[0]["playerId"], 2);
+        assert_eq!(standings[0]["score"], 2);
+        // Nothing was stored
+        let (_, body) = send(&app, "GET", "/tournaments/1/standings", None, None).await;
+        let standings = body["payload"]["standings"].as_array().unwrap();
+        assert!(standings.iter().all(|s| s["score"] == 0));
+        let missing = json!({ "results": [{ "roundId": 0, "boardId": 5, "result": "1-0" }] });
+        let (status, body) = send(&app, "POST", uri, Some(&token), Some(missing)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "BoardNotFound");
+        // A typo is not simulated as an unplayed game
+        let typo = json!({ "results": [{ "roundId": 0, "boardId": 0, "result": "1-O" }] });
+        let (status, body) = send(&app, "POST", uri, Some(&token), Some(typo)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "InvalidResult");
+        let ongoing = json!({ "results": [{ "roundId": 0, "boardId": 0, "result": "*" }] });
+        let (status, _) = send(&app, "POST", uri, Some(&token), Some(ongoing)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("create_players", "create_user")))]
+    async fn test_http_refresh_fide_limit(pool: sqlx::SqlitePool) {
+        let app = test_app(pool);
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        // Anonymous requests are rejected before the limit is taken
+        let (status, _) = send(&app, "POST", "/players/1/refresh-fide", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        // The test provider knows nobody, the scrape itself fails
+        let (_, body) = send(&app, "POST", "/players/1/refresh-fide", Some(&token), None).await;
+        assert_eq!(body["error"]["code"], "FideScrapeFailed");
+        let (status, body) =
+            send(&app, "POST", "/players/1/refresh-fide", Some(&token), None).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["error"]["code"], "RateLimited");
+        // Other players have their own limit
+        let (_, body) = send(&app, "POST", "/players/3/refresh-fide", Some(&token), None).await;
+        assert_eq!(body["error"]["code"], "FideScrapeFailed");
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("create_user", "create_tournament")))]
+    async fn test_http_expired_token(pool: sqlx::SqlitePool) {
+        let app = test_app(pool);
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(-1),
+        )
+        .unwrap();
+        let (status, body) = send(&app, "GET", "/tournaments/1/audit", Some(&token), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "TokenInvalid");
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("create_players")))]
+    async fn test_http_player_games_last_page(pool: sqlx::SqlitePool) {
+        let app = test_app(pool);
+        // The offset of the highest page does not fit in 32 bits
+        let uri = "/players/1/games?page=4294967295&pageSize=200";
+        let (status, body) = send(&app, "GET", uri, None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["games"], json!([]));
+        assert_eq!(body["payload"]["page"], 4294967295u32);
+    }
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("create_user", "create_tournament")))]
+    async fn test_http_visibility(pool: sqlx::SqlitePool) {
+        let app = test_app(pool);
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        let listed = |body: Value| body["payload"]["tournaments"].as_array().unwrap().len();
+        let (_, body) = send(&app, "GET", "/tournaments", None, None).await;
+        assert_eq!(listed(body), 1);
+
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/tournaments/1/visibility",
+            Some(&token),
+            Some(json!({ "visibility": "private" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["visibility"], "private");
+        let (status, _) = send(&app, "GET", "/tournaments/1", None, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, body) = send(&app, "GET", "/tournaments/1", Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["payload"]["organizerData"].is_object());
+        let (_, body) = send(&app, "GET", "/tournaments", None, None).await;
+        assert_eq!(listed(body), 0);
+
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/tournaments/1/visibility",
+            Some(&token),
+            Some(json!({ "visibility": "unlisted" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&app, "GET", "/tournaments/1", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        // Organizer-only data is left out for anonymous viewers
+        assert!(body["payload"].get("organizerData").is_none());
+        let (_, body) = send(&app, "GET", "/tournaments", None, None).await;
+        assert_eq!(listed(body), 0);
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_http_max_players(pool: sqlx::SqlitePool) {
+        sqlx::query("update tournaments set max_players = 2 where id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let app = test_app(pool);
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        for player_id in 1..=3 {
+            let registration = json!({
+                "playerId": player_id,
+                "rating": 2000,
+                "status": "active",
+                "absentResults": [],
+            });
+            let (status, body) = send(
+                &app,
+                "POST",
+                "/tournaments/1/register",
+                Some(&token),
+                Some(registration),
+            )
+            .await;
+            if player_id <= 2 {
+                assert_eq!(status, StatusCode::OK);
+            } else {
+                assert_eq!(status, StatusCode::CONFLICT);
+                assert_eq!(body["error"]["code"], "TournamentFull");
+            }
+        }
+        let (status, body) = send(&app, "GET", "/tournaments/1", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["playerCount"], 2);
+        assert_eq!(body["payload"]["maxPlayers"], 2);
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_http_mini_match_results(pool: sqlx::SqlitePool) {
+        sqlx::query("update tournaments set games_per_pairing = 2 where id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let app = test_app(pool);
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        for player_id in 1..=2 {
+            let registration = json!({
+                "playerId": player_id,
+                "rating": 2000 - player_id * 10,
+                "status": "active",
+                "absentResults": [],
+            });
+            let register = "/tournaments/1/register";
+            let (status, _) = send(&app, "POST", register, Some(&token), Some(registration)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let pair = json!({ "inactiveScores": [] });
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/tournaments/1/pair",
+            Some(&token),
+            Some(pair),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let uri = "/tournaments/1/result";
+        let agreed = json!({ "roundId": 0, "boardId": 0, "gameNumber": 0, "result": "1/2-1/2", "agreed": true });
+        let (status, body) = send(&app, "POST", uri, Some(&token), Some(agreed)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "AgreedDrawInMatch");
+        for game in 0..2 {
+            let result =
+                json!({ "roundId": 0, "boardId": 0, "gameNumber": game, "result": "1/2-1/2" });
+            let (status, _) = send(&app, "POST", uri, Some(&token), Some(result)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (_, body) = send(&app, "GET", "/tournaments/1", None, None).await;
+        assert_eq!(body["payload"]["pairings"][0][0]["result"], "1/2-1/2");
+    }
+
+    #[sqlx::test]
+    async fn test_http_legacy_error_status(pool: sqlx::SqlitePool) {
+        let app = test_app(pool).layer(middleware::map_response(legacy_error_status));
+        let (status, body) = send(&app, "GET", "/tournaments/999", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["error"]["code"], "TournamentNotFound");
+        let (status, _) = send(&app, "GET", "/errors", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[sqlx::test]
+    async fn test_http_errors(pool: sqlx::SqlitePool) {
+        let app = test_app(pool);
+        let (status, body) = send(&app, "GET", "/errors", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let errors = body["payload"]["errors"].as_array().unwrap();
+        let not_found = errors
+            .iter()
+            .find(|e| e["code"] == "TournamentNotFound")
+            .unwrap();
+        assert_eq!(not_found["status"], 404);
+        // The status of a real error response comes from the same table
+        let (status, body) = send(&app, "GET", "/tournaments/999", None, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "TournamentNotFound");
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_http_regenerate_round(pool: sqlx::SqlitePool) {
+        let app = test_app(pool);
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        let uri = "/tournaments/1/rounds/current/regenerate";
+        let pair = json!({ "inactiveScores": [] });
+        let (status, body) = send(&app, "POST", uri, Some(&token), Some(pair.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "TournamentNotStarted");
+        for player_id in 1..=4 {
+            let registration = json!({
+                "playerId": player_id,
+                "rating": 2000 - player_id * 10,
+                "status": "active",
+                "absentResults": [],
+            });
+            let register = "/tournaments/1/register";
+            let (status, _) = send(&app, "POST", register, Some(&token), Some(registration)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/tournaments/1/pair",
+            Some(&token),
+            Some(pair.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        // Fixed pairings replace the generated ones of the same round
+        let fixed = json!({ "inactiveScores": [], "fixedPairings": [[1, 2], [3, 4]] });
+        let (status, body) = send(&app, "POST", uri, Some(&token), Some(fixed)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["payload"]["round"], 0);
+        assert_eq!(body["payload"]["pairings"], json!([[1, 2], [3, 4]]));
+        let (_, body) = send(&app, "GET", "/tournaments/1", None, None).await;
+        assert_eq!(body["payload"]["currentRound"], 1);
+        assert_eq!(body["payload"]["pairings"].as_array().unwrap().len(), 1);
+        let result = json!({ "roundId": 0, "boardId": 0, "result": "1-0" });
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/tournaments/1/result",
+            Some(&token),
+            Some(result),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&app, "POST", uri, Some(&token), Some(pair)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"]["code"], "RoundHasResults");
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_http_pairing_graph(pool: sqlx::SqlitePool) {
+        let app = test_app(pool);
+        let token = |role: &str| {
+            create_token(
+                1,
+                "user".to_string(),
+                role.to_string(),
+                chrono::Duration::hours(1),
+            )
+            .unwrap()
+        };
+        for player_id in 1..=5 {
+            let registration = json!({
+                "playerId": player_id,
+                "rating": 2000 - player_id * 10,
+                "status": "active",
+                "absentResults": [],
+            });
+            let register = "/tournaments/1/register";
+            let (status, _) = send(
+                &app,
+                "POST",
+                register,
+                Some(&token("standard")),
+                Some(registration),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let uri = "/tournaments/1/pair/graph";
+        // Even the owner of the tournament cannot see the internals
+        let (status, body) = send(&app, "GET", uri, Some(&token("standard")), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "InsufficientPermissions");
+        let (status, body) = send(&app, "GET", uri, Some(&token("admin")), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let graph = &body["payload"]["graph"];
+        assert_eq!(graph["round"], 0);
+        assert_eq!(graph["byes"], json!([5]));
+        assert_eq!(graph["candidates"], json!([1, 2, 3, 4]));
+        let edges = graph["edges"].as_array().unwrap();
+        // Every pair of the 4 candidates
+        assert_eq!(edges.len(), 6);
+        for edge in edges {
+            let breakdown = &edge["breakdown"];
+            assert_eq!(breakdown["base"], 5_000);
+            assert_eq!(breakdown["repeatPairingPenalty"], 0);
+            let total = ["base", "combinedScoreBonus", "isolationBonus"]
+                .iter()
+                .map(|term| breakdown[term].as_i64().unwrap())
+                .sum::<i64>()
+                - [
+                    "scorePenalty",
+                    "colorPenalty",
+                    "halfPairDeviationPenalty",
+                    "repeatedFloatPenalty",
+                    "floatRankPenalty",
+                    "recentOpponentPenalty",
+                    "colorStreakPenalty",
+                ]
+                .iter()
+                .map(|term| breakdown[term].as_i64().unwrap())
+                .sum::<i64>();
+            assert_eq!(edge["weight"], total);
+        }
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_http_payload_too_large(pool: sqlx::SqlitePool) {
+        let app = test_app(pool);
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        for player_id in 1..=2 {
+            let registration = json!({
+                "playerId": player_id,
+                "rating": 2000,
+                "status": "active",
+                "absentResults": [],
+            });
+            let uri = "/tournaments/1/register";
+            let (status, _) = send(&app, "POST", uri, Some(&token), Some(registration)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let pairings = json!({ "inactiveScores": [[1, "1-0"], [2, "1-0"], [3, "1-0"]] });
+        let uri = "/tournaments/1/pair";
+        let (status, body) = send(&app, "POST", uri, Some(&token), Some(pairings)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "TooManyItems");
+        let pairings = json!({ "inactiveScores": [], "fixedByes": vec![0; super::MAX_BODY_BYTES] });
+        let (status, body) = send(&app, "POST", uri, Some(&token), Some(pairings)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["error"]["code"], "PayloadTooLarge");
+        // Refused from the Content-Length alone, before the body is read
+        let request = Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_LENGTH, super::MAX_BODY_BYTES + 1)
+            .body(Body::from("{}"))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "PayloadTooLarge");
+        assert_eq!(body["error"]["message"], "`request body` is too large");
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_http_registrations(pool: sqlx::SqlitePool) {
+        let app = test_app(pool);
+        let token = create_token(
+            1,
+            "user".to_string(),
+            "standard".to_string(),
+            chrono::Duration::hours(1),
+        )
+        .unwrap();
+        for (player_id, rating, status) in [
+            (1, 1800, "active"),
+            (2, 2200, "inactive"),
+            (3, 2000, "active"),
+            (4, 1900, "active"),
+            (5, 1700, "active"),
+        ] {
+            // Player 5 arrives late and leaves early
+            let unavailable = if player_id == 5 { vec![4, 0] } else { vec![] };
+            let registration = json!({
+                "playerId": player_id,
+                "rating": rating,
+                "status": status,
+                "absentResults": [],
+                "unavailableRounds": unavailable,
+            });
+            let (status, _) = send(
+                &app,
+                "POST",
+                "/tournaments/1/register",
+                Some(&token),
+                Some(registration),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (status, body) = send(&app, "GET", "/tournaments/1/registrations", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let seeds: Vec<(u64, u64)> = body["payload"]["registrations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| (r["playerId"].as_u64().unwrap(), r["seed"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(seeds, vec![(1, 4), (2, 1), (3, 2), (4, 3), (5, 5)]);
+        let unavailable = &body["payload"]["registrations"][4]["requestedByeRounds"];
+        assert_eq!(unavailable, &json!([0, 4]));
+        let (status, body) = send(
+            &app,
+            "GET",
+            "/tournaments/1/registrations?active=true",
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let registrations = body["payload"]["registrations"].as_array().unwrap();
+        assert_eq!(registrations.len(), 4);
+        assert!(registrations.iter().all(|r| r["status"] == "active"));
+        let (status, _) = send(&app, "GET", "/tournaments/99/registrations", None, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let pair = json!({ "inactiveScores": [] });
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/tournaments/1/pair",
+            Some(&token),
+            Some(pair),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        // Player 2 is inactive, player 5 is left out of the first round with a requested bye
+        assert_eq!(body["payload"]["pairings"].as_array().unwrap().len(), 1);
+        assert_eq!(body["payload"]["notPaired"], json!([2, 5]));
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_http_self_registration(pool: sqlx::SqlitePool) {
+        sqlx::query("insert into users (username, email, password_hash, role) values ('player', 'p@mail.com', 'hash', 'standard'), ('other', 'o@mail.com', 'hash', 'standard')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let app = test_app(pool.clone());
+        let token = |user_id, username: &str| {
+            create_token(
+                user_id,
+                username.to_string(),
+                "standard".to_string(),
+                chrono::Duration::hours(1),
+            )
+            .unwrap()
+        };
+        let (organizer, player, other) = (token(1, "user"), token(2, "player"), token(3, "other"));
+        let registration = |player_id| {
+            json!({
+                "playerId": player_id,
+                "rating": 2000,
+                "status": "inactive",
+                "absentResults": [],
+            })
+        };
+        let uri = "/tournaments/1/register";
+        let (status, body) = send(&app, "POST", uri, Some(&player), Some(registration(1))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "SelfRegistrationDisabled");
+
+        sqlx::query("update tournaments set self_registration = true where id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (status, _) = send(&app, "POST", uri, Some(&player), Some(registration(1))).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send(&app, "POST", uri, Some(&player), Some(registration(2))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"]["code"], "AlreadyRegistered");
+        // Player 1 now belongs to the first user, nobody else can register with it
+        let (status, body) = send(&app, "POST", uri, Some(&other), Some(registration(1))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "NotOwnPlayer");
+        // Organizers keep registering any number of players
+        for player_id in 2..=3 {
+            let (status, _) = send(
+                &app,
+                "POST",
+                uri,
+                Some(&organizer),
+                Some(registration(player_id)),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (_, body) = send(&app, "GET", "/tournaments/1", None, None).await;
+        let players = body["payload"]["players"].as_array().unwrap();
+        assert_eq!(players.len(), 3);
+        // Self-registered players are always active, with their stored rating
+        assert_eq!(players[0]["status"], "active");
+        assert_eq!(players[0]["rating"], 2840);
+        assert_eq!(players[1]["status"], "inactive");
+        assert_eq!(players[1]["rating"], 2000);
+    }
 }
@@ -0,0 +1,38 @@
+use crate::{models::tournament::GameResult, payloads::NewTournament};
+
+// A finished or running tournament read from another pairing program. Players are referred
+// to by their start rank until they get a registration id
+pub struct ImportedTournament {
+    pub tournament: NewTournament,
+    pub players: Vec<ImportedPlayer>,
+    pub games: Vec<ImportedGame>,
+    pub gaps: Vec<ImportedGap>,
+    // Number of rounds already paired
+    pub current_round: u32,
+}
+
+pub struct ImportedPlayer {
+    pub start_rank: u32,
+    pub first_name: String,
+    pub last_name: String,
+    pub title: Option<String>,
+    pub federation: Option<String>,
+    pub fide_id: Option<i64>,
+    pub rating: Option<u32>,
+}
+
+pub struct ImportedGame {
+    pub round_number: u32,
+    pub board_number: u32,
+    pub white: u32,
+    pub black: u32,
+    // Ongoing for games without a result yet
+    pub result: GameResult,
+}
+
+pub struct ImportedGap {
+    pub round_number: u32,
+    pub player: u32,
+    pub score: u32,
+    pub is_bye: bool,
+}
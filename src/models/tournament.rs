@@ -5,20 +5,31 @@ use serde::Serialize;
 use crate::{
     errors::AppError,
     repositories::{
-        pairing_repo::{DbPairing, DbPairingGap, NewDbPairing, NewDbPairingGap},
-        registration_repo::DbRegistration,
+        organizer_repo::DbOrganizer,
+        pairing_repo::{DbPairing, DbPairingGap, DbRecentMeetings, NewDbPairing, NewDbPairingGap},
+        playoff_repo::DbPlayoffGame,
+        registration_repo::{DbByeRequest, DbRegistration},
+        section_repo::DbSection,
         tournament_repo::DbTournament,
     },
 };
 
+// Hard cap on the registrations of any tournament, also the limit when max_players is not set
+pub const MAX_PLAYERS: u32 = 1000;
+
 pub struct TournamentDbData {
     pub tournament: DbTournament,
     pub players: Vec<DbRegistration>,
     pub pairings: Vec<DbPairing>,
     pub pairing_gaps: Vec<DbPairingGap>,
+    pub organizers: Vec<DbOrganizer>,
+    pub sections: Vec<DbSection>,
+    pub playoff: Vec<DbPlayoffGame>,
+    pub bye_requests: Vec<DbByeRequest>,
+    pub recent_meetings: Vec<DbRecentMeetings>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Tournament {
     pub id: u32,
     pub name: String,
@@ -28,10 +39,34 @@ pub struct Tournament {
     pub byes: Vec<Vec<u32>>,
     pub results: Vec<Vec<GameResult>>,
     pub num_rounds: usize,
-    pub start_date: usize,
+    pub start_date: u32,
     pub federation: String,
     pub user_id: u32,
     pub username: String,
+    pub organizers: Vec<DbOrganizer>,
+    pub sections: Vec<DbSection>,
+    pub playoff: Vec<DbPlayoffGame>,
+    pub max_float_gap: Option<u32>,
+    pub registration_open: bool,
+    pub late_entry_default_score: u32,
+    pub unrated_default_rating: Option<u32>,
+    pub board_offset: u32,
+    pub allow_repeat_pairings: bool,
+    pub visibility: Visibility,
+    pub requested_bye_score: u32,
+    pub games_per_pairing: u32,
+    pub tiebreaks: Vec<Tiebreak>,
+    pub max_players: Option<u32>,
+    pub broadcast_boards: u32,
+    pub self_registration: bool,
+    pub allow_three_same_colors: bool,
+    pub time_control: Option<String>,
+    pub avoid_recent_opponents: bool,
+    pub allow_rematch_reversed_colors: bool,
+    pub avoid_last_round_contender_bye: bool,
+    // Games between two players (player ids, lower first) in the organizer's recent
+    // tournaments, only loaded with `avoid_recent_opponents`
+    pub recent_meetings: HashMap<(u32, u32), u32>,
     pub updated_at: u32,
     pub end_date: Option<u32>,
     pub url: Option<String>,
@@ -44,24 +79,92 @@ pub enum GameResult {
     Draw,
     BlackWins,
     DoubleLoss,
+    // Neither player showed up, scored 0-0 like a double loss but the game never took place
+    DoubleForfeit,
 }
 
 impl GameResult {
+    // Accepts the common notations, whitespace and case aside. Anything else, including
+    // ambiguous inputs like `1`, `+` or `½` alone, is kept as ongoing
     pub fn from_str<S: AsRef<str>>(str: S) -> Self {
-        match str.as_ref().trim() {
-            "1-0" => Self::WhiteWins,
-            "1 - 0" => Self::WhiteWins,
-            "1/2-1/2" => Self::Draw,
-            "1/2 - 1/2" => Self::Draw,
-            "½-½" => Self::Draw,
-            "½ - ½" => Self::Draw,
-            "=-=" => Self::Draw,
-            "= - =" => Self::Draw,
-            "0-1" => Self::BlackWins,
-            "0 - 1" => Self::BlackWins,
-            "0-0" => Self::DoubleLoss,
-            "0 - 0" => Self::DoubleLoss,
-            _ => Self::Ongoing,
+        let notation: String = str
+            .as_ref()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        match notation.as_str() {
+            "1-0" | "1:0" | "white" => Self::WhiteWins,
+            "1/2-1/2" | "½-½" | "0.5-0.5" | "=-=" | "1/2:1/2" | "½:½" | "0.5:0.5" | "draw" => {
+                Self::Draw
+            }
+            "0-1" | "0:1" | "black" => Self::BlackWins,
+            "0-0" | "0:0" => Self::DoubleLoss,
+            "0f-0f" => Self::DoubleForfeit,
+            "" | "*" => Self::Ongoing,
+            _ => {
+                tracing::warn!(
+                    "unrecognized game result {:?}, kept as ongoing",
+                    str.as_ref()
+                );
+                Self::Ongoing
+            }
+        }
+    }
+
+    // Result from the point of view of the player with the given color, None if still ongoing
+    pub fn for_color(&self, color: Color) -> Option<PlayerResult> {
+        match (self, color) {
+            (GameResult::Ongoing, _) => None,
+            (GameResult::Draw, _) => Some(PlayerResult::Draw),
+            (GameResult::WhiteWins, Color::White) => Some(PlayerResult::Win),
+            (GameResult::BlackWins, Color::Black) => Some(PlayerResult::Win),
+            _ => Some(PlayerResult::Lose),
+        }
+    }
+
+    // Half points scored by the player with the given color, the only place games are scored
+    pub fn points_for(&self, color: Color) -> u32 {
+        match self.for_color(color) {
+            Some(PlayerResult::Win) => 2,
+            Some(PlayerResult::Draw) => 1,
+            Some(PlayerResult::Lose) | None => 0,
+        }
+    }
+
+    // Games that never took place over the board, still paired but not played. Only double
+    // forfeits are told apart, a single forfeit is entered as a regular win
+    pub fn is_forfeit(&self) -> bool {
+        *self == GameResult::DoubleForfeit
+    }
+
+    // Match result of a board with several games, every game result is given from the point
+    // of view of the board's white player. Ongoing until all games are done
+    pub fn aggregate(games: &[GameResult]) -> Self {
+        if games.is_empty() || games.contains(&GameResult::Ongoing) {
+            return GameResult::Ongoing;
+        }
+        if games.iter().all(|g| *g == GameResult::DoubleForfeit) {
+            return GameResult::DoubleForfeit;
+        }
+        if games
+            .iter()
+            .all(|g| matches!(g, GameResult::DoubleLoss | GameResult::DoubleForfeit))
+        {
+            return GameResult::DoubleLoss;
+        }
+        let (white, black) = games
+            .iter()
+            .fold((0, 0), |(white, black), game| match game {
+                GameResult::WhiteWins => (white + 2, black),
+                GameResult::Draw => (white + 1, black + 1),
+                GameResult::BlackWins => (white, black + 2),
+                _ => (white, black),
+            });
+        match white.cmp(&black) {
+            std::cmp::Ordering::Greater => GameResult::WhiteWins,
+            std::cmp::Ordering::Equal => GameResult::Draw,
+            std::cmp::Ordering::Less => GameResult::BlackWins,
         }
     }
 }
@@ -71,9 +174,10 @@ impl Display for GameResult {
         match self {
             GameResult::Ongoing => write!(f, "*"),
             GameResult::WhiteWins => write!(f, "1-0"),
-            GameResult::Draw => write!(f, "=-="),
+            GameResult::Draw => write!(f, "1/2-1/2"),
             GameResult::BlackWins => write!(f, "0-1"),
             GameResult::DoubleLoss => write!(f, "0-0"),
+            GameResult::DoubleForfeit => write!(f, "0F-0F"),
         }
     }
 }
@@ -85,6 +189,16 @@ pub enum PlayerResult {
     Draw,
 }
 
+impl Display for PlayerResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlayerResult::Win => write!(f, "win"),
+            PlayerResult::Lose => write!(f, "loss"),
+            PlayerResult::Draw => write!(f, "draw"),
+        }
+    }
+}
+
 impl PlayerResult {
     pub fn from_str<S: AsRef<str>>(str: S) -> Self {
         match str.as_ref().trim() {
@@ -132,11 +246,143 @@ impl Display for PlayerStatus {
     }
 }
 
-#[derive(Default, Debug)]
+// Who can see a tournament, organizers and admins always can
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Visibility {
+    #[default]
+    Public,
+    // Readable by anyone with the link, but not listed
+    Unlisted,
+    Private,
+}
+
+impl TryFrom<&str> for Visibility {
+    type Error = AppError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "public" => Ok(Self::Public),
+            "unlisted" => Ok(Self::Unlisted),
+            "private" => Ok(Self::Private),
+            _ => Err(AppError::InvalidVisibility(value.to_owned())),
+        }
+    }
+}
+
+impl Display for Visibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "public"),
+            Visibility::Unlisted => write!(f, "unlisted"),
+            Visibility::Private => write!(f, "private"),
+        }
+    }
+}
+
+// Role of a co-organizer, the tournament creator and admins are always owners
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TournamentRole {
+    // Everything but managing the co-organizers
+    #[default]
+    Owner,
+    // Results only
+    Arbiter,
+    // Organizer-only data, no changes
+    Viewer,
+}
+
+impl TournamentRole {
+    pub fn can_enter_results(&self) -> bool {
+        matches!(self, Self::Owner | Self::Arbiter)
+    }
+}
+
+impl TryFrom<&str> for TournamentRole {
+    type Error = AppError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "owner" => Ok(Self::Owner),
+            "arbiter" => Ok(Self::Arbiter),
+            "viewer" => Ok(Self::Viewer),
+            _ => Err(AppError::InvalidTournamentRole(value.to_owned())),
+        }
+    }
+}
+
+impl Display for TournamentRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TournamentRole::Owner => write!(f, "owner"),
+            TournamentRole::Arbiter => write!(f, "arbiter"),
+            TournamentRole::Viewer => write!(f, "viewer"),
+        }
+    }
+}
+
+// Escapes text for HTML element content and attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Name of a registered player, displayed as "Last, First". Fields are ordered so that names
+// sort by last name first
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PlayerName {
+    last: String,
+    first: String,
+}
+
+impl PlayerName {
+    // Surrounding whitespace is trimmed and inner runs of whitespace collapsed
+    pub fn new(first: &str, last: &str) -> Self {
+        let normalize = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+        Self {
+            last: normalize(last),
+            first: normalize(first),
+        }
+    }
+    pub fn to_html(&self) -> String {
+        escape_html(&self.to_string())
+    }
+}
+
+// Reads the display form back, everything before the first comma is the last name
+impl From<&str> for PlayerName {
+    fn from(value: &str) -> Self {
+        match value.split_once(',') {
+            Some((last, first)) => Self::new(first, last),
+            None => Self::new("", value),
+        }
+    }
+}
+
+impl Display for PlayerName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.first.is_empty() {
+            write!(f, "{}", self.last)
+        } else {
+            write!(f, "{}, {}", self.last, self.first)
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug)]
 pub struct Player {
     pub id: u32,
     pub db_id: u32,
-    pub name: String,
+    pub name: PlayerName,
     pub rating: u32,
     pub title: Title,
     pub history: Vec<HistoryItem>,
@@ -144,15 +390,58 @@ pub struct Player {
     pub fide_id: Option<usize>,
     pub federation: Option<String>,
     pub status: PlayerStatus,
+    pub section_id: Option<u32>,
+    // Rounds (0-based) the player asked to sit out, scored with the requested bye score
+    pub requested_bye_rounds: Vec<u32>,
+}
+
+// Player ids by starting rank. Seeding order: rating, title (highest first), rated before
+// unrated and finally name (alphabetical). Unrated players (rating 0) take the organizer's
+// default rating if any, otherwise they are seeded after every rated player, by name only
+pub fn seeding_order<'a>(
+    players: impl Iterator<Item = &'a Player>,
+    unrated_default_rating: Option<u32>,
+) -> Vec<u32> {
+    let seeding_rating = |player: &Player| {
+        if player.rating > 0 {
+            Some(player.rating)
+        } else {
+            unrated_default_rating
+        }
+    };
+    // Titles only break ties between players seeded by a rating
+    let seeding_prestige =
+        |player: &Player| seeding_rating(player).map(|_| player.title.prestige());
+    let mut players: Vec<&Player> = players.collect();
+    players.sort_by(|a, b| {
+        seeding_rating(b)
+            .cmp(&seeding_rating(a))
+            .then_with(|| seeding_prestige(b).cmp(&seeding_prestige(a)))
+            .then_with(|| (a.rating == 0).cmp(&(b.rating == 0)))
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    players.into_iter().map(|player| player.id).collect()
 }
 
 impl Player {
     pub fn color_history(&self) -> Vec<Color> {
+        self.colors_before(self.history.len())
+    }
+
+    // Colors of the games played before the given round (0-based). A double forfeit was never
+    // played and leaves no color
+    pub fn colors_before(&self, round: usize) -> Vec<Color> {
         self.history
             .iter()
+            .take(round)
             .filter_map(|item| match item {
                 HistoryItem::NotPaired { score: _ } => None,
                 HistoryItem::Bye => None,
+                HistoryItem::Game {
+                    result: GameResult::DoubleForfeit,
+                    ..
+                } => None,
                 HistoryItem::Game {
                     opponent_id: _,
                     color,
@@ -162,23 +451,30 @@ impl Player {
             .collect()
     }
 
+    // Score of the rounds played before the given round (0-based)
+    pub fn score_before(&self, round: usize) -> u32 {
+        self.history
+            .iter()
+            .take(round)
+            .map(|item| history_item_points(item, BYE_POINTS))
+            .sum()
+    }
+
     pub fn has_played(&self, player_id: u32) -> bool {
+        self.times_played(player_id) > 0
+    }
+
+    // Meetings with the given player, forfeits included like in `has_played`
+    pub fn times_played(&self, player_id: u32) -> usize {
         self.history
             .iter()
-            .filter_map(|item| match item {
-                HistoryItem::NotPaired { score: _ } => None,
-                HistoryItem::Bye => None,
-                HistoryItem::Game {
-                    opponent_id,
-                    color: _,
-                    result: _,
-                } => Some(opponent_id),
-            })
-            .any(|id| *id == player_id)
+            .filter_map(|item| item.opponent(true))
+            .filter(|id| *id == player_id)
+            .count()
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum HistoryItem {
     NotPaired {
         score: u32,
@@ -191,7 +487,40 @@ pub enum HistoryItem {
     },
 }
 
-#[derive(Default, Debug, PartialEq, Eq, PartialOrd, Ord)]
+impl HistoryItem {
+    // Opponent of a paired round. Pairing counts forfeits as played so they are never
+    // repeated, tiebreaks leave them out since the game never took place
+    pub fn opponent(&self, include_forfeits: bool) -> Option<u32> {
+        match self {
+            HistoryItem::Game {
+                opponent_id,
+                color: _,
+                result,
+            } if include_forfeits || !result.is_forfeit() => Some(*opponent_id),
+            _ => None,
+        }
+    }
+}
+
+// A bye is worth a full point, in half points
+pub const BYE_POINTS: u32 = 2;
+
+// Half points a round of the history is worth, the single scoring rule of every score and
+// tiebreak
+pub fn history_item_points(item: &HistoryItem, bye_value: u32) -> u32 {
+    match item {
+        HistoryItem::NotPaired { score } => *score,
+        HistoryItem::Bye => bye_value,
+        HistoryItem::Game {
+            opponent_id: _,
+            color,
+            result,
+        } => result.points_for(*color),
+    }
+}
+
+// Declaration order carries no meaning, compare titles with `prestige`
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
 pub enum Title {
     #[default]
     Untitled,
@@ -208,6 +537,25 @@ pub enum Title {
 }
 
 impl Title {
+    // Rank used for seeding, higher is more prestigious: the open FIDE titles each rank
+    // above the women's title of the same name (GM > IM > WGM > FM > WIM > CM > WFM > WCM),
+    // national titles come below every FIDE title, NM above WNM
+    pub fn prestige(&self) -> u8 {
+        match self {
+            Title::GM => 10,
+            Title::IM => 9,
+            Title::WGM => 8,
+            Title::FM => 7,
+            Title::WIM => 6,
+            Title::CM => 5,
+            Title::WFM => 4,
+            Title::WCM => 3,
+            Title::NM => 2,
+            Title::WNM => 1,
+            Title::Untitled => 0,
+        }
+    }
+
     pub fn from_str<S: AsRef<str>>(str: S) -> Self {
         match str.as_ref().to_lowercase().trim() {
             "wnm" => Self::WNM,
@@ -253,6 +601,37 @@ impl Display for Title {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimeCategory {
+    Blitz,
+    Rapid,
+    Standard,
+}
+
+impl TryFrom<&String> for TimeCategory {
+    type Error = AppError;
+
+    fn try_from(value: &String) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "blitz" => Ok(Self::Blitz),
+            "rapid" => Ok(Self::Rapid),
+            "standard" => Ok(Self::Standard),
+            _ => Err(AppError::InvalidTimeCategory(value.to_string())),
+        }
+    }
+}
+
+// Loose check of a time control: periods separated by `:`, each `[moves/]base[+increment]`
+pub fn is_valid_time_control(value: &str) -> bool {
+    let number = |s: &str| !s.is_empty() && s.len() <= 4 && s.bytes().all(|b| b.is_ascii_digit());
+    !value.is_empty()
+        && value.split(':').all(|period| {
+            let (time, increment) = period.split_once('+').unwrap_or((period, "0"));
+            let (moves, base) = time.split_once('/').unwrap_or(("1", time));
+            number(moves) && number(base) && number(increment)
+        })
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Color {
     White,
@@ -268,11 +647,107 @@ impl Color {
     }
 }
 
+impl TryFrom<&str> for Color {
+    type Error = AppError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "white" => Ok(Color::White),
+            "black" => Ok(Color::Black),
+            _ => Err(AppError::InvalidColor(value.to_string())),
+        }
+    }
+}
+
+// Whites minus blacks, positive when the player had white more often
+pub fn color_balance(colors: &[Color]) -> i32 {
+    colors.iter().fold(0, |acc, c| match c {
+        Color::White => acc + 1,
+        Color::Black => acc - 1,
+    })
+}
+
+// Rule that decided which player of a board got white
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorRule {
+    // First round, colors alternate down the boards starting from the chosen first color
+    InitialColor,
+    // Neither player has a game yet, the order of the pairing is kept
+    Unconstrained,
+    // Players alternate from the color they had in their last game
+    Alternation,
+    // Same last color, the player with more whites gets black
+    BalanceCorrection,
+    // Same last color and balance, the better seeded player (lower tpn) gets black
+    TpnTiebreak,
+    // Second game of two players who met once, the colors of their first game reversed
+    ReversedRematch,
+    // Fixed pairing or color override chosen by the arbiter
+    ArbiterDecision,
+}
+
+// A player as seen by the color allocation of a pairing
+pub struct ColorCandidate<'a> {
+    pub id: u32,
+    pub colors: &'a [Color],
+}
+
+// Returns (white, black) and the rule that decided the colors, `tpn_cmp` compares the
+// starting rank of p1 to the one of p2
+pub fn allocate_colors(
+    p1: ColorCandidate,
+    p2: ColorCandidate,
+    tpn_cmp: std::cmp::Ordering,
+) -> (u32, u32, ColorRule) {
+    let keep = (p1.id, p2.id);
+    let swap = (p2.id, p1.id);
+    let ((white, black), rule) = match (p1.colors.last(), p2.colors.last()) {
+        (None, None) => (keep, ColorRule::Unconstrained),
+        (None, Some(p2_last)) => match p2_last {
+            Color::Black => (swap, ColorRule::Alternation),
+            Color::White => (keep, ColorRule::Alternation),
+        },
+        (Some(p1_last), None) => match p1_last {
+            Color::White => (swap, ColorRule::Alternation),
+            Color::Black => (keep, ColorRule::Alternation),
+        },
+        // Different colors in the last round, both players alternate
+        (Some(p1_last), Some(p2_last)) if p1_last != p2_last => match p1_last {
+            Color::White => (swap, ColorRule::Alternation),
+            Color::Black => (keep, ColorRule::Alternation),
+        },
+        // Same color in the last round, the color balance decides, then the tpn
+        _ => {
+            let (p1_balance, p2_balance) = (color_balance(p1.colors), color_balance(p2.colors));
+            match p1_balance.cmp(&p2_balance) {
+                std::cmp::Ordering::Greater => (swap, ColorRule::BalanceCorrection),
+                std::cmp::Ordering::Less => (keep, ColorRule::BalanceCorrection),
+                std::cmp::Ordering::Equal if tpn_cmp.is_lt() => (swap, ColorRule::TpnTiebreak),
+                std::cmp::Ordering::Equal => (keep, ColorRule::TpnTiebreak),
+            }
+        }
+    };
+    (white, black, rule)
+}
+
+// Color allocation of one board, with the balances the players had before the round
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorDecision {
+    pub board_number: u32,
+    pub white_id: u32,
+    pub black_id: u32,
+    pub white_balance: i32,
+    pub black_balance: i32,
+    pub rule: ColorRule,
+}
+
+#[derive(Debug)]
 pub struct NewPairings {
     pub round: u32,
     pub pairings: Vec<NewDbPairing>,
     pub gaps: Vec<NewDbPairingGap>,
-    pub floats: Vec<u32>,
 }
 
 #[derive(Clone, Copy, Debug, Serialize)]
@@ -284,6 +759,8 @@ pub struct PlayerStanding {
     pub median_buchholz: u32,
     pub cut_one_buchholz: u32,
     pub progressive: u32,
+    // Progressive score without the running score after the first round
+    pub cumulative_cut1: u32,
 }
 
 impl PlayerStanding {
@@ -295,6 +772,63 @@ impl PlayerStanding {
             median_buchholz: 0,
             cut_one_buchholz: 0,
             progressive: 0,
+            cumulative_cut1: 0,
+        }
+    }
+}
+
+// Tiebreaks applied in order to players on the same score, higher values rank first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Tiebreak {
+    MedianBuchholz,
+    CutOneBuchholz,
+    Buchholz,
+    Progressive,
+    CumulativeCut1,
+}
+
+impl Tiebreak {
+    pub const DEFAULT: [Tiebreak; 4] = [
+        Tiebreak::MedianBuchholz,
+        Tiebreak::CutOneBuchholz,
+        Tiebreak::Buchholz,
+        Tiebreak::Progressive,
+    ];
+
+    pub fn value(&self, standing: &PlayerStanding) -> u32 {
+        match self {
+            Tiebreak::MedianBuchholz => standing.median_buchholz,
+            Tiebreak::CutOneBuchholz => standing.cut_one_buchholz,
+            Tiebreak::Buchholz => standing.buchholz,
+            Tiebreak::Progressive => standing.progressive,
+            Tiebreak::CumulativeCut1 => standing.cumulative_cut1,
+        }
+    }
+}
+
+impl TryFrom<&str> for Tiebreak {
+    type Error = AppError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "median_buchholz" => Ok(Self::MedianBuchholz),
+            "cut_one_buchholz" => Ok(Self::CutOneBuchholz),
+            "buchholz" => Ok(Self::Buchholz),
+            "progressive" => Ok(Self::Progressive),
+            "cumulative_cut1" => Ok(Self::CumulativeCut1),
+            _ => Err(AppError::InvalidTiebreak(value.to_owned())),
+        }
+    }
+}
+
+impl Display for Tiebreak {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tiebreak::MedianBuchholz => write!(f, "median_buchholz"),
+            Tiebreak::CutOneBuchholz => write!(f, "cut_one_buchholz"),
+            Tiebreak::Buchholz => write!(f, "buchholz"),
+            Tiebreak::Progressive => write!(f, "progressive"),
+            Tiebreak::CumulativeCut1 => write!(f, "cumulative_cut1"),
         }
     }
 }
@@ -22,6 +22,40 @@ pub struct NewTournament {
     pub start_date: u32,
     pub federation: String,
     pub url: Option<String>,
+    // Maximum score difference (in half points) between paired players
+    pub max_float_gap: Option<u32>,
+    // Score (in half points) given for each missed round to late entries without absent_results
+    pub late_entry_default_score: Option<u32>,
+    // Rating assumed for unrated players when seeding, never stored on the registration
+    pub unrated_default_rating: Option<u32>,
+    // Number added to every board, so a second section can continue the board numbering
+    pub board_offset: Option<u32>,
+    // Allow replaying an opponent, with a heavy penalty, when a round cannot be paired otherwise
+    pub allow_repeat_pairings: Option<bool>,
+    // public (default), unlisted (readable by link, not listed) or private (organizers only)
+    pub visibility: Option<String>,
+    // Half points for a bye requested in advance, 1 by default
+    pub requested_bye_score: Option<u32>,
+    // Games played on each board (mini-matches), 1 by default
+    pub games_per_pairing: Option<u32>,
+    pub tiebreaks: Option<Vec<String>>,
+    // Registrations allowed, MAX_PLAYERS when not set
+    pub max_players: Option<u32>,
+    // Top boards of the current round flagged for the livestream, none by default
+    pub broadcast_boards: Option<u32>,
+    // Lets any user register themselves once, organizers only by default
+    pub self_registration: Option<bool>,
+    // Same color three times in a row, with a heavy penalty, when a round cannot be paired
+    // otherwise. false by default
+    pub allow_three_same_colors: Option<bool>,
+    // Actual time control, e.g. 90+30 or 40/90+30:30+30, None when not given
+    pub time_control: Option<String>,
+    // Mild penalty for opponents already met in the organizer's recent tournaments. false by default
+    pub avoid_recent_opponents: Option<bool>,
+    // Rematches of players who met once, when colors reverse, for double round events. false by default
+    pub allow_rematch_reversed_colors: Option<bool>,
+    // No pairing bye in the last round for players who can still tie for first. false by default
+    pub avoid_last_round_contender_bye: Option<bool>,
 }
 
 #[derive(Deserialize)]
@@ -31,6 +65,13 @@ pub struct NewRegistration {
     pub rating: u32,
     pub status: String,
     pub absent_results: Vec<String>,
+    // Section the player is paired in, None for tournaments without sections
+    #[serde(default)]
+    pub section_id: Option<u32>,
+    // Rounds (0-based) the player will sit out, each one scored as a requested bye. Also
+    // accepted as `unavailableRounds` for players who arrive late or leave early
+    #[serde(default, alias = "unavailableRounds")]
+    pub requested_bye_rounds: Vec<u32>,
 }
 
 #[derive(Deserialize)]
@@ -38,6 +79,13 @@ pub struct NewRegistration {
 pub struct NextPairings {
     pub first_color: Option<String>,
     pub inactive_scores: Vec<(u32, String)>,
+    #[serde(default)]
+    pub fixed_pairings: Vec<(u32, u32)>,
+    #[serde(default)]
+    pub fixed_byes: Vec<u32>,
+    // (player id, "white" | "black") forced after the color allocation, may unbalance colors
+    #[serde(default)]
+    pub color_overrides: Vec<(u32, String)>,
 }
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -45,6 +93,36 @@ pub struct RoundResult {
     pub round_id: u32,
     pub board_id: u32,
     pub result: String,
+    // Game (0-based) of a mini-match, only for tournaments with several games per pairing
+    #[serde(default)]
+    pub game_number: Option<u32>,
+    // Result the client last saw on the board, the update is rejected if it changed since
+    #[serde(default)]
+    pub expected_result: Option<String>,
+    // Marks a draw as agreed between the players, metadata only, scored like any other draw
+    #[serde(default)]
+    pub agreed: Option<bool>,
+}
+
+// Hypothetical results, only applied to a copy of the tournament in memory
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedResults {
+    pub results: Vec<RoundResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergePlayers {
+    pub keep_id: u32,
+    pub remove_id: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayoffResult {
+    pub game_id: u32,
+    pub result: String,
 }
 
 #[derive(Deserialize)]
@@ -54,12 +132,94 @@ pub struct PlayerStatusPayload {
     pub status: String,
 }
 
+#[derive(Deserialize)]
+pub struct VisibilityPayload {
+    pub visibility: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeControlPayload {
+    // None or empty to clear it
+    pub time_control: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FideLookup {
+    // Time category the player is looked up for, its rating becomes the primary rating
+    pub category: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StandingsQuery {
+    // Round (0-based) after which the standings are taken, the latest one when missing
+    pub round: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneQuery {
+    // Registers the players of the source tournament again, for league series
+    pub registrations: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationsQuery {
+    // Only the active players when true, only the inactive ones when false
+    pub active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl Pagination {
+    pub const MAX_PAGE_SIZE: u32 = 200;
+
+    // Pages start at 1
+    pub fn page(&self) -> u32 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn page_size(&self) -> u32 {
+        self.page_size.unwrap_or(50).clamp(1, Self::MAX_PAGE_SIZE)
+    }
+
+    // Pages past the last row are empty, whatever their number
+    pub fn offset(&self) -> u32 {
+        (self.page() - 1).saturating_mul(self.page_size())
+    }
+}
+
+#[derive(Deserialize)]
+pub struct NewSection {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct NewOrganizer {
+    pub username: String,
+    // owner (default), arbiter or viewer
+    pub role: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct LoginPayload {
     pub username: String,
     pub password: String,
 }
 
+#[derive(Deserialize)]
+pub struct VerifyEmail {
+    pub token: String,
+}
+
 #[derive(Deserialize)]
 pub struct NewUser {
     pub username: String,
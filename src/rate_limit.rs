@@ -0,0 +1,139 @@
+use std::{
+    collections::HashMap,
+    env,
+    hash::Hash,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use axum::{
+    extract::{ConnectInfo, Path, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::errors::AppError;
+
+// Per-key buckets are dropped once the map grows past this size and the bucket is full again
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+// In-memory token bucket limiter, each key gets `capacity` requests refilled over a minute
+#[derive(Debug)]
+pub struct RateLimiter<K> {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Mutex<HashMap<K, Bucket>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn per_minute(requests: u32) -> Self {
+        Self {
+            capacity: requests as f64,
+            refill_per_sec: requests as f64 / 60.0,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Reads the number of requests per minute from the env variable `name`
+    pub fn from_env(name: &str, default: u32) -> Self {
+        let requests = env::var(name)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default);
+        Self::per_minute(requests)
+    }
+
+    pub fn try_acquire(&self, key: K) -> bool {
+        self.try_acquire_at(key, Instant::now())
+    }
+
+    fn try_acquire_at(&self, key: K, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > MAX_TRACKED_KEYS {
+            buckets.retain(|_, b| {
+                b.tokens + now.duration_since(b.last_refill).as_secs_f64() * self.refill_per_sec
+                    < self.capacity
+            });
+        }
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: self.capacity,
+            last_refill: now,
+        });
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.last_refill = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+// Global limit, shared by every client
+pub async fn global_rate_limit(
+    State(limiter): State<Arc<RateLimiter<()>>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !limiter.try_acquire(()) {
+        return AppError::RateLimited.into_response();
+    }
+    next.run(req).await
+}
+
+// Limit per id in the path, e.g. per player for requests hitting an external service
+pub async fn path_id_rate_limit(
+    State(limiter): State<Arc<RateLimiter<u32>>>,
+    Path(id): Path<u32>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !limiter.try_acquire(id) {
+        return AppError::RateLimited.into_response();
+    }
+    next.run(req).await
+}
+
+// Limit per client ip, requires the server to be started with connect info
+pub async fn ip_rate_limit(
+    State(limiter): State<Arc<RateLimiter<IpAddr>>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !limiter.try_acquire(addr.ip()) {
+        return AppError::RateLimited.into_response();
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::RateLimiter;
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = RateLimiter::per_minute(2);
+        let start = Instant::now();
+        assert!(limiter.try_acquire_at("a", start));
+        assert!(limiter.try_acquire_at("a", start));
+        assert!(!limiter.try_acquire_at("a", start));
+        // Keys have their own buckets
+        assert!(limiter.try_acquire_at("b", start));
+        // One token is refilled every 30 seconds
+        assert!(!limiter.try_acquire_at("a", start + Duration::from_secs(20)));
+        assert!(limiter.try_acquire_at("a", start + Duration::from_secs(31)));
+        assert!(!limiter.try_acquire_at("a", start + Duration::from_secs(31)));
+    }
+}
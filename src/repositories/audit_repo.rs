@@ -0,0 +1,86 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+use sqlx::{Sqlite, Transaction, prelude::FromRow};
+
+// The string representation is persisted, so existing values must never change
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    ResultUpdated,
+    PairingsGenerated,
+    RegistrationStatusUpdated,
+    RegistrationDeleted,
+    TournamentEnded,
+    PlayoffGenerated,
+    PlayoffResultUpdated,
+    ResultsAnnulled,
+    ResultsRestored,
+    TournamentImported,
+    RoundRegenerated,
+    TournamentCloned,
+}
+
+impl Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditAction::ResultUpdated => write!(f, "result_updated"),
+            AuditAction::PairingsGenerated => write!(f, "pairings_generated"),
+            AuditAction::RegistrationStatusUpdated => write!(f, "registration_status_updated"),
+            AuditAction::RegistrationDeleted => write!(f, "registration_deleted"),
+            AuditAction::TournamentEnded => write!(f, "tournament_ended"),
+            AuditAction::PlayoffGenerated => write!(f, "playoff_generated"),
+            AuditAction::PlayoffResultUpdated => write!(f, "playoff_result_updated"),
+            AuditAction::ResultsAnnulled => write!(f, "results_annulled"),
+            AuditAction::ResultsRestored => write!(f, "results_restored"),
+            AuditAction::TournamentImported => write!(f, "tournament_imported"),
+            AuditAction::RoundRegenerated => write!(f, "round_regenerated"),
+            AuditAction::TournamentCloned => write!(f, "tournament_cloned"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct DbAuditEntry {
+    pub id: u32,
+    pub user_id: u32,
+    pub username: String,
+    pub action: String,
+    pub detail: String,
+    pub created_at: u32,
+}
+
+pub async fn record(
+    tx: &mut Transaction<'_, Sqlite>,
+    user_id: u32,
+    tournament_id: u32,
+    action: AuditAction,
+    detail: String,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        "insert into audit_log (user_id, tournament_id, action, detail) values (?1, ?2, ?3, ?4)",
+    )
+    .bind(user_id)
+    .bind(tournament_id)
+    .bind(action.to_string())
+    .bind(detail)
+    .execute(tx.as_mut())
+    .await?;
+    Ok(())
+}
+
+pub async fn select_audit_log(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+) -> sqlx::Result<Vec<DbAuditEntry>> {
+    sqlx::query_as(
+        "select a.id, a.user_id, u.username, a.action, a.detail, a.created_at
+        from audit_log a
+        inner join users u on u.id = a.user_id
+        where a.tournament_id = ?1
+        order by a.created_at desc, a.id desc",
+    )
+    .bind(tournament_id)
+    .fetch_all(pool)
+    .await
+}
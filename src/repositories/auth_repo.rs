@@ -28,30 +28,62 @@ pub async fn get_user(pool: &sqlx::SqlitePool, username: &str) -> Result<DbUser,
     }
 }
 
+pub async fn find_user_id(pool: &sqlx::SqlitePool, username: &str) -> sqlx::Result<Option<u32>> {
+    sqlx::query_scalar("select id from users where username = ?")
+        .bind(username)
+        .fetch_optional(pool)
+        .await
+}
+
 pub async fn create_user(
     pool: &sqlx::SqlitePool,
     username: &str,
     password_hash: &str,
     email: Option<String>,
+    verification_token: Option<&str>,
 ) -> sqlx::Result<i64> {
-    let result =
-        sqlx::query("insert into users (username, password_hash, email, role) values (?, ?, ?, ?)")
-            .bind(username)
-            .bind(password_hash)
-            .bind(email)
-            .bind("standard")
-            .execute(pool)
-            .await?;
+    let result = sqlx::query(
+        "insert into users (username, password_hash, email, role, verification_token) values (?, ?, ?, ?, ?)",
+    )
+    .bind(username)
+    .bind(password_hash)
+    .bind(email)
+    .bind("standard")
+    .bind(verification_token)
+    .execute(pool)
+    .await?;
     Ok(result.last_insert_rowid())
 }
 
+// Returns the number of verified users, 0 if the token does not exist
+pub async fn verify_email(pool: &sqlx::SqlitePool, token: &str) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        "update users set email_verified = 1, verification_token = null where verification_token = ?",
+    )
+    .bind(token)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+// Users without an email have no address to verify and count as verified
+pub async fn is_email_verified(pool: &sqlx::SqlitePool, user_id: u32) -> sqlx::Result<bool> {
+    let verified: Option<bool> =
+        sqlx::query_scalar("select email_verified or email is null from users where id = ?")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(verified.unwrap_or(false))
+}
+
 pub async fn create_admin(
     pool: &sqlx::SqlitePool,
     username: &str,
     password_hash: &str,
 ) -> sqlx::Result<i64> {
-    let result =
-        sqlx::query("insert or ignore into users (username, password_hash, role) values (?, ?, ?)")
+    let result = sqlx::query(
+        "insert or ignore into users (username, password_hash, role, email_verified) values (?, ?, ?, 1)",
+    )
             .bind(username)
             .bind(password_hash)
             .bind("admin")
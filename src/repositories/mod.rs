@@ -1,5 +1,9 @@
+pub mod audit_repo;
 pub mod auth_repo;
+pub mod organizer_repo;
 pub mod pairing_repo;
 pub mod player_repo;
+pub mod playoff_repo;
 pub mod registration_repo;
+pub mod section_repo;
 pub mod tournament_repo;
@@ -0,0 +1,61 @@
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+
+use crate::models::tournament::TournamentRole;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct DbOrganizer {
+    pub user_id: u32,
+    pub username: String,
+    pub role: String,
+}
+
+// Adding an existing co-organizer again changes their role
+pub async fn add_organizer(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    user_id: u32,
+    role: TournamentRole,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        "insert into tournament_organizers (tournament_id, user_id, role) values (?1, ?2, ?3)
+        on conflict (tournament_id, user_id) do update set role = excluded.role",
+    )
+    .bind(tournament_id)
+    .bind(user_id)
+    .bind(role.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn remove_organizer(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    user_id: u32,
+) -> sqlx::Result<u64> {
+    let result =
+        sqlx::query("delete from tournament_organizers where tournament_id = ?1 and user_id = ?2")
+            .bind(tournament_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn select_organizers(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+) -> sqlx::Result<Vec<DbOrganizer>> {
+    sqlx::query_as(
+        "select u.id as user_id, u.username, o.role
+        from tournament_organizers o
+        inner join users u on u.id = o.user_id
+        where o.tournament_id = ?1
+        order by o.created_at",
+    )
+    .bind(tournament_id)
+    .fetch_all(pool)
+    .await
+}
@@ -1,7 +1,13 @@
+use itertools::Itertools;
 use sqlx::prelude::FromRow;
 
 use crate::{
-    models::tournament::GameResult, repositories::tournament_repo::mark_tournament_updated,
+    errors::AppError,
+    models::tournament::GameResult,
+    repositories::{
+        audit_repo::{self, AuditAction},
+        tournament_repo::mark_tournament_updated,
+    },
 };
 
 #[derive(FromRow)]
@@ -14,8 +20,19 @@ pub struct DbPairing {
     pub black_id: u32,
     pub result: Option<String>,
     pub pgn: Option<String>,
+    pub games: Option<String>,
 }
 
+// Individual game results of a mini-match, padded with ongoing games up to `count`
+fn parse_games(games: Option<&str>, count: usize) -> Vec<GameResult> {
+    let mut games: Vec<GameResult> = games
+        .map(|g| g.split(',').map(GameResult::from_str).collect())
+        .unwrap_or_default();
+    games.resize(count, GameResult::Ongoing);
+    games
+}
+
+#[derive(Debug)]
 pub struct NewDbPairing {
     pub tournament_id: u32,
     pub round_number: u32,
@@ -62,22 +79,824 @@ pub async fn select_pairing_gaps(
         .await
 }
 
+#[derive(FromRow)]
+pub struct DbPlayerGame {
+    pub tournament_id: u32,
+    pub tournament_name: String,
+    pub round_number: u32,
+    pub board_number: u32,
+    pub is_white: bool,
+    pub opponent_id: u32,
+    pub opponent_first_name: String,
+    pub opponent_last_name: String,
+    pub result: Option<String>,
+}
+
+// Games of a player (players.id) across all tournaments, most recently updated tournaments first
+pub async fn select_player_games(
+    pool: &sqlx::SqlitePool,
+    player_id: u32,
+    limit: u32,
+    offset: u32,
+) -> sqlx::Result<Vec<DbPlayerGame>> {
+    sqlx::query_as(
+        "select
+            t.id as tournament_id,
+            t.name as tournament_name,
+            p.round_number,
+            p.board_number + t.board_offset as board_number,
+            rw.player_id = ?1 as is_white,
+            op.id as opponent_id,
+            op.first_name as opponent_first_name,
+            op.last_name as opponent_last_name,
+            p.result
+        from pairings p
+        inner join registrations rw on rw.id = p.white_id
+        inner join registrations rb on rb.id = p.black_id
+        inner join tournaments t on t.id = p.tournament_id
+        inner join players op on op.id = case when rw.player_id = ?1 then rb.player_id else rw.player_id end
+        where (rw.player_id = ?1 or rb.player_id = ?1) and t.visibility != 'private'
+            and p.result is not '0F-0F'
+        order by t.updated_at desc, t.id desc, p.round_number desc
+        limit ?2 offset ?3",
+    )
+    .bind(player_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+}
+
+// Games between two players (players.id) across all tournaments, from the point of view of player_id
+pub async fn head_to_head(
+    pool: &sqlx::SqlitePool,
+    player_id: u32,
+    opponent_id: u32,
+) -> sqlx::Result<Vec<DbPlayerGame>> {
+    sqlx::query_as(
+        "select
+            t.id as tournament_id,
+            t.name as tournament_name,
+            p.round_number,
+            p.board_number + t.board_offset as board_number,
+            rw.player_id = ?1 as is_white,
+            op.id as opponent_id,
+            op.first_name as opponent_first_name,
+            op.last_name as opponent_last_name,
+            p.result
+        from pairings p
+        inner join registrations rw on rw.id = p.white_id
+        inner join registrations rb on rb.id = p.black_id
+        inner join tournaments t on t.id = p.tournament_id
+        inner join players op on op.id = ?2
+        where ((rw.player_id = ?1 and rb.player_id = ?2) or (rw.player_id = ?2 and rb.player_id = ?1))
+            and t.visibility != 'private' and p.result is not '0F-0F'
+        order by t.updated_at desc, t.id desc, p.round_number desc",
+    )
+    .bind(player_id)
+    .bind(opponent_id)
+    .fetch_all(pool)
+    .await
+}
+
+// Compare-and-set when an expected result is given, returns the number of updated boards,
+// 0 if the stored result no longer matches the expected one. The comparison is part of the
+// update, which takes the write lock right away instead of upgrading a read
+#[allow(clippy::too_many_arguments)]
 pub async fn update_game_result(
     pool: &sqlx::SqlitePool,
+    user_id: u32,
     tournament_id: u32,
     round_id: u32,
     board_id: u32,
     result: GameResult,
-) -> sqlx::Result<()> {
+    agreed: bool,
+    expected: Option<GameResult>,
+) -> sqlx::Result<u64> {
     let mut tx = pool.begin().await?;
-    sqlx::query("update pairings set result = ?1 where tournament_id = ?2 and round_number = ?3 and board_number = ?4")
+    let updated = sqlx::query("update pairings set result = ?1, agreed_draw = ?2 where tournament_id = ?3 and round_number = ?4 and board_number = ?5
+            and (?6 is null or coalesce(result, '*') = ?6)")
         .bind(result.to_string())
+        .bind(agreed)
         .bind(tournament_id)
         .bind(round_id)
         .bind(board_id)
+        .bind(expected.map(|expected| expected.to_string()))
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if updated == 0 {
+        tx.rollback().await?;
+        return Ok(0);
+    }
+    audit_repo::record(
+        &mut tx,
+        user_id,
+        tournament_id,
+        AuditAction::ResultUpdated,
+        format!(
+            "round {} board {}: {}{}",
+            round_id,
+            board_id,
+            result,
+            if agreed { " (agreed)" } else { "" }
+        ),
+    )
+    .await?;
+    mark_tournament_updated(tournament_id, &mut tx).await?;
+    tx.commit().await?;
+    Ok(updated)
+}
+
+#[derive(FromRow)]
+pub struct DbAgreedDraws {
+    pub registration_id: u32,
+    pub first_name: String,
+    pub last_name: String,
+    pub agreed_draws: u32,
+}
+
+// Every registration of the tournament with its number of agreed draws, most first
+pub async fn select_agreed_draws(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+) -> sqlx::Result<Vec<DbAgreedDraws>> {
+    sqlx::query_as(
+        "select r.id as registration_id, p.first_name, p.last_name, count(g.id) as agreed_draws
+        from registrations r
+        inner join players p on p.id = r.player_id
+        left join pairings g on g.tournament_id = r.tournament_id and g.agreed_draw
+            and (g.white_id = r.id or g.black_id = r.id)
+        where r.tournament_id = ?1
+        group by r.id
+        order by agreed_draws desc, r.id",
+    )
+    .bind(tournament_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(FromRow)]
+pub struct DbRecentMeetings {
+    // Player ids (not registrations), lower id first
+    pub player_a: u32,
+    pub player_b: u32,
+    pub games: u32,
+}
+
+// Played games between players of the tournament in the organizer's last `tournaments`
+// tournaments before it, by start date then id. Unplayed boards and double forfeits are not
+// meetings
+pub async fn select_recent_meetings(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    tournaments: u32,
+) -> sqlx::Result<Vec<DbRecentMeetings>> {
+    sqlx::query_as(
+        "select min(w.player_id, b.player_id) as player_a, max(w.player_id, b.player_id) as player_b,
+            count(*) as games
+        from pairings g
+        inner join registrations w on w.id = g.white_id
+        inner join registrations b on b.id = g.black_id
+        where g.tournament_id in (
+            select t.id from tournaments t
+            inner join tournaments current on current.id = ?1
+            where t.created_by = current.created_by
+                and (t.start_date, t.id) < (current.start_date, current.id)
+            order by t.start_date desc, t.id desc
+            limit ?2
+        )
+            and g.result is not null and g.result is not '0F-0F'
+            and w.player_id in (select player_id from registrations where tournament_id = ?1)
+            and b.player_id in (select player_id from registrations where tournament_id = ?1)
+        group by player_a, player_b",
+    )
+    .bind(tournament_id)
+    .bind(tournaments)
+    .fetch_all(pool)
+    .await
+}
+
+// Attempts at storing a mini-match game before giving up on a board that keeps changing
+const MATCH_UPDATE_ATTEMPTS: usize = 5;
+
+// Stores one game of a mini-match of `games_per_pairing` games together with the resulting
+// match score, which stays empty until every game of the board has a result. The games are only written if they did not change since they were read, another game
+// entered in the meantime is read again instead of being overwritten. Compare-and-set on the
+// game when an expected result is given, returns false if the stored game no longer matches it
+#[allow(clippy::too_many_arguments)]
+pub async fn update_match_game_result(
+    pool: &sqlx::SqlitePool,
+    user_id: u32,
+    tournament_id: u32,
+    round_id: u32,
+    board_id: u32,
+    games_per_pairing: usize,
+    game_number: u32,
+    result: GameResult,
+    expected: Option<GameResult>,
+) -> sqlx::Result<bool> {
+    for _ in 0..MATCH_UPDATE_ATTEMPTS {
+        // Read outside of the write transaction, SQLite fails a read transaction that
+        // upgrades to a write while another one does. `games is ?6` catches the change
+        let stored: Option<String> = sqlx::query_scalar("select games from pairings where tournament_id = ?1 and round_number = ?2 and board_number = ?3")
+            .bind(tournament_id)
+            .bind(round_id)
+            .bind(board_id)
+            .fetch_one(pool)
+            .await?;
+        let mut games = parse_games(stored.as_deref(), games_per_pairing);
+        if expected.is_some_and(|expected| expected != games[game_number as usize]) {
+            return Ok(false);
+        }
+        games[game_number as usize] = result;
+        let aggregated = match GameResult::aggregate(&games) {
+            GameResult::Ongoing => None,
+            result => Some(result.to_string()),
+        };
+        let mut tx = pool.begin().await?;
+        let updated = sqlx::query("update pairings set games = ?1, result = ?2 where tournament_id = ?3 and round_number = ?4 and board_number = ?5 and games is ?6")
+            .bind(games.iter().join(","))
+            .bind(aggregated)
+            .bind(tournament_id)
+            .bind(round_id)
+            .bind(board_id)
+            .bind(&stored)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if updated == 0 {
+            tx.rollback().await?;
+            continue;
+        }
+        audit_repo::record(
+            &mut tx,
+            user_id,
+            tournament_id,
+            AuditAction::ResultUpdated,
+            format!(
+                "round {} board {} game {}: {}",
+                round_id, board_id, game_number, result
+            ),
+        )
+        .await?;
+        mark_tournament_updated(tournament_id, &mut tx).await?;
+        tx.commit().await?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+// Result of a board annulled for the registration: a loss for them and a win for their
+// opponent, in every game of a mini-match
+fn annulled_board(
+    registration_id: u32,
+    white_id: u32,
+    games: Option<&str>,
+) -> (GameResult, Option<String>) {
+    let result = if white_id == registration_id {
+        GameResult::BlackWins
+    } else {
+        GameResult::WhiteWins
+    };
+    let games = games.map(|g| g.split(',').map(|_| result).join(","));
+    (result, games)
+}
+
+#[derive(FromRow)]
+struct DbAnnulledBoard {
+    id: u32,
+    round_number: u32,
+    board_number: u32,
+    white_id: u32,
+    result: Option<String>,
+    games: Option<String>,
+    agreed_draw: bool,
+    annulled_games: Option<String>,
+}
+
+// Turns every finished game of the registration into a loss for them and a win for their
+// opponent, keeping the previous result, games and agreed draw so the annulment can be undone.
+// Double forfeits and games already annulled for another player are left alone. Returns the
+// number of annulled games, None if the registration does not belong to the tournament
+pub async fn annul_player_results(
+    pool: &sqlx::SqlitePool,
+    user_id: u32,
+    tournament_id: u32,
+    registration_id: u32,
+) -> sqlx::Result<Option<u64>> {
+    let mut tx = pool.begin().await?;
+    let registered: Option<u32> =
+        sqlx::query_scalar("select id from registrations where id = ?1 and tournament_id = ?2")
+            .bind(registration_id)
+            .bind(tournament_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    if registered.is_none() {
+        tx.rollback().await?;
+        return Ok(None);
+    }
+    let boards: Vec<DbAnnulledBoard> = sqlx::query_as(
+        "select id, round_number, board_number, white_id, result, games, agreed_draw, annulled_games
+        from pairings
+        where tournament_id = ?2
+            and (white_id = ?1 or black_id = ?1)
+            and annulled_result is null
+            and result is not null
+            and result not in ('*', ?3)",
+    )
+    .bind(registration_id)
+    .bind(tournament_id)
+    .bind(GameResult::DoubleForfeit.to_string())
+    .fetch_all(&mut *tx)
+    .await?;
+    for board in &boards {
+        let (result, games) =
+            annulled_board(registration_id, board.white_id, board.games.as_deref());
+        sqlx::query(
+            "update pairings set
+                annulled_result = result,
+                annulled_games = games,
+                annulled_agreed_draw = agreed_draw,
+                annulled_registration_id = ?1,
+                result = ?2,
+                games = ?3,
+                agreed_draw = false
+            where id = ?4",
+        )
+        .bind(registration_id)
+        .bind(result.to_string())
+        .bind(games)
+        .bind(board.id)
         .execute(&mut *tx)
         .await?;
+    }
+    let annulled = boards.len() as u64;
+    audit_repo::record(
+        &mut tx,
+        user_id,
+        tournament_id,
+        AuditAction::ResultsAnnulled,
+        format!("registration {}: {} games", registration_id, annulled),
+    )
+    .await?;
+    mark_tournament_updated(tournament_id, &mut tx).await?;
+    tx.commit().await?;
+    Ok(Some(annulled))
+}
+
+// Puts back the results annulled by `annul_player_results`. Nothing is restored if a board
+// was changed after the annulment, its current result would be lost. Returns the number of
+// restored games, None if the registration does not belong to the tournament
+pub async fn restore_player_results(
+    pool: &sqlx::SqlitePool,
+    user_id: u32,
+    tournament_id: u32,
+    registration_id: u32,
+) -> Result<Option<u64>, AppError> {
+    let mut tx = pool.begin().await?;
+    let registered: Option<u32> =
+        sqlx::query_scalar("select id from registrations where id = ?1 and tournament_id = ?2")
+            .bind(registration_id)
+            .bind(tournament_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    if registered.is_none() {
+        tx.rollback().await?;
+        return Ok(None);
+    }
+    let boards: Vec<DbAnnulledBoard> = sqlx::query_as(
+        "select id, round_number, board_number, white_id, result, games, agreed_draw, annulled_games
+        from pairings
+        where tournament_id = ?1 and annulled_registration_id = ?2",
+    )
+    .bind(tournament_id)
+    .bind(registration_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    for board in &boards {
+        let (result, games) = annulled_board(
+            registration_id,
+            board.white_id,
+            board.annulled_games.as_deref(),
+        );
+        if board.result != Some(result.to_string()) || board.games != games || board.agreed_draw {
+            tx.rollback().await?;
+            return Err(AppError::AnnulledResultChanged {
+                round: board.round_number,
+                board: board.board_number,
+            });
+        }
+    }
+    let restored = sqlx::query(
+        "update pairings set
+            result = annulled_result,
+            games = annulled_games,
+            agreed_draw = coalesce(annulled_agreed_draw, false),
+            annulled_result = null,
+            annulled_games = null,
+            annulled_agreed_draw = null,
+            annulled_registration_id = null
+        where tournament_id = ?1 and annulled_registration_id = ?2",
+    )
+    .bind(tournament_id)
+    .bind(registration_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    audit_repo::record(
+        &mut tx,
+        user_id,
+        tournament_id,
+        AuditAction::ResultsRestored,
+        format!("registration {}: {} games", registration_id, restored),
+    )
+    .await?;
     mark_tournament_updated(tournament_id, &mut tx).await?;
     tx.commit().await?;
-    Ok(())
+    Ok(Some(restored))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_select_player_games(pool: sqlx::SqlitePool) {
+        sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id, result) values (1, 0, 0, 1, 2, '0-1'), (1, 1, 0, 3, 1, '=-='), (1, 1, 1, 2, 4, null), (1, 2, 0, 1, 4, '0F-0F')")
+            .execute(&pool)
+            .await
+            .expect("failed to insert pairings");
+        let games = select_player_games(&pool, 1, 10, 0)
+            .await
+            .expect("failed to select games");
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].round_number, 1);
+        assert!(!games[0].is_white);
+        assert_eq!(games[0].opponent_id, 3);
+        assert_eq!(games[1].opponent_last_name, "Nakamura");
+        let games = select_player_games(&pool, 1, 1, 1)
+            .await
+            .expect("failed to select games");
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].round_number, 0);
+        let games = head_to_head(&pool, 2, 1)
+            .await
+            .expect("failed to select head to head");
+        assert_eq!(games.len(), 1);
+        assert!(!games[0].is_white);
+        assert_eq!(games[0].opponent_id, 1);
+        assert_eq!(games[0].result.as_deref(), Some("0-1"));
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_result_conflict(pool: sqlx::SqlitePool) {
+        sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id) values (1, 0, 0, 1, 2)")
+            .execute(&pool)
+            .await
+            .expect("failed to insert pairing");
+        // First arbiter enters a result on an empty board
+        let updated = update_game_result(
+            &pool,
+            1,
+            1,
+            0,
+            0,
+            GameResult::WhiteWins,
+            false,
+            Some(GameResult::Ongoing),
+        )
+        .await
+        .expect("failed to update result");
+        assert_eq!(updated, 1);
+        // Second arbiter still sees the board as ongoing
+        let updated = update_game_result(
+            &pool,
+            1,
+            1,
+            0,
+            0,
+            GameResult::Draw,
+            false,
+            Some(GameResult::Ongoing),
+        )
+        .await
+        .expect("failed to update result");
+        assert_eq!(updated, 0);
+        let result = || async { select_pairings(&pool, 1).await.unwrap()[0].result.clone() };
+        assert_eq!(result().await.as_deref(), Some("1-0"));
+        // Without an expected result the last write wins
+        let updated = update_game_result(&pool, 1, 1, 0, 0, GameResult::Draw, false, None)
+            .await
+            .expect("failed to update result");
+        assert_eq!(updated, 1);
+        assert_eq!(result().await.as_deref(), Some("1/2-1/2"));
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_agreed_draws(pool: sqlx::SqlitePool) {
+        sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id) values (1, 0, 0, 1, 2), (1, 0, 1, 3, 4), (1, 1, 0, 3, 1)")
+            .execute(&pool)
+            .await
+            .expect("failed to insert pairings");
+        for (round, board, agreed) in [(0, 0, true), (0, 1, false), (1, 0, true)] {
+            update_game_result(&pool, 1, 1, round, board, GameResult::Draw, agreed, None)
+                .await
+                .expect("failed to update result");
+        }
+        let draws = select_agreed_draws(&pool, 1)
+            .await
+            .expect("failed to select agreed draws");
+        let counts: Vec<(u32, u32)> = draws
+            .iter()
+            .map(|d| (d.registration_id, d.agreed_draws))
+            .collect();
+        assert_eq!(counts[..4], [(1, 2), (2, 1), (3, 1), (4, 0)]);
+        // Replacing the result clears the agreed flag
+        update_game_result(&pool, 1, 1, 1, 0, GameResult::WhiteWins, false, None)
+            .await
+            .expect("failed to update result");
+        let draws = select_agreed_draws(&pool, 1)
+            .await
+            .expect("failed to select agreed draws");
+        assert_eq!(draws[0].registration_id, 1);
+        assert_eq!(draws[0].agreed_draws, 1);
+        assert_eq!(draws[2].agreed_draws, 0);
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_concurrent_match_games(pool: sqlx::SqlitePool) {
+        sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id) values (1, 0, 0, 1, 2)")
+            .execute(&pool)
+            .await
+            .expect("failed to insert pairing");
+        // Two arbiters enter different games of the board at the same time
+        let (first, second) = tokio::join!(
+            update_match_game_result(&pool, 1, 1, 0, 0, 2, 0, GameResult::WhiteWins, None),
+            update_match_game_result(&pool, 1, 1, 0, 0, 2, 1, GameResult::Draw, None),
+        );
+        assert!(first.expect("failed to store first game"));
+        assert!(second.expect("failed to store second game"));
+        let pairing = select_pairings(&pool, 1).await.unwrap().remove(0);
+        assert_eq!(pairing.games.as_deref(), Some("1-0,1/2-1/2"));
+        assert_eq!(pairing.result.as_deref(), Some("1-0"));
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_match_game_results(pool: sqlx::SqlitePool) {
+        sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id) values (1, 0, 0, 1, 2)")
+            .execute(&pool)
+            .await
+            .expect("failed to insert pairing");
+        let select = async || select_pairings(&pool, 1).await.unwrap().remove(0);
+        let mut games = parse_games(select().await.games.as_deref(), 2);
+        assert_eq!(games, vec![GameResult::Ongoing, GameResult::Ongoing]);
+        games[0] = GameResult::WhiteWins;
+        let stored = update_match_game_result(&pool, 1, 1, 0, 0, 2, 0, GameResult::WhiteWins, None)
+            .await
+            .expect("failed to store first game");
+        assert!(stored);
+        let pairing = select().await;
+        // The match is not decided until both games are done
+        assert_eq!(pairing.result, None);
+        assert_eq!(parse_games(pairing.games.as_deref(), 2), games);
+        // The game no longer holds the result the client read
+        let expected = Some(GameResult::Ongoing);
+        let stored = update_match_game_result(&pool, 1, 1, 0, 0, 2, 0, GameResult::Draw, expected)
+            .await
+            .expect("failed to compare first game");
+        assert!(!stored);
+        games[1] = GameResult::Draw;
+        let stored = update_match_game_result(&pool, 1, 1, 0, 0, 2, 1, GameResult::Draw, expected)
+            .await
+            .expect("failed to store second game");
+        assert!(stored);
+        let pairing = select().await;
+        assert_eq!(pairing.result.as_deref(), Some("1-0"));
+        assert_eq!(pairing.games.as_deref(), Some("1-0,1/2-1/2"));
+        games[1] = GameResult::BlackWins;
+        assert_eq!(GameResult::aggregate(&games), GameResult::Draw);
+        assert_eq!(
+            GameResult::aggregate(&[GameResult::DoubleLoss, GameResult::DoubleLoss]),
+            GameResult::DoubleLoss
+        );
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_annul_player_results(pool: sqlx::SqlitePool) {
+        sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id, result) values (1, 0, 0, 1, 2, '1-0'), (1, 0, 1, 3, 4, '1-0'), (1, 1, 0, 4, 1, '=-='), (1, 1, 1, 2, 3, '0F-0F'), (1, 2, 0, 1, 3, null)")
+            .execute(&pool)
+            .await
+            .expect("failed to insert pairings");
+        let results = || async {
+            select_pairings(&pool, 1)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|p| p.result)
+                .collect::<Vec<_>>()
+        };
+        let before = results().await;
+        let annulled = annul_player_results(&pool, 1, 1, 1)
+            .await
+            .expect("failed to annul results");
+        // The unfinished third round game is left alone
+        assert_eq!(annulled, Some(2));
+        assert_eq!(
+            results().await,
+            vec![
+                Some("0-1".to_string()),
+                Some("1-0".to_string()),
+                Some("1-0".to_string()),
+                Some("0F-0F".to_string()),
+                None,
+            ]
+        );
+        // Annulling twice does not lose the original results
+        assert_eq!(annul_player_results(&pool, 1, 1, 1).await.unwrap(), Some(0));
+        assert_eq!(
+            restore_player_results(&pool, 1, 1, 1).await.unwrap(),
+            Some(2)
+        );
+        assert_eq!(results().await, before);
+        assert_eq!(annul_player_results(&pool, 1, 1, 99).await.unwrap(), None);
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_annul_match_results(pool: sqlx::SqlitePool) {
+        sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id, result, games, agreed_draw) values (1, 0, 0, 1, 2, '1-0', '1-0,1/2-1/2', false), (1, 1, 0, 3, 1, '1/2-1/2', null, true)")
+            .execute(&pool)
+            .await
+            .expect("failed to insert pairings");
+        let boards = || async {
+            sqlx::query_as::<_, (Option<String>, Option<String>, bool)>(
+                "select result, games, agreed_draw from pairings order by round_number",
+            )
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+        };
+        let before = boards().await;
+        assert_eq!(annul_player_results(&pool, 1, 1, 1).await.unwrap(), Some(2));
+        assert_eq!(
+            boards().await,
+            vec![
+                (Some("0-1".to_string()), Some("0-1,0-1".to_string()), false),
+                (Some("1-0".to_string()), None, false),
+            ]
+        );
+        assert_eq!(
+            restore_player_results(&pool, 1, 1, 1).await.unwrap(),
+            Some(2)
+        );
+        assert_eq!(boards().await, before);
+
+        // A result entered after the annulment is not overwritten by the restore
+        annul_player_results(&pool, 1, 1, 1).await.unwrap();
+        sqlx::query("update pairings set result = '0-1' where round_number = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(matches!(
+            restore_player_results(&pool, 1, 1, 1).await,
+            Err(AppError::AnnulledResultChanged { round: 1, board: 0 })
+        ));
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_recent_meetings(pool: sqlx::SqlitePool) {
+        sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id, result) values (1, 0, 0, 1, 2, '1-0'), (1, 0, 1, 3, 4, '1-0'), (1, 1, 0, 2, 1, null), (1, 1, 1, 3, 1, '0-1'), (1, 2, 0, 1, 2, '0F-0F'), (1, 2, 1, 3, 2, '1/2-1/2')")
+            .execute(&pool)
+            .await
+            .expect("failed to insert pairings");
+        // Next tournament of the same organizer, player 4 does not take part
+        sqlx::query("insert into tournaments (id, created_by, name, time_category, current_round, federation, num_rounds, start_date) values (2, 1, 'Next', 'standard', 0, 'BRA', 5, 1769999999)")
+            .execute(&pool)
+            .await
+            .expect("failed to insert tournament");
+        sqlx::query("insert into registrations (tournament_id, player_id, status, rating) values (2, 1, 'active', 2840), (2, 2, 'active', 2810), (2, 3, 'active', 2795)")
+            .execute(&pool)
+            .await
+            .expect("failed to insert registrations");
+        let meetings: Vec<(u32, u32, u32)> = select_recent_meetings(&pool, 2, 10)
+            .await
+            .expect("failed to select meetings")
+            .into_iter()
+            .map(|m| (m.player_a, m.player_b, m.games))
+            .sorted()
+            .collect();
+        // Neither the unplayed board nor the double forfeit count
+        assert_eq!(meetings, vec![(1, 2, 1), (1, 3, 1), (2, 3, 1)]);
+        assert!(
+            select_recent_meetings(&pool, 2, 0)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        // Later tournaments are not looked at, also when they start the same day
+        assert!(
+            select_recent_meetings(&pool, 1, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        sqlx::query("insert into tournaments (id, created_by, name, time_category, current_round, federation, num_rounds, start_date) values (3, 1, 'Same day', 'standard', 0, 'BRA', 5, 1769999999)")
+            .execute(&pool)
+            .await
+            .expect("failed to insert tournament");
+        sqlx::query("insert into registrations (tournament_id, player_id, status, rating) values (3, 1, 'active', 2840), (3, 2, 'active', 2810)")
+            .execute(&pool)
+            .await
+            .expect("failed to insert registrations");
+        sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id, result) select 3, 0, 0, w.id, b.id, '1-0' from registrations w, registrations b where w.tournament_id = 3 and w.player_id = 1 and b.tournament_id = 3 and b.player_id = 2")
+            .execute(&pool)
+            .await
+            .expect("failed to insert pairings");
+        let meetings: Vec<(u32, u32, u32)> = select_recent_meetings(&pool, 2, 10)
+            .await
+            .expect("failed to select meetings")
+            .into_iter()
+            .map(|m| (m.player_a, m.player_b, m.games))
+            .sorted()
+            .collect();
+        assert_eq!(meetings, vec![(1, 2, 1), (1, 3, 1), (2, 3, 1)]);
+        let meetings: Vec<(u32, u32, u32)> = select_recent_meetings(&pool, 3, 10)
+            .await
+            .expect("failed to select meetings")
+            .into_iter()
+            .map(|m| (m.player_a, m.player_b, m.games))
+            .collect();
+        assert_eq!(meetings, vec![(1, 2, 1)]);
+        // The last tournament before the same day one is tournament 2, without games
+        assert!(
+            select_recent_meetings(&pool, 3, 1)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
@@ -2,9 +2,12 @@ use chrono::Utc;
 use serde::Serialize;
 use sqlx::prelude::FromRow;
 
-use crate::payloads::NewPlayer;
+use crate::{errors::AppError, payloads::NewPlayer};
 
-pub async fn create_player(pool: &sqlx::SqlitePool, player: NewPlayer) -> sqlx::Result<i64> {
+pub async fn create_player(
+    pool: impl sqlx::SqliteExecutor<'_>,
+    player: NewPlayer,
+) -> sqlx::Result<i64> {
     let now = Utc::now();
     let result = sqlx::query(
         "insert into players
@@ -27,7 +30,7 @@ pub async fn create_player(pool: &sqlx::SqlitePool, player: NewPlayer) -> sqlx::
 }
 
 pub async fn get_player_by_fide_id(
-    pool: &sqlx::SqlitePool,
+    pool: impl sqlx::SqliteExecutor<'_>,
     fide_id: i64,
 ) -> sqlx::Result<Option<DbPlayer>> {
     sqlx::query_as("select * from players where fide_id = ?1")
@@ -85,6 +88,159 @@ pub async fn list_players(pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<DbPlayer>
         .await
 }
 
+// Lowercase letters and digits only, with the accents of latin letters dropped
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .map(|c| match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+            'ç' | 'ć' | 'č' => 'c',
+            'è' | 'é' | 'ê' | 'ë' | 'ě' => 'e',
+            'ì' | 'í' | 'î' | 'ï' => 'i',
+            'ñ' | 'ń' | 'ň' => 'n',
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => 'o',
+            'ř' => 'r',
+            'ś' | 'š' => 's',
+            'ù' | 'ú' | 'û' | 'ü' | 'ů' => 'u',
+            'ý' | 'ÿ' => 'y',
+            'ź' | 'ż' | 'ž' => 'z',
+            c => c,
+        })
+        .collect()
+}
+
+// Players created without a FIDE id that are likely the same person: same normalized last
+// name, first names equal or one an abbreviation of the other, and no conflicting federation
+pub async fn find_similar(
+    pool: &sqlx::SqlitePool,
+    first_name: &str,
+    last_name: &str,
+    federation: Option<&str>,
+) -> sqlx::Result<Vec<DbPlayer>> {
+    let (first, last) = (normalize_name(first_name), normalize_name(last_name));
+    let candidates: Vec<DbPlayer> = sqlx::query_as("select * from players where fide_id is null")
+        .fetch_all(pool)
+        .await?;
+    Ok(candidates
+        .into_iter()
+        .filter(|p| normalize_name(&p.last_name) == last)
+        .filter(|p| {
+            let other = normalize_name(&p.first_name);
+            other.starts_with(&first) || first.starts_with(&other)
+        })
+        .filter(|p| match (p.federation.as_deref(), federation) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            _ => true,
+        })
+        .collect())
+}
+
+pub async fn get_player(pool: &sqlx::SqlitePool, id: u32) -> sqlx::Result<Option<DbPlayer>> {
+    sqlx::query_as("select * from players where id = ?1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+// User account the player belongs to, and the player of the user, see `link_user_player`
+pub async fn get_player_user(pool: &sqlx::SqlitePool, player_id: u32) -> sqlx::Result<Option<u32>> {
+    sqlx::query_scalar("select user_id from players where id = ?1")
+        .bind(player_id)
+        .fetch_optional(pool)
+        .await
+        .map(Option::flatten)
+}
+
+pub async fn get_user_player(pool: &sqlx::SqlitePool, user_id: u32) -> sqlx::Result<Option<u32>> {
+    sqlx::query_scalar("select id from players where user_id = ?1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
+// Moves the registrations of `remove_id` to `keep_id` and deletes `remove_id`. The FIDE id
+// and ratings the kept player lacks are taken from the removed one. Both players, and that
+// they are not registered in the same tournament, are checked in the same transaction as the
+// merge. Returns the number of moved registrations
+pub async fn merge_players(
+    pool: &sqlx::SqlitePool,
+    keep_id: u32,
+    remove_id: u32,
+) -> Result<u64, AppError> {
+    if keep_id == remove_id {
+        return Err(AppError::MergeSamePlayer(keep_id));
+    }
+    let mut tx = pool.begin().await?;
+    let kept: Option<u32> = sqlx::query_scalar("select id from players where id = ?1")
+        .bind(keep_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if kept.is_none() {
+        tx.rollback().await?;
+        return Err(AppError::PlayerNotFound(keep_id as usize));
+    }
+    let Some(removed): Option<DbPlayer> = sqlx::query_as("select * from players where id = ?1")
+        .bind(remove_id)
+        .fetch_optional(&mut *tx)
+        .await?
+    else {
+        tx.rollback().await?;
+        return Err(AppError::PlayerNotFound(remove_id as usize));
+    };
+    // Merging would register the kept player twice in the first tournament they share
+    let shared: Option<u32> = sqlx::query_scalar(
+        "select a.tournament_id
+        from registrations a
+        inner join registrations b on a.tournament_id = b.tournament_id
+        where a.player_id = ?1 and b.player_id = ?2
+        order by a.tournament_id
+        limit 1",
+    )
+    .bind(keep_id)
+    .bind(remove_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some(tournament_id) = shared {
+        tx.rollback().await?;
+        return Err(AppError::MergeRegistrationConflict(tournament_id));
+    }
+    let removed_user: Option<u32> = sqlx::query_scalar("select user_id from players where id = ?1")
+        .bind(remove_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    let moved = sqlx::query("update registrations set player_id = ?1 where player_id = ?2")
+        .bind(keep_id)
+        .bind(remove_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    sqlx::query("delete from players where id = ?1")
+        .bind(remove_id)
+        .execute(&mut *tx)
+        .await?;
+    // Only after the delete, FIDE ids and user accounts are unique
+    sqlx::query(
+        "update players set
+            fide_id = coalesce(fide_id, ?1),
+            rating = coalesce(rating, ?2),
+            rating_rapid = coalesce(rating_rapid, ?3),
+            rating_blitz = coalesce(rating_blitz, ?4),
+            user_id = coalesce(user_id, ?5)
+        where id = ?6",
+    )
+    .bind(removed.fide_id)
+    .bind(removed.rating)
+    .bind(removed.rating_rapid)
+    .bind(removed.rating_blitz)
+    .bind(removed_user)
+    .bind(keep_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(moved)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,6 +262,52 @@ mod tests {
             .expect("Player inserted");
         assert!(id >= 0);
     }
+    #[sqlx::test(fixtures(path = "../../fixtures", scripts("create_players")))]
+    async fn test_find_similar_and_merge(pool: sqlx::SqlitePool) {
+        let manual = NewPlayer {
+            first_name: "M.".to_string(),
+            last_name: "Cárlsen ".to_string(),
+            federation: None,
+            fide_id: None,
+            title: None,
+            rating: None,
+            rating_rapid: None,
+            rating_blitz: None,
+        };
+        let id = create_player(&pool, manual)
+            .await
+            .expect("failed to create player");
+        let similar = find_similar(&pool, "Magnus", "Carlsen", Some("NOR"))
+            .await
+            .expect("failed to find similar players");
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].id, id);
+        let similar = find_similar(&pool, "Hikaru", "Carlsen", None)
+            .await
+            .expect("failed to find similar players");
+        assert!(similar.is_empty());
+        let carlsen = get_player_by_fide_id(&pool, 1503014)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            merge_players(&pool, id as u32, id as u32).await,
+            Err(AppError::MergeSamePlayer(_))
+        ));
+        assert!(matches!(
+            merge_players(&pool, carlsen.id as u32, 9999).await,
+            Err(AppError::PlayerNotFound(9999))
+        ));
+        assert_eq!(
+            merge_players(&pool, carlsen.id as u32, id as u32)
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(get_player(&pool, id as u32).await.unwrap().is_none());
+        assert_eq!(list_players(&pool).await.unwrap().len(), 101);
+    }
+
     #[sqlx::test(fixtures(path = "../../fixtures", scripts("create_players")))]
     async fn test_list_players(pool: sqlx::SqlitePool) {
         let players = list_players(&pool).await.expect("failed to list players");
@@ -0,0 +1,96 @@
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+
+use crate::{
+    models::tournament::GameResult,
+    repositories::{
+        audit_repo::{self, AuditAction},
+        tournament_repo::mark_tournament_updated,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct DbPlayoffGame {
+    pub id: u32,
+    pub round_number: u32,
+    pub white_id: u32,
+    pub black_id: u32,
+    pub result: Option<String>,
+}
+
+pub async fn insert_playoff_round(
+    pool: &sqlx::SqlitePool,
+    user_id: u32,
+    tournament_id: u32,
+    round_number: u32,
+    games: &[(u32, u32)],
+) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
+    for (white_id, black_id) in games.iter() {
+        sqlx::query("insert into playoff_games (tournament_id, round_number, white_id, black_id) values (?1, ?2, ?3, ?4)")
+            .bind(tournament_id)
+            .bind(round_number)
+            .bind(white_id)
+            .bind(black_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    audit_repo::record(
+        &mut tx,
+        user_id,
+        tournament_id,
+        AuditAction::PlayoffGenerated,
+        format!("playoff round {}: {} games", round_number, games.len()),
+    )
+    .await?;
+    mark_tournament_updated(tournament_id, &mut tx).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn select_playoff_games(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+) -> sqlx::Result<Vec<DbPlayoffGame>> {
+    sqlx::query_as(
+        "select id, round_number, white_id, black_id, result from playoff_games
+        where tournament_id = ?1
+        order by round_number, id",
+    )
+    .bind(tournament_id)
+    .fetch_all(pool)
+    .await
+}
+
+// Returns the number of updated games, 0 if the game is not part of the tournament
+pub async fn update_playoff_result(
+    pool: &sqlx::SqlitePool,
+    user_id: u32,
+    tournament_id: u32,
+    game_id: u32,
+    result: GameResult,
+) -> sqlx::Result<u64> {
+    let mut tx = pool.begin().await?;
+    let updated =
+        sqlx::query("update playoff_games set result = ?1 where id = ?2 and tournament_id = ?3")
+            .bind(result.to_string())
+            .bind(game_id)
+            .bind(tournament_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    if updated > 0 {
+        audit_repo::record(
+            &mut tx,
+            user_id,
+            tournament_id,
+            AuditAction::PlayoffResultUpdated,
+            format!("playoff game {}: {}", game_id, result),
+        )
+        .await?;
+        mark_tournament_updated(tournament_id, &mut tx).await?;
+    }
+    tx.commit().await?;
+    Ok(updated)
+}
@@ -1,32 +1,82 @@
 use sqlx::prelude::FromRow;
 
 use crate::{
+    errors::AppError,
     models::tournament::{PlayerResult, PlayerStatus},
     payloads::NewRegistration,
-    repositories::pairing_repo::DbPairing,
+    repositories::{
+        audit_repo::{self, AuditAction},
+        pairing_repo::DbPairing,
+        tournament_repo::mark_tournament_updated,
+    },
 };
 
+// `user_id` links a self-registration to the user who made it, None when organizers register
+// the player
 pub async fn create_tournament_registration(
     pool: &sqlx::SqlitePool,
     tournament_id: u32,
     payload: NewRegistration,
-) -> sqlx::Result<i64> {
+    user_id: Option<u32>,
+    max_players: u32,
+) -> Result<i64, AppError> {
     let mut tx = pool.begin().await?;
-    let result = sqlx::query("insert into registrations (player_id, tournament_id, floats, status, rating) values (?1, ?2, ?3, ?4, ?5)")
+    // Counted in the insert itself, concurrent registrations cannot both take the last place
+    let result = sqlx::query("insert into registrations (player_id, tournament_id, status, rating, section_id, user_id) select ?1, ?2, ?3, ?4, ?5, ?6 where (select count(*) from registrations where tournament_id = ?2) < ?7")
         .bind(payload.player_id)
         .bind(tournament_id)
-        .bind(0)
         .bind(payload.status)
         .bind(payload.rating)
+        .bind(payload.section_id)
+        .bind(user_id)
+        .bind(max_players)
         .execute(&mut *tx)
-        .await?;
+        .await
+        .map_err(|e| match e {
+            // `uq_registration_user`, the same user registering twice at once
+            sqlx::Error::Database(e) if e.is_unique_violation() && user_id.is_some() => {
+                AppError::AlreadyRegistered
+            }
+            e => e.into(),
+        })?;
+    if result.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Err(AppError::TournamentFull(max_players));
+    }
     let registration_id = result.last_insert_rowid();
+    // A user registering themselves claims the player, it cannot be taken by another user
+    if let Some(user_id) = user_id {
+        let claimed = sqlx::query(
+            "update players set user_id = ?1 where id = ?2 and (user_id is null or user_id = ?1)",
+        )
+        .bind(user_id)
+        .bind(payload.player_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            // `uq_player_user`, the user already claimed another player
+            sqlx::Error::Database(e) if e.is_unique_violation() => {
+                AppError::NotOwnPlayer(payload.player_id as u32)
+            }
+            e => e.into(),
+        })?
+        .rows_affected();
+        if claimed == 0 {
+            tx.rollback().await?;
+            return Err(AppError::NotOwnPlayer(payload.player_id as u32));
+        }
+    }
     let current_pairings: Vec<DbPairing> =
         sqlx::query_as("select * from pairings where tournament_id = ?1")
             .bind(tournament_id)
             .fetch_all(&mut *tx)
             .await?;
     if !current_pairings.is_empty() {
+        let default_score: u32 =
+            sqlx::query_scalar("select late_entry_default_score from tournaments where id = ?1")
+                .bind(tournament_id)
+                .fetch_one(&mut *tx)
+                .await?;
         let last_round = current_pairings
             .iter()
             .map(|pair| pair.round_number)
@@ -39,7 +89,7 @@ pub async fn create_tournament_registration(
                     PlayerResult::Draw => 1,
                     PlayerResult::Lose => 0,
                 },
-                None => 0,
+                None => default_score,
             };
             sqlx::query("insert into pairing_gaps (player_id, tournament_id, is_bye, round_id, score) values (?1, ?2, ?3, ?4, ?5)")
                 .bind(registration_id)
@@ -51,27 +101,84 @@ pub async fn create_tournament_registration(
                 .await?;
         }
     }
+    for round_number in payload.requested_bye_rounds {
+        sqlx::query(
+            "insert or ignore into bye_requests (registration_id, round_number) values (?1, ?2)",
+        )
+        .bind(registration_id)
+        .bind(round_number)
+        .execute(&mut *tx)
+        .await?;
+    }
     tx.commit().await?;
     Ok(registration_id)
 }
 
 pub async fn update_registration_status(
     pool: &sqlx::SqlitePool,
+    user_id: u32,
+    tournament_id: u32,
     registration_id: u32,
     status: PlayerStatus,
 ) -> sqlx::Result<()> {
-    sqlx::query("update registrations set status = ?1 where id = ?2")
+    let mut tx = pool.begin().await?;
+    sqlx::query("update registrations set status = ?1 where id = ?2 and tournament_id = ?3")
         .bind(status.to_string())
         .bind(registration_id)
-        .execute(pool)
+        .bind(tournament_id)
+        .execute(&mut *tx)
         .await?;
+    audit_repo::record(
+        &mut tx,
+        user_id,
+        tournament_id,
+        AuditAction::RegistrationStatusUpdated,
+        format!("registration {}: {}", registration_id, status),
+    )
+    .await?;
+    mark_tournament_updated(tournament_id, &mut tx).await?;
+    tx.commit().await?;
     Ok(())
 }
 
+// Returns the number of deleted registrations, 0 if it did not belong to the tournament
+pub async fn delete_registration(
+    pool: &sqlx::SqlitePool,
+    user_id: u32,
+    tournament_id: u32,
+    registration_id: u32,
+) -> sqlx::Result<u64> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("delete from pairing_gaps where player_id = ?1 and tournament_id = ?2")
+        .bind(registration_id)
+        .bind(tournament_id)
+        .execute(&mut *tx)
+        .await?;
+    let result = sqlx::query("delete from registrations where id = ?1 and tournament_id = ?2")
+        .bind(registration_id)
+        .bind(tournament_id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(0);
+    }
+    audit_repo::record(
+        &mut tx,
+        user_id,
+        tournament_id,
+        AuditAction::RegistrationDeleted,
+        format!("registration {}", registration_id),
+    )
+    .await?;
+    mark_tournament_updated(tournament_id, &mut tx).await?;
+    tx.commit().await?;
+    Ok(result.rows_affected())
+}
+
 #[derive(FromRow)]
 pub struct DbRegistration {
     pub id: u32,
-    pub floats: u32,
     pub status: String,
     pub player_id: u32,
     pub rating: u32,
@@ -80,6 +187,7 @@ pub struct DbRegistration {
     pub federation: Option<String>,
     pub fide_id: Option<u32>,
     pub title: String,
+    pub section_id: Option<u32>,
 }
 
 pub async fn select_registrations(
@@ -89,7 +197,6 @@ pub async fn select_registrations(
     let registrations: Vec<DbRegistration> = sqlx::query_as(
         "select
             r.id,
-            r.floats,
             r.status,
             r.player_id,
             r.rating,
@@ -97,7 +204,8 @@ pub async fn select_registrations(
             p.last_name,
             p.federation,
             p.fide_id,
-            p.title
+            p.title,
+            r.section_id
         from registrations r
         inner join players p on r.player_id = p.id
         where r.tournament_id = ?",
@@ -108,9 +216,48 @@ pub async fn select_registrations(
     Ok(registrations)
 }
 
+pub async fn is_user_registered(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    user_id: u32,
+) -> sqlx::Result<bool> {
+    sqlx::query_scalar(
+        "select exists(select 1 from registrations where tournament_id = ?1 and user_id = ?2)",
+    )
+    .bind(tournament_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+#[derive(FromRow)]
+pub struct DbByeRequest {
+    pub registration_id: u32,
+    pub round_number: u32,
+}
+
+pub async fn select_bye_requests(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+) -> sqlx::Result<Vec<DbByeRequest>> {
+    sqlx::query_as(
+        "select b.registration_id, b.round_number
+        from bye_requests b
+        inner join registrations r on b.registration_id = r.id
+        where r.tournament_id = ?",
+    )
+    .bind(tournament_id)
+    .fetch_all(pool)
+    .await
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::models::tournament::PlayerStatus;
+    use crate::{
+        models::tournament::{MAX_PLAYERS, PlayerStatus},
+        payloads::NewTournament,
+        repositories::{pairing_repo::select_pairing_gaps, tournament_repo},
+    };
 
     use super::*;
 
@@ -124,8 +271,10 @@ mod tests {
             status: PlayerStatus::Active.to_string(),
             rating: 2000,
             absent_results: Vec::new(),
+            section_id: None,
+            requested_bye_rounds: Vec::new(),
         };
-        create_tournament_registration(&pool, 1, payload)
+        create_tournament_registration(&pool, 1, payload, None, MAX_PLAYERS)
             .await
             .expect("failed to register player 1");
         let payload = NewRegistration {
@@ -133,9 +282,106 @@ mod tests {
             status: PlayerStatus::Active.to_string(),
             rating: 2000,
             absent_results: Vec::new(),
+            section_id: None,
+            requested_bye_rounds: Vec::new(),
         };
-        create_tournament_registration(&pool, 1, payload)
+        create_tournament_registration(&pool, 1, payload, None, MAX_PLAYERS)
             .await
             .expect("failed to register player 2");
+        let payload = NewRegistration {
+            player_id: 3,
+            status: PlayerStatus::Active.to_string(),
+            rating: 2000,
+            absent_results: Vec::new(),
+            section_id: None,
+            requested_bye_rounds: Vec::new(),
+        };
+        assert!(matches!(
+            create_tournament_registration(&pool, 1, payload, None, 2).await,
+            Err(AppError::TournamentFull(2))
+        ));
+        let registered: u32 =
+            sqlx::query_scalar("select count(*) from registrations where tournament_id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(registered, 2);
+    }
+
+    #[sqlx::test(fixtures(path = "../../fixtures", scripts("create_players", "create_user")))]
+    async fn test_late_registration_default_score(pool: sqlx::SqlitePool) {
+        let new_tournament = NewTournament {
+            name: "Late entries".to_string(),
+            rounds: 5,
+            time_category: "rapid".to_string(),
+            start_date: 0,
+            federation: "FID".to_string(),
+            url: None,
+            max_float_gap: None,
+            late_entry_default_score: Some(1),
+            unrated_default_rating: None,
+            board_offset: None,
+            allow_repeat_pairings: None,
+            visibility: None,
+            requested_bye_score: None,
+            games_per_pairing: None,
+            tiebreaks: None,
+            max_players: None,
+            broadcast_boards: None,
+            self_registration: None,
+            allow_three_same_colors: None,
+            time_control: None,
+            avoid_recent_opponents: None,
+            allow_rematch_reversed_colors: None,
+            avoid_last_round_contender_bye: None,
+        };
+        let tournament_id = tournament_repo::create_tournament(&pool, 1, new_tournament)
+            .await
+            .expect("failed to create tournament") as u32;
+        for player_id in 1..=4 {
+            let payload = NewRegistration {
+                player_id,
+                status: PlayerStatus::Active.to_string(),
+                rating: 2000,
+                absent_results: Vec::new(),
+                section_id: None,
+                requested_bye_rounds: Vec::new(),
+            };
+            create_tournament_registration(&pool, tournament_id, payload, None, MAX_PLAYERS)
+                .await
+                .expect("failed to register player");
+        }
+        // Rounds 1 and 2 have been played
+        for (round, white, black) in [(0, 1, 2), (0, 3, 4), (1, 3, 1), (1, 2, 4)] {
+            sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id, result) values (?1, ?2, 0, ?3, ?4, '1-0')")
+                .bind(tournament_id)
+                .bind(round)
+                .bind(white)
+                .bind(black)
+                .execute(&pool)
+                .await
+                .expect("failed to insert pairing");
+        }
+        let payload = NewRegistration {
+            player_id: 5,
+            status: PlayerStatus::Active.to_string(),
+            rating: 2000,
+            absent_results: Vec::new(),
+            section_id: None,
+            requested_bye_rounds: Vec::new(),
+        };
+        let registration_id =
+            create_tournament_registration(&pool, tournament_id, payload, None, MAX_PLAYERS)
+                .await
+                .expect("failed to register late player");
+        let gaps = select_pairing_gaps(&pool, tournament_id)
+            .await
+            .expect("failed to select gaps");
+        assert_eq!(gaps.len(), 2);
+        for gap in gaps {
+            assert_eq!(gap.player_id as i64, registration_id);
+            assert_eq!(gap.score, 1);
+            assert!(!gap.is_bye);
+        }
     }
 }
@@ -0,0 +1,32 @@
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct DbSection {
+    pub id: u32,
+    pub name: String,
+}
+
+pub async fn create_section(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    name: &str,
+) -> sqlx::Result<i64> {
+    let result = sqlx::query("insert into sections (tournament_id, name) values (?1, ?2)")
+        .bind(tournament_id)
+        .bind(name)
+        .execute(pool)
+        .await?;
+    Ok(result.last_insert_rowid())
+}
+
+pub async fn select_sections(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+) -> sqlx::Result<Vec<DbSection>> {
+    sqlx::query_as("select id, name from sections where tournament_id = ?1 order by id")
+        .bind(tournament_id)
+        .fetch_all(pool)
+        .await
+}
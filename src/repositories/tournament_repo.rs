@@ -1,17 +1,30 @@
+use std::collections::HashMap;
+
 use chrono::Utc;
+use itertools::Itertools;
 use sqlx::{Sqlite, Transaction, prelude::FromRow};
 
 use crate::{
-    auth::jwt::Claims, errors::AppError, models::tournament::NewPairings, payloads::NewTournament,
+    auth::jwt::Claims,
+    errors::AppError,
+    models::{
+        import::ImportedTournament,
+        tournament::{GameResult, NewPairings, PlayerStatus, Tiebreak, TournamentRole, Visibility},
+    },
+    payloads::{NewPlayer, NewTournament},
+    repositories::{
+        audit_repo::{self, AuditAction},
+        player_repo,
+    },
 };
 
 pub async fn create_tournament(
-    pool: &sqlx::SqlitePool,
+    pool: impl sqlx::SqliteExecutor<'_>,
     user_id: u32,
     payload: NewTournament,
 ) -> sqlx::Result<i64> {
     let result =
-        sqlx::query("insert into tournaments (created_by, name, num_rounds, time_category, start_date, federation, url, current_round) values (?, ?, ?, ?, ?, ?, ?, 0)")
+        sqlx::query("insert into tournaments (created_by, name, num_rounds, time_category, start_date, federation, url, max_float_gap, late_entry_default_score, unrated_default_rating, board_offset, allow_repeat_pairings, visibility, requested_bye_score, games_per_pairing, tiebreaks, max_players, broadcast_boards, self_registration, allow_three_same_colors, time_control, avoid_recent_opponents, allow_rematch_reversed_colors, avoid_last_round_contender_bye, current_round) values (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 0)")
             .bind(user_id)
             .bind(&payload.name)
             .bind(&payload.rounds)
@@ -19,48 +32,254 @@ pub async fn create_tournament(
             .bind(&payload.start_date)
             .bind(&payload.federation)
             .bind(&payload.url)
+            .bind(payload.max_float_gap)
+            .bind(payload.late_entry_default_score.unwrap_or(0))
+            .bind(payload.unrated_default_rating)
+            .bind(payload.board_offset.unwrap_or(0))
+            .bind(payload.allow_repeat_pairings.unwrap_or(false))
+            .bind(payload.visibility.unwrap_or_else(|| Visibility::Public.to_string()))
+            .bind(payload.requested_bye_score.unwrap_or(1))
+            .bind(payload.games_per_pairing.unwrap_or(1))
+            .bind(payload.tiebreaks.map(|t| t.join(",")).unwrap_or_else(|| Tiebreak::DEFAULT.iter().join(",")))
+            .bind(payload.max_players)
+            .bind(payload.broadcast_boards.unwrap_or(0))
+            .bind(payload.self_registration.unwrap_or(false))
+            .bind(payload.allow_three_same_colors.unwrap_or(false))
+            .bind(&payload.time_control)
+            .bind(payload.avoid_recent_opponents.unwrap_or(false))
+            .bind(payload.allow_rematch_reversed_colors.unwrap_or(false))
+            .bind(payload.avoid_last_round_contender_bye.unwrap_or(false))
             .execute(pool)
             .await?;
     Ok(result.last_insert_rowid())
 }
 
+// Copies the settings of a tournament into a new one owned by `user_id`, without its rounds or
+// organizers. With `registrations` the active players are registered again with the same
+// seeds. Self-registrations are copied as organizer registrations, the users did not sign up
+// for the new tournament
+pub async fn clone_tournament(
+    pool: &sqlx::SqlitePool,
+    user_id: u32,
+    tournament_id: u32,
+    registrations: bool,
+) -> sqlx::Result<i64> {
+    let mut tx = pool.begin().await?;
+    let result = sqlx::query("insert into tournaments (created_by, name, num_rounds, time_category, start_date, federation, url, max_float_gap, late_entry_default_score, unrated_default_rating, board_offset, allow_repeat_pairings, visibility, requested_bye_score, games_per_pairing, tiebreaks, max_players, broadcast_boards, self_registration, allow_three_same_colors, time_control, avoid_recent_opponents, allow_rematch_reversed_colors, avoid_last_round_contender_bye, current_round)
+        select ?1, name, num_rounds, time_category, start_date, federation, url, max_float_gap, late_entry_default_score, unrated_default_rating, board_offset, allow_repeat_pairings, visibility, requested_bye_score, games_per_pairing, tiebreaks, max_players, broadcast_boards, self_registration, allow_three_same_colors, time_control, avoid_recent_opponents, allow_rematch_reversed_colors, avoid_last_round_contender_bye, 0
+        from tournaments where id = ?2")
+        .bind(user_id)
+        .bind(tournament_id)
+        .execute(&mut *tx)
+        .await?;
+    let id = result.last_insert_rowid();
+    let mut detail = format!("from tournament {}", tournament_id);
+    if registrations {
+        let result = sqlx::query(
+            "insert into registrations (player_id, tournament_id, status, rating)
+            select player_id, ?1, status, rating from registrations where tournament_id = ?2 and status = ?3 order by id",
+        )
+        .bind(id)
+        .bind(tournament_id)
+        .bind(PlayerStatus::Active.to_string())
+        .execute(&mut *tx)
+        .await?;
+        detail.push_str(&format!(", {} players", result.rows_affected()));
+    }
+    audit_repo::record(
+        &mut tx,
+        user_id,
+        id as u32,
+        AuditAction::TournamentCloned,
+        detail,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(id)
+}
+
+// Creates an imported tournament together with its players, registrations and past rounds
+pub async fn import_tournament(
+    pool: &sqlx::SqlitePool,
+    user_id: u32,
+    imported: ImportedTournament,
+) -> sqlx::Result<i64> {
+    let mut tx = pool.begin().await?;
+    let tournament_id = create_tournament(&mut *tx, user_id, imported.tournament).await?;
+    // Players already known by their FIDE id are reused
+    let mut registrations = HashMap::new();
+    for player in imported.players {
+        let existing = match player.fide_id {
+            Some(fide_id) => player_repo::get_player_by_fide_id(&mut *tx, fide_id).await?,
+            None => None,
+        };
+        let player_id = match existing {
+            Some(existing) => existing.id,
+            None => {
+                let new_player = NewPlayer {
+                    first_name: player.first_name,
+                    last_name: player.last_name,
+                    federation: player.federation,
+                    fide_id: player.fide_id,
+                    title: player.title,
+                    rating: player.rating,
+                    rating_rapid: None,
+                    rating_blitz: None,
+                };
+                player_repo::create_player(&mut *tx, new_player).await?
+            }
+        };
+        let registration = sqlx::query("insert into registrations (player_id, tournament_id, status, rating) values (?1, ?2, ?3, ?4)")
+            .bind(player_id)
+            .bind(tournament_id)
+            .bind(PlayerStatus::Active.to_string())
+            .bind(player.rating.unwrap_or(0))
+            .execute(&mut *tx)
+            .await?;
+        registrations.insert(player.start_rank, registration.last_insert_rowid());
+    }
+    for game in imported.games {
+        let result = match game.result {
+            GameResult::Ongoing => None,
+            result => Some(result.to_string()),
+        };
+        sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id, result) values (?1, ?2, ?3, ?4, ?5, ?6)")
+            .bind(tournament_id)
+            .bind(game.round_number)
+            .bind(game.board_number)
+            .bind(registrations[&game.white])
+            .bind(registrations[&game.black])
+            .bind(result)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for gap in imported.gaps {
+        sqlx::query("insert into pairing_gaps (tournament_id, player_id, round_id, score, is_bye) values (?1, ?2, ?3, ?4, ?5)")
+            .bind(tournament_id)
+            .bind(registrations[&gap.player])
+            .bind(gap.round_number)
+            .bind(gap.score)
+            .bind(gap.is_bye)
+            .execute(&mut *tx)
+            .await?;
+    }
+    sqlx::query("update tournaments set current_round = ?1 where id = ?2")
+        .bind(imported.current_round)
+        .bind(tournament_id)
+        .execute(&mut *tx)
+        .await?;
+    audit_repo::record(
+        &mut tx,
+        user_id,
+        tournament_id as u32,
+        AuditAction::TournamentImported,
+        format!(
+            "{} players, {} rounds",
+            registrations.len(),
+            imported.current_round
+        ),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(tournament_id)
+}
+
 #[derive(Debug, FromRow)]
 struct TournamentOwnerAndEndDate {
     created_by: u32,
     end_date: Option<u32>,
+    // None when the user does not co-organize the tournament
+    organizer_role: Option<String>,
+}
+
+async fn get_tournament_owner_and_end_date(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    user_id: u32,
+) -> Result<TournamentOwnerAndEndDate, AppError> {
+    let tourn: Option<TournamentOwnerAndEndDate> = match sqlx::query_as(
+        "select
+            t.created_by,
+            t.end_date,
+            (
+                select o.role from tournament_organizers o where o.tournament_id = t.id and o.user_id = ?1
+            ) as organizer_role
+        from tournaments t
+        where t.id = ?2",
+    )
+    .bind(user_id)
+    .bind(tournament_id)
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(u) => u,
+        Err(e) => {
+            tracing::error!("get_tournament_owner_and_end_date: {:?}", e);
+            return Err(AppError::Unknown);
+        }
+    };
+    tourn.ok_or(AppError::TournamentNotFound)
 }
 
 // Cannot edit tournaments that have already ended
-// Users can only edit tournaments they created
+// Users can only edit tournaments they created or co-organize, within their role
 // Admin can edit any tournament that has not ended
+// Returns None when the user cannot edit the tournament at all
 pub async fn check_user_tournament_permissions(
     pool: &sqlx::SqlitePool,
     tournament_id: u32,
     claims: Claims,
+) -> Result<Option<TournamentRole>, AppError> {
+    let t = get_tournament_owner_and_end_date(pool, tournament_id, claims.sub).await?;
+    if t.end_date.is_some() {
+        return Ok(None);
+    }
+    if t.created_by == claims.sub || claims.role == "admin" {
+        return Ok(Some(TournamentRole::Owner));
+    }
+    t.organizer_role
+        .map(|role| TournamentRole::try_from(role.as_str()))
+        .transpose()
+}
+
+// Organizers keep read access to organizer-only data after the tournament has ended
+pub async fn check_user_is_organizer(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    claims: &Claims,
 ) -> Result<bool, AppError> {
-    let tourn: Option<TournamentOwnerAndEndDate> =
-        match sqlx::query_as("select created_by, end_date from tournaments where id = ?")
-            .bind(tournament_id)
-            .fetch_optional(pool)
-            .await
-        {
-            Ok(u) => u,
-            Err(e) => {
-                tracing::error!("check_user_tournament_permissions: {:?}", e);
-                return Err(AppError::Unknown);
-            }
-        };
-    if let Some(t) = tourn {
-        if t.end_date.is_some() {
-            return Ok(false);
-        }
-        if t.created_by == claims.sub || claims.role == "admin" {
-            return Ok(true);
-        }
+    let t = get_tournament_owner_and_end_date(pool, tournament_id, claims.sub).await?;
+    Ok(t.created_by == claims.sub || t.organizer_role.is_some() || claims.role == "admin")
+}
+
+// The Owner role of `check_user_tournament_permissions`, also once the tournament has ended
+pub async fn check_user_is_owner(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    claims: &Claims,
+) -> Result<bool, AppError> {
+    let t = get_tournament_owner_and_end_date(pool, tournament_id, claims.sub).await?;
+    if t.created_by == claims.sub || claims.role == "admin" {
+        return Ok(true);
+    }
+    let role = t
+        .organizer_role
+        .map(|role| TournamentRole::try_from(role.as_str()))
+        .transpose()?;
+    Ok(role == Some(TournamentRole::Owner))
+}
+
+// Only the creator (or an admin) can manage the co-organizers of a tournament
+pub async fn check_user_tournament_ownership(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    claims: &Claims,
+) -> Result<bool, AppError> {
+    let t = get_tournament_owner_and_end_date(pool, tournament_id, claims.sub).await?;
+    if t.end_date.is_some() {
         return Ok(false);
-    } else {
-        return Err(AppError::TournamentNotFound);
     }
+    Ok(t.created_by == claims.sub || claims.role == "admin")
 }
 
 pub async fn mark_tournament_updated(
@@ -90,13 +309,43 @@ pub struct DbTournament {
     pub updated_at: u32,
     pub end_date: Option<u32>,
     pub url: Option<String>,
+    pub max_float_gap: Option<u32>,
+    pub registration_open: bool,
+    pub late_entry_default_score: u32,
+    pub unrated_default_rating: Option<u32>,
+    pub board_offset: u32,
+    pub allow_repeat_pairings: bool,
+    pub visibility: String,
+    pub requested_bye_score: u32,
+    pub games_per_pairing: u32,
+    pub tiebreaks: String,
+    pub max_players: Option<u32>,
+    pub broadcast_boards: u32,
+    pub self_registration: bool,
+    pub allow_three_same_colors: bool,
+    pub time_control: Option<String>,
+    pub avoid_recent_opponents: bool,
+    pub allow_rematch_reversed_colors: bool,
+    pub avoid_last_round_contender_bye: bool,
 }
 
-pub async fn list_tournaments(pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<DbTournament>> {
+// A listed tournament with the progress of its current round
+#[derive(FromRow)]
+pub struct DbTournamentSummary {
+    #[sqlx(flatten)]
+    pub tournament: DbTournament,
+    pub completed_boards: u32,
+    pub total_boards: u32,
+}
+
+pub async fn list_tournaments(pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<DbTournamentSummary>> {
     sqlx::query_as("select
-            t.id, t.name, t.current_round, t.num_rounds, t.time_category, t.start_date, t.federation, t.end_date, t.url, t.updated_at, u.id as user_id, u.username as username
+            t.id, t.name, t.current_round, t.num_rounds, t.time_category, t.start_date, t.federation, t.end_date, t.url, t.updated_at, t.max_float_gap, t.registration_open, t.late_entry_default_score, t.unrated_default_rating, t.board_offset, t.allow_repeat_pairings, t.visibility, t.requested_bye_score, t.games_per_pairing, t.tiebreaks, t.max_players, t.broadcast_boards, t.self_registration, t.allow_three_same_colors, t.time_control, t.avoid_recent_opponents, t.allow_rematch_reversed_colors, t.avoid_last_round_contender_bye, u.id as user_id, u.username as username,
+            (select count(*) from pairings p where p.tournament_id = t.id and p.round_number = t.current_round - 1 and p.result is not null and p.result != '*') as completed_boards,
+            (select count(*) from pairings p where p.tournament_id = t.id and p.round_number = t.current_round - 1) as total_boards
             from tournaments t
             inner join users u on t.created_by = u.id
+            where t.visibility = 'public'
             order by t.updated_at desc"
         )
         .fetch_all(pool)
@@ -105,7 +354,7 @@ pub async fn list_tournaments(pool: &sqlx::SqlitePool) -> sqlx::Result<Vec<DbTou
 
 pub async fn get_tournament(pool: &sqlx::SqlitePool, id: u32) -> sqlx::Result<DbTournament> {
     sqlx::query_as("select
-        t.id, t.name, t.current_round, t.num_rounds, t.time_category, t.start_date, t.federation, t.end_date, t.url, t.updated_at, t.url, u.id as user_id, u.username as username
+        t.id, t.name, t.current_round, t.num_rounds, t.time_category, t.start_date, t.federation, t.end_date, t.url, t.updated_at, t.url, t.max_float_gap, t.registration_open, t.late_entry_default_score, t.unrated_default_rating, t.board_offset, t.allow_repeat_pairings, t.visibility, t.requested_bye_score, t.games_per_pairing, t.tiebreaks, t.max_players, t.broadcast_boards, t.self_registration, t.allow_three_same_colors, t.time_control, t.avoid_recent_opponents, t.allow_rematch_reversed_colors, t.avoid_last_round_contender_bye, u.id as user_id, u.username as username
         from tournaments t
         inner join users u on u.id = t.created_by
         where t.id = ?1")
@@ -115,8 +364,87 @@ pub async fn get_tournament(pool: &sqlx::SqlitePool, id: u32) -> sqlx::Result<Db
 }
 
 impl NewPairings {
-    pub async fn commit(&self, pool: &sqlx::Pool<sqlx::Sqlite>) -> sqlx::Result<()> {
+    // Boards must be numbered 0..pairings.len() with no duplicates or gaps
+    fn validate_board_numbers(&self) -> Result<(), AppError> {
+        let mut seen = vec![false; self.pairings.len()];
+        for pairing in self.pairings.iter() {
+            match seen.get_mut(pairing.board_number as usize) {
+                Some(seen) if !*seen => *seen = true,
+                _ => return Err(AppError::InvalidBoardNumbering),
+            }
+        }
+        Ok(())
+    }
+    pub async fn commit(
+        &self,
+        pool: &sqlx::Pool<sqlx::Sqlite>,
+        tournament_id: u32,
+        user_id: u32,
+    ) -> Result<(), AppError> {
+        self.validate_board_numbers()?;
         let mut tx = pool.begin().await?;
+        self.insert(&mut tx, tournament_id, user_id).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    // Replaces the pairings of the same round, refused once a result has been entered there
+    pub async fn replace(
+        &self,
+        pool: &sqlx::Pool<sqlx::Sqlite>,
+        tournament_id: u32,
+        user_id: u32,
+    ) -> Result<(), AppError> {
+        self.validate_board_numbers()?;
+        let mut tx = pool.begin().await?;
+        // Checked again in the transaction, a result may have come in since the round was read
+        let has_results: bool = sqlx::query_scalar(
+            "select exists(select 1 from pairings where tournament_id = ?1 and round_number = ?2 and (result is not null or games is not null))",
+        )
+        .bind(tournament_id)
+        .bind(self.round)
+        .fetch_one(&mut *tx)
+        .await?;
+        if has_results {
+            tx.rollback().await?;
+            return Err(AppError::RoundHasResults(self.round));
+        }
+        let discarded =
+            sqlx::query("delete from pairings where tournament_id = ?1 and round_number = ?2")
+                .bind(tournament_id)
+                .bind(self.round)
+                .execute(&mut *tx)
+                .await?;
+        sqlx::query("delete from pairing_gaps where tournament_id = ?1 and round_id = ?2")
+            .bind(tournament_id)
+            .bind(self.round)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("update tournaments set current_round = current_round - 1 where id = ?1")
+            .bind(tournament_id)
+            .execute(&mut *tx)
+            .await?;
+        audit_repo::record(
+            &mut tx,
+            user_id,
+            tournament_id,
+            AuditAction::RoundRegenerated,
+            format!(
+                "round {}: {} boards discarded",
+                self.round,
+                discarded.rows_affected()
+            ),
+        )
+        .await?;
+        self.insert(&mut tx, tournament_id, user_id).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    async fn insert(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        tournament_id: u32,
+        user_id: u32,
+    ) -> sqlx::Result<()> {
         for pairing in self.pairings.iter() {
             sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id) values (?1, ?2, ?3, ?4, ?5)")
                 .bind(pairing.tournament_id)
@@ -124,7 +452,7 @@ impl NewPairings {
                 .bind(pairing.board_number)
                 .bind(pairing.white_id)
                 .bind(pairing.black_id)
-                .execute(&mut *tx)
+                .execute(&mut **tx)
                 .await?;
         }
         for gap in self.gaps.iter() {
@@ -134,40 +462,113 @@ impl NewPairings {
                 .bind(gap.round_id)
                 .bind(gap.score)
                 .bind(gap.is_bye)
-                .execute(&mut *tx)
-                .await?;
-        }
-        for id in self.floats.iter() {
-            sqlx::query("update registrations set floats = floats + 1 where id = ?1")
-                .bind(id)
-                .execute(&mut *tx)
+                .execute(&mut **tx)
                 .await?;
         }
         sqlx::query("update tournaments set current_round = current_round + 1 where id = ?1")
-            .bind(self.pairings[0].tournament_id)
-            .execute(&mut *tx)
+            .bind(tournament_id)
+            .execute(&mut **tx)
             .await?;
-        mark_tournament_updated(self.pairings[0].tournament_id, &mut tx).await?;
-        tx.commit().await?;
-        Ok(())
+        audit_repo::record(
+            tx,
+            user_id,
+            tournament_id,
+            AuditAction::PairingsGenerated,
+            format!(
+                "round {}: {} boards, {} unpaired",
+                self.round,
+                self.pairings.len(),
+                self.gaps.len()
+            ),
+        )
+        .await?;
+        mark_tournament_updated(tournament_id, tx).await
     }
 }
 
-pub async fn end_tournament(pool: &sqlx::SqlitePool, tournament_id: u32) -> sqlx::Result<i64> {
+pub async fn set_registration_open(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    registration_open: bool,
+) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("update tournaments set registration_open = ?1 where id = ?2")
+        .bind(registration_open)
+        .bind(tournament_id)
+        .execute(&mut *tx)
+        .await?;
+    mark_tournament_updated(tournament_id, &mut tx).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn set_visibility(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    visibility: Visibility,
+) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("update tournaments set visibility = ?1 where id = ?2")
+        .bind(visibility.to_string())
+        .bind(tournament_id)
+        .execute(&mut *tx)
+        .await?;
+    mark_tournament_updated(tournament_id, &mut tx).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn set_time_control(
+    pool: &sqlx::SqlitePool,
+    tournament_id: u32,
+    time_control: Option<&str>,
+) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("update tournaments set time_control = ?1 where id = ?2")
+        .bind(time_control)
+        .bind(tournament_id)
+        .execute(&mut *tx)
+        .await?;
+    mark_tournament_updated(tournament_id, &mut tx).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn end_tournament(
+    pool: &sqlx::SqlitePool,
+    user_id: u32,
+    tournament_id: u32,
+) -> sqlx::Result<i64> {
     let now = Utc::now().timestamp();
+    let mut tx = pool.begin().await?;
     let _ = sqlx::query("update tournaments set end_date = ?, updated_at = ? where id = ?")
         .bind(now)
         .bind(now)
         .bind(tournament_id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
+    audit_repo::record(
+        &mut tx,
+        user_id,
+        tournament_id,
+        AuditAction::TournamentEnded,
+        format!("ended at {}", now),
+    )
+    .await?;
+    tx.commit().await?;
     Ok(now)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        models::tournament::{Color, Tournament},
+        models::tournament::{Color, MAX_PLAYERS, Tournament},
+        payloads::NewRegistration,
+        repositories::{
+            auth_repo, organizer_repo,
+            pairing_repo::{self, NewDbPairing},
+            registration_repo,
+        },
         services::tournament_service,
     };
 
@@ -182,6 +583,23 @@ mod tests {
             start_date: 0,
             federation: "FID".to_string(),
             url: None,
+            max_float_gap: None,
+            late_entry_default_score: None,
+            unrated_default_rating: None,
+            board_offset: None,
+            allow_repeat_pairings: None,
+            visibility: None,
+            requested_bye_score: None,
+            games_per_pairing: None,
+            tiebreaks: None,
+            max_players: None,
+            broadcast_boards: None,
+            self_registration: None,
+            allow_three_same_colors: None,
+            time_control: None,
+            avoid_recent_opponents: None,
+            allow_rematch_reversed_colors: None,
+            avoid_last_round_contender_bye: None,
         };
         let id = create_tournament(&pool, 1, new_tournament)
             .await
@@ -203,7 +621,11 @@ mod tests {
             .expect("failed to read_tournament");
         let tournament: Tournament = tournament.into();
         let new_pairings = tournament
-            .generate_first_round_pairings(tournament_service::InactiveScores::new(), Color::White)
+            .generate_first_round_pairings(
+                tournament_service::InactiveScores::new(),
+                Color::White,
+                &tournament_service::FixedPairings::default(),
+            )
             .expect("failed to generate first round pairings");
         for pair in new_pairings.pairings.iter() {
             println!(
@@ -213,4 +635,377 @@ mod tests {
         }
         assert_eq!(new_pairings.pairings.len(), 25)
     }
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_fixed_pairings(pool: sqlx::SqlitePool) {
+        let tournament: Tournament = tournament_service::read_tournament(&pool, 1)
+            .await
+            .expect("failed to read_tournament")
+            .into();
+        let fixed = tournament_service::FixedPairings {
+            pairs: vec![(50, 1)],
+            byes: vec![49],
+            colors: Vec::new(),
+        };
+        let new_pairings = tournament
+            .generate_first_round_pairings(
+                tournament_service::InactiveScores::new(),
+                Color::White,
+                &fixed,
+            )
+            .expect("failed to generate first round pairings");
+        assert_eq!(new_pairings.pairings[0].board_number, 0);
+        assert_eq!(new_pairings.pairings[0].white_id, 50);
+        assert_eq!(new_pairings.pairings[0].black_id, 1);
+        // 47 remaining players: 23 generated boards and one more bye
+        assert_eq!(new_pairings.pairings.len(), 24);
+        assert_eq!(new_pairings.gaps.iter().filter(|g| g.is_bye).count(), 2);
+        assert!(new_pairings.gaps.iter().any(|g| g.player_id == 49));
+        for pair in new_pairings.pairings.iter().skip(1) {
+            for id in [pair.white_id, pair.black_id] {
+                assert!(![1, 49, 50].contains(&id));
+            }
+        }
+        new_pairings
+            .commit(&pool, 1, 1)
+            .await
+            .expect("failed to commit pairings");
+        let tournament: Tournament = tournament_service::read_tournament(&pool, 1)
+            .await
+            .expect("failed to read_tournament")
+            .into();
+        let result = tournament.generate_next_round_pairings(
+            tournament_service::InactiveScores::new(),
+            &tournament_service::FixedPairings {
+                pairs: vec![(1, 50)],
+                byes: Vec::new(),
+                colors: Vec::new(),
+            },
+        );
+        assert!(matches!(
+            result,
+            Err(AppError::FixedPairingAlreadyPlayed {
+                white: 1,
+                black: 50
+            })
+        ));
+    }
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_commit_rejects_invalid_board_numbers(pool: sqlx::SqlitePool) {
+        let pairing = |board_number, white_id, black_id| NewDbPairing {
+            tournament_id: 1,
+            round_number: 0,
+            board_number,
+            white_id,
+            black_id,
+        };
+        let duplicated = NewPairings {
+            round: 0,
+            pairings: vec![pairing(0, 1, 2), pairing(0, 3, 4)],
+            gaps: Vec::new(),
+        };
+        let result = duplicated.commit(&pool, 1, 1).await;
+        assert!(matches!(result, Err(AppError::InvalidBoardNumbering)));
+        let gapped = NewPairings {
+            round: 0,
+            pairings: vec![pairing(0, 1, 2), pairing(2, 3, 4)],
+            gaps: Vec::new(),
+        };
+        let result = gapped.commit(&pool, 1, 1).await;
+        assert!(matches!(result, Err(AppError::InvalidBoardNumbering)));
+        let pairings = pairing_repo::select_pairings(&pool, 1)
+            .await
+            .expect("failed to select pairings");
+        assert!(pairings.is_empty());
+    }
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_replace_recomputes_floats(pool: sqlx::SqlitePool) {
+        let round = |round: u32, pairs: [(u32, u32); 2]| NewPairings {
+            round,
+            pairings: pairs
+                .iter()
+                .zip(0..)
+                .map(|((white_id, black_id), board_number)| NewDbPairing {
+                    tournament_id: 1,
+                    round_number: round,
+                    board_number,
+                    white_id: *white_id,
+                    black_id: *black_id,
+                })
+                .collect(),
+            gaps: Vec::new(),
+        };
+        let floats = |pool: sqlx::SqlitePool| async move {
+            let tournament: Tournament = tournament_service::read_tournament(&pool, 1)
+                .await
+                .expect("failed to read tournament")
+                .into();
+            (1..=4)
+                .map(|id| (id, tournament.players[&id].floats))
+                .collect::<Vec<_>>()
+        };
+        round(0, [(1, 2), (3, 4)])
+            .commit(&pool, 1, 1)
+            .await
+            .expect("failed to commit round 0");
+        sqlx::query("update pairings set result = '1-0' where round_number = 0")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let before = floats(pool.clone()).await;
+        round(1, [(1, 4), (2, 3)])
+            .commit(&pool, 1, 1)
+            .await
+            .expect("failed to commit round 1");
+        assert_eq!(
+            floats(pool.clone()).await,
+            vec![(1, 0), (2, 1), (3, 0), (4, 1)]
+        );
+        // Only the floats of the replaced round are reverted
+        round(1, [(1, 3), (2, 4)])
+            .replace(&pool, 1, 1)
+            .await
+            .expect("failed to replace round 1");
+        let after = floats(pool.clone()).await;
+        assert_eq!(after, before);
+        sqlx::query(
+            "update pairings set result = '1-0' where round_number = 1 and board_number = 0",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let result = round(1, [(1, 4), (2, 3)]).replace(&pool, 1, 1).await;
+        assert!(matches!(result, Err(AppError::RoundHasResults(1))));
+        assert_eq!(floats(pool.clone()).await, after);
+    }
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_floats_computed_from_history(pool: sqlx::SqlitePool) {
+        // Four players of the roster, every round paired and scored by hand
+        let rounds = [
+            [(1, 2, "1-0"), (3, 4, "1-0")],
+            [(1, 4, "1-0"), (2, 3, "0-1")],
+            [(4, 1, "1/2-1/2"), (3, 2, "1-0")],
+            [(1, 3, "1-0"), (2, 4, "0-1")],
+        ];
+        for (round, boards) in (0..).zip(rounds) {
+            NewPairings {
+                round,
+                pairings: (0..)
+                    .zip(boards)
+                    .map(|(board_number, (white_id, black_id, _))| NewDbPairing {
+                        tournament_id: 1,
+                        round_number: round,
+                        board_number,
+                        white_id,
+                        black_id,
+                    })
+                    .collect(),
+                gaps: Vec::new(),
+            }
+            .commit(&pool, 1, 1)
+            .await
+            .expect("failed to commit pairings");
+            for (board_number, (_, _, result)) in (0u32..).zip(boards) {
+                sqlx::query(
+                    "update pairings set result = ?1 where round_number = ?2 and board_number = ?3",
+                )
+                .bind(result)
+                .bind(round)
+                .bind(board_number)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        }
+        let tournament: Tournament = tournament_service::read_tournament(&pool, 1)
+            .await
+            .expect("failed to read tournament")
+            .into();
+        // Round 1: 4 and 2 float up. Round 2: 4 (0 against 2) and 2 (0 against 2).
+        // Round 3: 1 (2.5 against 3) and 2 (0 against 0.5)
+        let floats: Vec<(u32, usize)> = (1..=4)
+            .map(|id| (id, tournament.players[&id].floats))
+            .collect();
+        assert_eq!(floats, vec![(1, 1), (2, 3), (3, 0), (4, 2)]);
+    }
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_closed_registration(pool: sqlx::SqlitePool) {
+        let claims = Claims {
+            sub: 1,
+            username: "user".to_string(),
+            role: "standard".to_string(),
+            exp: 0,
+        };
+        tournament_service::set_registration_open(&pool, 1, claims.clone(), false)
+            .await
+            .expect("failed to close registration");
+        let payload = NewRegistration {
+            player_id: 1,
+            status: "active".to_string(),
+            rating: 2000,
+            absent_results: Vec::new(),
+            section_id: None,
+            requested_bye_rounds: Vec::new(),
+        };
+        let result = tournament_service::register_player(&pool, 1, claims.clone(), payload).await;
+        assert!(matches!(result, Err(AppError::RegistrationClosed)));
+        tournament_service::set_registration_open(&pool, 1, claims, true)
+            .await
+            .expect("failed to reopen registration");
+        assert!(get_tournament(&pool, 1).await.unwrap().registration_open);
+    }
+    #[sqlx::test(fixtures(path = "../../fixtures", scripts("create_user", "create_tournament")))]
+    async fn test_co_organizer_permissions(pool: sqlx::SqlitePool) {
+        let user_id = auth_repo::create_user(&pool, "arbiter", "hash", None, None)
+            .await
+            .expect("failed to create user") as u32;
+        let claims = Claims {
+            sub: user_id,
+            username: "arbiter".to_string(),
+            role: "standard".to_string(),
+            exp: 0,
+        };
+        let role = check_user_tournament_permissions(&pool, 1, claims.clone())
+            .await
+            .expect("failed to check permissions");
+        assert_eq!(role, None);
+        organizer_repo::add_organizer(&pool, 1, user_id, TournamentRole::Owner)
+            .await
+            .expect("failed to add organizer");
+        let role = check_user_tournament_permissions(&pool, 1, claims.clone())
+            .await
+            .expect("failed to check permissions");
+        assert_eq!(role, Some(TournamentRole::Owner));
+        let is_owner = check_user_tournament_ownership(&pool, 1, &claims)
+            .await
+            .expect("failed to check ownership");
+        assert!(!is_owner);
+        // Arbiters enter results but cannot change the tournament
+        organizer_repo::add_organizer(&pool, 1, user_id, TournamentRole::Arbiter)
+            .await
+            .expect("failed to change role");
+        let role = check_user_tournament_permissions(&pool, 1, claims.clone())
+            .await
+            .expect("failed to check permissions");
+        assert_eq!(role, Some(TournamentRole::Arbiter));
+        let result =
+            tournament_service::set_registration_open(&pool, 1, claims.clone(), false).await;
+        assert!(matches!(result, Err(AppError::InsufficientPermissions)));
+        let is_organizer = check_user_is_organizer(&pool, 1, &claims)
+            .await
+            .expect("failed to check organizer");
+        assert!(is_organizer);
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts("create_players", "create_user", "create_tournament")
+    ))]
+    async fn test_delete_tournament_cascades(pool: sqlx::SqlitePool) {
+        for player_id in 1..=2 {
+            let payload = NewRegistration {
+                player_id,
+                status: PlayerStatus::Active.to_string(),
+                rating: 2000,
+                absent_results: Vec::new(),
+                section_id: None,
+                requested_bye_rounds: vec![3],
+            };
+            registration_repo::create_tournament_registration(&pool, 1, payload, None, MAX_PLAYERS)
+                .await
+                .expect("failed to register player");
+        }
+        sqlx::query("insert into pairings (tournament_id, round_number, board_number, white_id, black_id, result) values (1, 0, 0, 1, 2, '1-0')")
+            .execute(&pool)
+            .await
+            .expect("failed to insert pairing");
+        sqlx::query("delete from tournaments where id = 1")
+            .execute(&pool)
+            .await
+            .expect("failed to delete tournament");
+        for table in ["pairings", "registrations", "bye_requests"] {
+            let count: i64 = sqlx::query_scalar(&format!("select count(*) from {}", table))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(count, 0, "{} left behind", table);
+        }
+    }
+
+    #[sqlx::test(fixtures(path = "../../fixtures", scripts("create_players", "create_user")))]
+    async fn test_import_tournament(pool: sqlx::SqlitePool) {
+        let player = |rank: u32, name: &str, fide_id: u32, rounds: &str| {
+            format!(
+                "001 {:>4}      {:<33} 2000 NOR {:>11} 1990/01/01  1.0 {:>4}  {}",
+                rank, name, fide_id, rank, rounds
+            )
+        };
+        let trf = [
+            "012 Imported Open".to_string(),
+            "042 2024/03/01".to_string(),
+            player(1, "Carlsen, Magnus", 1503014, "   2 w 1     2 b ="),
+            player(2, "Newcomer, Nora", 0, "   1 b 0     1 w ="),
+        ]
+        .join("\n");
+        let players_before: u32 = sqlx::query_scalar("select count(*) from players")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let imported = crate::services::import::from_trf16(&trf).unwrap();
+        let id = import_tournament(&pool, 1, imported)
+            .await
+            .expect("failed to import tournament") as u32;
+        // Carlsen is already known by his FIDE id
+        let players_after: u32 = sqlx::query_scalar("select count(*) from players")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(players_after, players_before + 1);
+        let tournament: Tournament = tournament_service::read_tournament(&pool, id)
+            .await
+            .expect("failed to read tournament")
+            .into();
+        assert_eq!(tournament.name, "Imported Open");
+        assert_eq!(tournament.num_rounds, 2);
+        assert_eq!(tournament.current_round(), 2);
+        assert_eq!(tournament.players.len(), 2);
+        assert_eq!(
+            tournament.results,
+            vec![vec![GameResult::WhiteWins], vec![GameResult::Draw]]
+        );
+    }
 }
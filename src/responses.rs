@@ -1,22 +1,35 @@
 use axum::{
     Json as AxumJson,
     extract::{FromRequest, Request, rejection::JsonRejection},
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::IntoResponse,
 };
+use std::collections::BTreeMap;
+
 use itertools::Itertools;
 use serde::{Serialize, de::DeserializeOwned};
 
 use crate::{
     errors::AppError,
-    models::tournament::{HistoryItem, NewPairings, PlayerStanding, Tournament},
+    models::tournament::{
+        Color, ColorDecision, GameResult, HistoryItem, NewPairings, Player, PlayerName,
+        PlayerResult, PlayerStanding, TimeCategory, Tournament, seeding_order,
+    },
     payloads::{NewPlayer, RoundResult},
-    repositories::{player_repo::DbPlayer, tournament_repo::DbTournament},
+    repositories::{
+        audit_repo::DbAuditEntry,
+        organizer_repo::DbOrganizer,
+        pairing_repo::{DbAgreedDraws, DbPlayerGame},
+        player_repo::DbPlayer,
+        playoff_repo::DbPlayoffGame,
+        tournament_repo::DbTournamentSummary,
+    },
 };
 
 #[derive(Debug, Serialize)]
 #[serde(tag = "status")]
 #[serde(rename_all = "camelCase")]
+#[allow(clippy::large_enum_variant)]
 pub enum AppResponse {
     Error { error: ErrorResponse },
     Success { payload: SuccessResponse },
@@ -39,6 +52,9 @@ where
                 JsonRejection::JsonDataError(_) => Err(AppError::JsonDataError),
                 JsonRejection::JsonSyntaxError(e) => Err(AppError::JsonSyntaxError(e.to_string())),
                 JsonRejection::MissingJsonContentType(_) => Err(AppError::MissingContentType),
+                rej if rej.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                    Err(AppError::PayloadTooLarge("request body".to_string()))
+                }
                 _ => Err(AppError::JsonUnknownError),
             },
         }
@@ -56,9 +72,37 @@ pub struct RegisteredPlayer {
     rating: u32,
     fide_id: Option<usize>,
     status: String,
+    section_id: Option<u32>,
+    // Starting rank, 1-based
+    seed: u32,
+    // Rounds (0-based) the player is not paired in, scored as requested byes
+    requested_bye_rounds: Vec<u32>,
 }
 
-#[derive(Debug, Serialize)]
+impl RegisteredPlayer {
+    pub fn new(player: &Player, seed: u32) -> Self {
+        Self {
+            id: player.id,
+            player_id: player.db_id,
+            name: player.name.to_string(),
+            title: player.title.to_string(),
+            federation: player.federation.clone(),
+            fide_id: player.fide_id,
+            rating: player.rating,
+            status: player.status.to_string(),
+            section_id: player.section_id,
+            seed,
+            requested_bye_rounds: player
+                .requested_bye_rounds
+                .iter()
+                .copied()
+                .sorted()
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FidePlayer {
     pub fide_id: i64,
@@ -71,28 +115,57 @@ pub struct FidePlayer {
     pub rating_blitz: Option<u32>,
 }
 
+impl FidePlayer {
+    // Rating that counts in the given time category, players without a rapid or blitz
+    // rating are rated with the next slower one, as FIDE does
+    pub fn rating_for(&self, category: Option<TimeCategory>) -> Option<u32> {
+        match category {
+            None | Some(TimeCategory::Standard) => self.rating,
+            Some(TimeCategory::Rapid) => self.rating_rapid.or(self.rating),
+            Some(TimeCategory::Blitz) => self.rating_blitz.or(self.rating_rapid).or(self.rating),
+        }
+    }
+
+    // With a category the primary `rating` is the one of that category, the rapid and blitz
+    // ratings are kept as they are
+    pub fn into_new_player(self, category: Option<TimeCategory>) -> NewPlayer {
+        NewPlayer {
+            rating: self.rating_for(category),
+            first_name: self.first_name,
+            last_name: self.last_name,
+            federation: self.federation,
+            fide_id: Some(self.fide_id),
+            title: self.title,
+            rating_rapid: self.rating_rapid,
+            rating_blitz: self.rating_blitz,
+        }
+    }
+}
+
 impl From<FidePlayer> for NewPlayer {
     fn from(value: FidePlayer) -> Self {
-        Self {
-            first_name: value.first_name,
-            last_name: value.last_name,
-            federation: value.federation,
-            fide_id: Some(value.fide_id),
-            title: value.title,
-            rating: value.rating,
-            rating_rapid: value.rating_rapid,
-            rating_blitz: value.rating_blitz,
-        }
+        value.into_new_player(None)
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RoundPairing {
     board_number: u32,
     white_id: u32,
     black_id: u32,
     result: Option<String>,
+    // One of the top boards of the current round shown on the livestream
+    broadcast: bool,
+}
+
+// Pairings and standings restricted to the players of one section
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionData {
+    id: u32,
+    pairings: Vec<Vec<RoundPairing>>,
+    standings: Vec<Vec<PlayerStanding>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -103,6 +176,382 @@ pub struct RoundGap {
     is_bye: bool,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerGame {
+    tournament_id: u32,
+    tournament_name: String,
+    round: u32,
+    board_number: u32,
+    color: String,
+    opponent_id: u32,
+    opponent_name: String,
+    result: Option<String>,
+}
+
+impl DbPlayerGame {
+    fn color(&self) -> Color {
+        if self.is_white {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
+    fn player_result(&self) -> Option<PlayerResult> {
+        self.result
+            .as_ref()
+            .and_then(|r| GameResult::from_str(r).for_color(self.color()))
+    }
+}
+
+impl From<DbPlayerGame> for PlayerGame {
+    fn from(value: DbPlayerGame) -> Self {
+        let color = value.color();
+        let result = value.player_result().map(|r| r.to_string());
+        Self {
+            tournament_id: value.tournament_id,
+            tournament_name: value.tournament_name,
+            round: value.round_number,
+            board_number: value.board_number,
+            color: match color {
+                Color::White => String::from("white"),
+                Color::Black => String::from("black"),
+            },
+            opponent_id: value.opponent_id,
+            opponent_name: format!(
+                "{}, {}",
+                value.opponent_last_name, value.opponent_first_name
+            ),
+            result,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerAgreedDraws {
+    registration_id: u32,
+    name: String,
+    agreed_draws: u32,
+}
+
+impl From<DbAgreedDraws> for PlayerAgreedDraws {
+    fn from(value: DbAgreedDraws) -> Self {
+        Self {
+            registration_id: value.registration_id,
+            name: PlayerName::new(&value.first_name, &value.last_name).to_string(),
+            agreed_draws: value.agreed_draws,
+        }
+    }
+}
+
+pub struct HeadToHead {
+    pub player_id: u32,
+    pub opponent_id: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub games: Vec<PlayerGame>,
+}
+
+impl HeadToHead {
+    pub fn new(player_id: u32, opponent_id: u32, games: Vec<DbPlayerGame>) -> Self {
+        let (mut wins, mut draws, mut losses) = (0, 0, 0);
+        for game in games.iter() {
+            match game.player_result() {
+                Some(PlayerResult::Win) => wins += 1,
+                Some(PlayerResult::Draw) => draws += 1,
+                Some(PlayerResult::Lose) => losses += 1,
+                None => {}
+            }
+        }
+        Self {
+            player_id,
+            opponent_id,
+            wins,
+            draws,
+            losses,
+            games: games.into_iter().map(PlayerGame::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentBoard {
+    board_number: u32,
+    white_id: u32,
+    white_name: String,
+    black_id: u32,
+    black_name: String,
+    result: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundBye {
+    player_id: u32,
+    name: String,
+}
+
+// Only sent to the organizers and admins viewing a tournament
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizerData {
+    audit_entries: usize,
+    recent_audit: Vec<DbAuditEntry>,
+}
+
+impl OrganizerData {
+    const RECENT_AUDIT_ENTRIES: usize = 5;
+
+    // Entries are expected newest first, as returned by the audit log
+    pub fn new(entries: Vec<DbAuditEntry>) -> Self {
+        Self {
+            audit_entries: entries.len(),
+            recent_audit: entries
+                .into_iter()
+                .take(Self::RECENT_AUDIT_ENTRIES)
+                .collect(),
+        }
+    }
+}
+
+impl CurrentBoard {
+    fn new(
+        tournament: &Tournament,
+        board: usize,
+        (white_id, black_id): (usize, usize),
+        result: &GameResult,
+    ) -> Self {
+        let name = |id: usize| tournament.players[&(id as u32)].name.to_string();
+        Self {
+            board_number: board as u32 + tournament.board_offset,
+            white_id: white_id as u32,
+            white_name: name(white_id),
+            black_id: black_id as u32,
+            black_name: name(black_id),
+            result: result.to_string(),
+        }
+    }
+}
+
+// Boards of a round still waiting for a result, for arbiters collecting the missing ones
+pub struct PendingBoards {
+    pub round: u32,
+    pub boards: Vec<CurrentBoard>,
+}
+
+impl PendingBoards {
+    pub fn new(tournament: &Tournament, round: usize) -> Result<Self, AppError> {
+        let (Some(pairings), Some(results)) = (
+            tournament.pairings.get(round),
+            tournament.results.get(round),
+        ) else {
+            return Err(AppError::RoundNotFound(round));
+        };
+        let boards = pairings
+            .iter()
+            .zip(results.iter())
+            .enumerate()
+            .filter(|(_, (_, result))| **result == GameResult::Ongoing)
+            .map(|(board, (pair, result))| CurrentBoard::new(tournament, board, *pair, result))
+            .collect();
+        Ok(Self {
+            round: round as u32,
+            boards,
+        })
+    }
+}
+
+// Summary of the games played so far and of the field, percentages have one decimal
+pub struct TournamentStats {
+    pub games: u32,
+    pub decisive_percentage: f64,
+    pub white_wins: u32,
+    pub black_wins: u32,
+    pub draws: u32,
+    pub white_win_percentage: f64,
+    pub black_win_percentage: f64,
+    pub byes: u32,
+    pub forfeits: u32,
+    pub average_rating: u32,
+    pub rating_spread: u32,
+}
+
+impl TournamentStats {
+    pub fn new(tournament: &Tournament) -> Self {
+        let percentage = |part: u32, total: u32| {
+            if total == 0 {
+                0.0
+            } else {
+                (1000.0 * part as f64 / total as f64).round() / 10.0
+            }
+        };
+        let count = |result: GameResult| {
+            tournament
+                .results
+                .iter()
+                .flatten()
+                .filter(|r| **r == result)
+                .count() as u32
+        };
+        let (white_wins, black_wins, draws) = (
+            count(GameResult::WhiteWins),
+            count(GameResult::BlackWins),
+            count(GameResult::Draw),
+        );
+        let games = white_wins + black_wins + draws;
+        // Unrated players are left out of the rating figures
+        let ratings = tournament
+            .players
+            .values()
+            .map(|p| p.rating)
+            .filter(|rating| *rating > 0);
+        let (min, max) = ratings.clone().minmax().into_option().unwrap_or_default();
+        let rated = ratings.clone().count() as u32;
+        Self {
+            games,
+            decisive_percentage: percentage(white_wins + black_wins, games),
+            white_wins,
+            black_wins,
+            draws,
+            white_win_percentage: percentage(white_wins, games),
+            black_win_percentage: percentage(black_wins, games),
+            byes: tournament.byes.iter().map(|round| round.len() as u32).sum(),
+            // Double forfeits are the only forfeits told apart from played games
+            forfeits: count(GameResult::DoubleForfeit),
+            average_rating: ratings.sum::<u32>().checked_div(rated).unwrap_or_default(),
+            rating_spread: max - min,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerConnectivity {
+    pub registration_id: u32,
+    // Candidates of the round already met
+    pub played: u32,
+    // Candidates the player may be paired with under the strict rules
+    pub available: u32,
+}
+
+// Structural analysis of the upcoming round, nothing is paired or stored
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingConstraints {
+    // 0-based
+    pub round: u32,
+    // Players to be matched, without the byes
+    pub players: u32,
+    pub byes: Vec<u32>,
+    // Fewest available opponents first
+    pub connectivity: Vec<PlayerConnectivity>,
+    // Number of players by color balance (whites minus blacks)
+    pub color_balance: BTreeMap<i32, u32>,
+    // Without repeat pairings, third colors in a row or floats beyond the float cap
+    pub perfect_matching: bool,
+    // With the fallbacks the tournament allows (allowRepeatPairings, allowThreeSameColors)
+    pub perfect_matching_with_fallbacks: bool,
+}
+
+// Terms of the weight of a pairing graph edge, see `edge_weight`. Bonuses are added to the
+// base, penalties subtracted
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeWeight {
+    pub base: isize,
+    pub score_penalty: isize,
+    pub combined_score_bonus: isize,
+    pub color_penalty: isize,
+    pub half_pair_deviation_penalty: isize,
+    pub repeated_float_penalty: isize,
+    pub isolation_bonus: isize,
+    pub float_rank_penalty: isize,
+    pub recent_opponent_penalty: isize,
+    // Only set on the fallback edges, a rematch or a third color in a row
+    pub repeat_pairing_penalty: isize,
+    pub color_streak_penalty: isize,
+}
+
+impl EdgeWeight {
+    pub fn total(&self) -> isize {
+        (self.base + self.combined_score_bonus + self.isolation_bonus
+            - self.score_penalty
+            - self.color_penalty
+            - self.half_pair_deviation_penalty
+            - self.repeated_float_penalty
+            - self.float_rank_penalty
+            - self.recent_opponent_penalty)
+            .saturating_sub(self.repeat_pairing_penalty)
+            .saturating_sub(self.color_streak_penalty)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingGraphEdge {
+    // Registration ids, lower id first
+    pub players: (u32, u32),
+    pub weight: isize,
+    pub breakdown: EdgeWeight,
+}
+
+// Matching graph of the next round before the matching, for tuning the edge weights
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingGraph {
+    // 0-based
+    pub round: u32,
+    pub byes: Vec<u32>,
+    pub candidates: Vec<u32>,
+    // Every edge the tournament's fallbacks allow, in the order given to the matching
+    pub edges: Vec<PairingGraphEdge>,
+}
+
+// Boards and byes of the latest round only, for scoreboards polling the live results
+pub struct CurrentRound {
+    pub round: u32,
+    pub boards: Vec<CurrentBoard>,
+    pub byes: Vec<RoundBye>,
+}
+
+impl CurrentRound {
+    pub fn new(tournament: &Tournament) -> Result<Self, AppError> {
+        let (Some(pairings), Some(results)) =
+            (tournament.pairings.last(), tournament.results.last())
+        else {
+            return Err(AppError::TournamentNotStarted);
+        };
+        let name = |id: u32| tournament.players[&id].name.to_string();
+        let boards = pairings
+            .iter()
+            .zip(results.iter())
+            .enumerate()
+            .map(|(board, (pair, result))| CurrentBoard::new(tournament, board, *pair, result))
+            .collect();
+        let byes = tournament
+            .byes
+            .last()
+            .map(|byes| {
+                byes.iter()
+                    .map(|id| RoundBye {
+                        player_id: *id,
+                        name: name(*id),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(Self {
+            round: tournament.current_round() as u32 - 1,
+            boards,
+            byes,
+        })
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TournamentItem {
@@ -115,8 +564,31 @@ pub struct TournamentItem {
     user_id: u32,
     username: String,
     updated_at: u32,
+    start_date: u32,
     end_date: Option<u32>,
     url: Option<String>,
+    // Progress of the current round, both 0 before the first pairing
+    completed_boards: u32,
+    total_boards: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorDescription {
+    pub code: String,
+    pub status: u16,
+    pub message: String,
+}
+
+// The message is the error's own, placeholders stand for the fields, see `AppError::catalog`
+impl From<&AppError> for ErrorDescription {
+    fn from(value: &AppError) -> Self {
+        Self {
+            code: value.code(),
+            status: value.status().as_u16(),
+            message: value.to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -124,6 +596,8 @@ pub struct TournamentItem {
 pub struct ErrorResponse {
     pub code: String,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
     #[serde(skip_serializing)]
     pub status_code: StatusCode,
 }
@@ -132,10 +606,23 @@ pub struct ErrorResponse {
 #[serde(rename_all = "camelCase")]
 #[serde(rename_all_fields = "camelCase")]
 #[serde(tag = "type")]
+#[allow(clippy::large_enum_variant)]
 pub enum SuccessResponse {
     UserCreated {
         id: i64,
     },
+    EmailVerified,
+    SectionCreated {
+        id: i64,
+    },
+    PlayoffRoundCreated {
+        round: u32,
+        games: Vec<(u32, u32)>,
+    },
+    PlayoffResultUpdated {
+        game_id: u32,
+        game_result: String,
+    },
     TournamentEnded {
         timestamp: i64,
     },
@@ -149,12 +636,35 @@ pub enum SuccessResponse {
     PlayerUpdated {
         player: DbPlayer,
     },
+    Player {
+        player: DbPlayer,
+    },
+    PlayersMerged {
+        keep_id: u32,
+        remove_id: u32,
+        registrations: u64,
+    },
     TournamentCreated {
         id: i64,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        warnings: Vec<String>,
     },
     PlayerList {
         players: Vec<DbPlayer>,
     },
+    PlayerGames {
+        games: Vec<PlayerGame>,
+        page: u32,
+        page_size: u32,
+    },
+    HeadToHead {
+        player_id: u32,
+        opponent_id: u32,
+        wins: u32,
+        draws: u32,
+        losses: u32,
+        games: Vec<PlayerGame>,
+    },
     PlayerRegistered {
         id: i64,
     },
@@ -170,7 +680,7 @@ pub enum SuccessResponse {
         current_round: u32,
         num_rounds: u32,
         time_category: String,
-        start_date: usize,
+        start_date: u32,
         federation: String,
         players: Vec<RegisteredPlayer>,
         pairings: Vec<Vec<RoundPairing>>,
@@ -178,6 +688,30 @@ pub enum SuccessResponse {
         standings: Vec<Vec<PlayerStanding>>,
         user_id: u32,
         username: String,
+        organizers: Vec<DbOrganizer>,
+        sections: BTreeMap<String, SectionData>,
+        playoff: Vec<DbPlayoffGame>,
+        max_float_gap: Option<u32>,
+        registration_open: bool,
+        late_entry_default_score: u32,
+        unrated_default_rating: Option<u32>,
+        board_offset: u32,
+        allow_repeat_pairings: bool,
+        visibility: String,
+        requested_bye_score: u32,
+        games_per_pairing: u32,
+        tiebreaks: Vec<String>,
+        player_count: u32,
+        max_players: u32,
+        broadcast_boards: u32,
+        self_registration: bool,
+        allow_three_same_colors: bool,
+        time_control: Option<String>,
+        avoid_recent_opponents: bool,
+        allow_rematch_reversed_colors: bool,
+        avoid_last_round_contender_bye: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        organizer_data: Option<OrganizerData>,
         updated_at: u32,
         end_date: Option<u32>,
         url: Option<String>,
@@ -185,6 +719,42 @@ pub enum SuccessResponse {
     TournamentList {
         tournaments: Vec<TournamentItem>,
     },
+    CurrentRound {
+        round: u32,
+        boards: Vec<CurrentBoard>,
+        byes: Vec<RoundBye>,
+    },
+    PendingBoards {
+        round: u32,
+        boards: Vec<CurrentBoard>,
+    },
+    TournamentStats {
+        games: u32,
+        decisive_percentage: f64,
+        white_wins: u32,
+        black_wins: u32,
+        draws: u32,
+        white_win_percentage: f64,
+        black_win_percentage: f64,
+        byes: u32,
+        forfeits: u32,
+        average_rating: u32,
+        rating_spread: u32,
+    },
+    Standings {
+        round: u32,
+        standings: Vec<PlayerStanding>,
+        updated_at: u32,
+    },
+    // Nothing was stored, see `simulate_standings`
+    SimulatedStandings {
+        round: u32,
+        standings: Vec<PlayerStanding>,
+    },
+    RoundColors {
+        round: u32,
+        boards: Vec<ColorDecision>,
+    },
     ResultUpdated {
         board_id: u32,
         game_result: String,
@@ -193,8 +763,61 @@ pub enum SuccessResponse {
         registration_id: u32,
         status: String,
     },
+    OrganizerAdded {
+        user_id: u32,
+        username: String,
+    },
+    OrganizerRemoved {
+        user_id: u32,
+    },
+    ResultsAnnulled {
+        registration_id: u32,
+        games: u64,
+    },
+    ResultsRestored {
+        registration_id: u32,
+        games: u64,
+    },
+    AuditLog {
+        entries: Vec<DbAuditEntry>,
+    },
+    AgreedDraws {
+        players: Vec<PlayerAgreedDraws>,
+    },
+    Registrations {
+        registrations: Vec<RegisteredPlayer>,
+    },
+    PairingConstraints {
+        constraints: PairingConstraints,
+    },
+    PairingGraph {
+        graph: PairingGraph,
+    },
+    // Byes of the next round with the current scores, empty when every section is even
+    ByePrediction {
+        round: u32,
+        byes: Vec<u32>,
+    },
+    Errors {
+        errors: Vec<ErrorDescription>,
+    },
+    RegistrationOpenUpdated {
+        registration_open: bool,
+    },
+    VisibilityUpdated {
+        visibility: String,
+    },
+    TimeControlUpdated {
+        time_control: Option<String>,
+    },
+    PlayerUnregistered {
+        registration_id: u32,
+    },
     FidePlayer {
         player: FidePlayer,
+        // Stored players without a FIDE id that look like the same person, to merge instead
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        possible_duplicates: Vec<DbPlayer>,
     },
     LoginSuccess {
         token: String,
@@ -234,18 +857,22 @@ impl From<NewPairings> for AppResponse {
 
 impl From<Tournament> for AppResponse {
     fn from(value: Tournament) -> Self {
+        let current_round = value.current_round();
         let mut pairings: Vec<Vec<RoundPairing>> = value
             .pairings
             .iter()
-            .map(|round| {
+            .enumerate()
+            .map(|(round_number, round)| {
                 round
                     .iter()
                     .enumerate()
                     .map(|(board_number, (white_id, black_id))| RoundPairing {
-                        board_number: board_number as u32,
+                        board_number: board_number as u32 + value.board_offset,
                         white_id: *white_id as u32,
                         black_id: *black_id as u32,
                         result: None,
+                        broadcast: round_number + 1 == current_round
+                            && (board_number as u32) < value.broadcast_boards,
                     })
                     .collect()
             })
@@ -277,6 +904,38 @@ impl From<Tournament> for AppResponse {
                 }
             }
         }
+        let sections = value
+            .section_standings()
+            .into_iter()
+            .zip(value.sections.iter())
+            .map(|((id, standings), section)| {
+                let pairings = pairings
+                    .iter()
+                    .map(|round| {
+                        round
+                            .iter()
+                            .filter(|p| value.players[&p.white_id].section_id == Some(id))
+                            .cloned()
+                            .collect()
+                    })
+                    .collect();
+                (
+                    section.name.clone(),
+                    SectionData {
+                        id,
+                        pairings,
+                        standings,
+                    },
+                )
+            })
+            .collect();
+        let player_count = value.players.len() as u32;
+        let max_players = value.max_players();
+        let seeds: Vec<(u32, u32)> =
+            seeding_order(value.players.values(), value.unrated_default_rating)
+                .into_iter()
+                .zip(1..)
+                .collect();
         Self::Success {
             payload: SuccessResponse::TournamentData {
                 id: value.id,
@@ -287,20 +946,10 @@ impl From<Tournament> for AppResponse {
                 start_date: value.start_date,
                 federation: value.federation.clone(),
                 end_date: value.end_date,
-                players: value
-                    .players
-                    .values()
-                    .map(|p| RegisteredPlayer {
-                        id: p.id,
-                        player_id: p.db_id,
-                        name: p.name.clone(),
-                        title: p.title.to_string(),
-                        federation: p.federation.clone(),
-                        fide_id: p.fide_id,
-                        rating: p.rating,
-                        status: p.status.to_string(),
-                    })
-                    .sorted_unstable_by(|a, b| a.id.cmp(&b.id))
+                players: seeds
+                    .into_iter()
+                    .sorted_unstable()
+                    .map(|(id, seed)| RegisteredPlayer::new(&value.players[&id], seed))
                     .collect(),
                 pairings,
                 standings: value.standings(),
@@ -308,30 +957,72 @@ impl From<Tournament> for AppResponse {
                 gaps,
                 user_id: value.user_id,
                 username: value.username,
+                organizers: value.organizers,
+                sections,
+                playoff: value.playoff,
+                max_float_gap: value.max_float_gap,
+                registration_open: value.registration_open,
+                late_entry_default_score: value.late_entry_default_score,
+                unrated_default_rating: value.unrated_default_rating,
+                board_offset: value.board_offset,
+                allow_repeat_pairings: value.allow_repeat_pairings,
+                visibility: value.visibility.to_string(),
+                requested_bye_score: value.requested_bye_score,
+                games_per_pairing: value.games_per_pairing,
+                tiebreaks: value.tiebreaks.iter().map(ToString::to_string).collect(),
+                player_count,
+                max_players,
+                broadcast_boards: value.broadcast_boards,
+                self_registration: value.self_registration,
+                allow_three_same_colors: value.allow_three_same_colors,
+                time_control: value.time_control,
+                avoid_recent_opponents: value.avoid_recent_opponents,
+                allow_rematch_reversed_colors: value.allow_rematch_reversed_colors,
+                avoid_last_round_contender_bye: value.avoid_last_round_contender_bye,
+                organizer_data: None,
                 updated_at: value.updated_at,
             },
         }
     }
 }
 
-impl From<Vec<DbTournament>> for AppResponse {
-    fn from(value: Vec<DbTournament>) -> Self {
+impl AppResponse {
+    // Adds the organizer-only part to a TournamentData response
+    pub fn with_organizer_data(mut self, data: Option<OrganizerData>) -> Self {
+        if let Self::Success {
+            payload: SuccessResponse::TournamentData { organizer_data, .. },
+        } = &mut self
+        {
+            *organizer_data = data;
+        }
+        self
+    }
+}
+
+impl From<Vec<DbTournamentSummary>> for AppResponse {
+    fn from(value: Vec<DbTournamentSummary>) -> Self {
         Self::Success {
             payload: SuccessResponse::TournamentList {
                 tournaments: value
                     .into_iter()
-                    .map(|t| TournamentItem {
-                        id: t.id,
-                        name: t.name,
-                        num_rounds: t.num_rounds,
-                        current_round: t.current_round,
-                        time_category: t.time_category,
-                        end_date: t.end_date,
-                        federation: t.federation,
-                        url: t.url,
-                        user_id: t.user_id,
-                        username: t.username,
-                        updated_at: t.updated_at,
+                    .map(|summary| {
+                        let t = summary.tournament;
+                        TournamentItem {
+                            id: t.id,
+                            name: t.name,
+                            num_rounds: t.num_rounds,
+                            current_round: t.current_round,
+                            time_category: t.time_category,
+                            start_date: t.start_date,
+                            end_date: t.end_date,
+                            federation: t.federation,
+                            url: t.url,
+                            user_id: t.user_id,
+                            username: t.username,
+                            updated_at: t.updated_at,
+                            completed_boards: summary.completed_boards,
+                            total_boards: summary.total_boards,
+                        }
                     })
                     .collect(),
             },
@@ -344,7 +1035,8 @@ impl From<RoundResult> for AppResponse {
         Self::Success {
             payload: SuccessResponse::ResultUpdated {
                 board_id: value.board_id,
-                game_result: value.result,
+                // Canonical notation, whichever one the client sent
+                game_result: GameResult::from_str(&value.result).to_string(),
             },
         }
     }
@@ -353,7 +1045,68 @@ impl From<RoundResult> for AppResponse {
 impl From<FidePlayer> for AppResponse {
     fn from(value: FidePlayer) -> Self {
         Self::Success {
-            payload: SuccessResponse::FidePlayer { player: value },
+            payload: SuccessResponse::FidePlayer {
+                player: value,
+                possible_duplicates: Vec::new(),
+            },
+        }
+    }
+}
+
+impl From<HeadToHead> for AppResponse {
+    fn from(value: HeadToHead) -> Self {
+        Self::Success {
+            payload: SuccessResponse::HeadToHead {
+                player_id: value.player_id,
+                opponent_id: value.opponent_id,
+                wins: value.wins,
+                draws: value.draws,
+                losses: value.losses,
+                games: value.games,
+            },
+        }
+    }
+}
+
+impl From<CurrentRound> for AppResponse {
+    fn from(value: CurrentRound) -> Self {
+        Self::Success {
+            payload: SuccessResponse::CurrentRound {
+                round: value.round,
+                boards: value.boards,
+                byes: value.byes,
+            },
+        }
+    }
+}
+
+impl From<PendingBoards> for AppResponse {
+    fn from(value: PendingBoards) -> Self {
+        Self::Success {
+            payload: SuccessResponse::PendingBoards {
+                round: value.round,
+                boards: value.boards,
+            },
+        }
+    }
+}
+
+impl From<TournamentStats> for AppResponse {
+    fn from(value: TournamentStats) -> Self {
+        Self::Success {
+            payload: SuccessResponse::TournamentStats {
+                games: value.games,
+                decisive_percentage: value.decisive_percentage,
+                white_wins: value.white_wins,
+                black_wins: value.black_wins,
+                draws: value.draws,
+                white_win_percentage: value.white_win_percentage,
+                black_win_percentage: value.black_win_percentage,
+                byes: value.byes,
+                forfeits: value.forfeits,
+                average_rating: value.average_rating,
+                rating_spread: value.rating_spread,
+            },
         }
     }
 }
@@ -367,47 +1120,53 @@ impl IntoResponse for AppResponse {
     }
 }
 
+// Marks error responses so `legacy_error_status` and `body_limit_error` can find them
+#[derive(Clone, Copy)]
+struct ErrorMarker;
+
+// Older clients read `error.code` from 200 responses, with LEGACY_ERROR_STATUS errors are sent
+// with 200 again, the body is unchanged
+pub async fn legacy_error_status(
+    mut response: axum::response::Response,
+) -> axum::response::Response {
+    if response.extensions().get::<ErrorMarker>().is_some() {
+        *response.status_mut() = StatusCode::OK;
+    }
+    response
+}
+
+// The body limit layer answers a too large Content-Length with a plain text 413 before any
+// handler runs, it gets the same error envelope as bodies found too large while reading
+pub async fn body_limit_error(response: axum::response::Response) -> axum::response::Response {
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE
+        && response.extensions().get::<ErrorMarker>().is_none()
+    {
+        return AppError::PayloadTooLarge("request body".to_string()).into_response();
+    }
+    response
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
-        let status_code = match &self {
-            AppError::EmptyPairingsGenerated => StatusCode::BAD_REQUEST,
-            AppError::InvalidPlayerStatus(_) => StatusCode::BAD_REQUEST,
-            AppError::DuplicatePlayerResult(_) => StatusCode::BAD_REQUEST,
-            AppError::RoundNotDone => StatusCode::BAD_REQUEST,
-            AppError::InvalidPlayerId(_) => StatusCode::NOT_FOUND,
-            AppError::InvalidPlayerScore(_) => StatusCode::BAD_REQUEST,
-            AppError::InvalidTimeCategory(_) => StatusCode::BAD_REQUEST,
-            AppError::InvalidNumberOfRounds(_) => StatusCode::BAD_REQUEST,
-            AppError::RoundNotFound(_) => StatusCode::NOT_FOUND,
-            AppError::GameNotFound { round: _, game: _ } => StatusCode::NOT_FOUND,
-            AppError::PlayerNotFound(_) => StatusCode::NOT_FOUND,
-            AppError::InsertGameHistorySkipsRound => StatusCode::BAD_REQUEST,
-            AppError::TournamentEnded => StatusCode::BAD_REQUEST,
-            AppError::TournamentNotStarted => StatusCode::BAD_REQUEST,
-            AppError::InvalidRound(_) => StatusCode::NOT_FOUND,
-            AppError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            AppError::InsufficientPlayers => StatusCode::BAD_REQUEST,
-            AppError::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
-            AppError::FideScrapeFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            AppError::MissingContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
-            AppError::JsonSyntaxError(_) => StatusCode::BAD_REQUEST,
-            AppError::JsonDataError => StatusCode::BAD_REQUEST,
-            AppError::JsonUnknownError => StatusCode::BAD_REQUEST,
-            AppError::LoginFailed(_) => StatusCode::UNAUTHORIZED,
-            AppError::UsernameTaken(_) => StatusCode::BAD_REQUEST,
-            AppError::TournamentNotFound => StatusCode::NOT_FOUND,
-            AppError::InsufficientPermissions => StatusCode::UNAUTHORIZED,
-            AppError::CannotEndTournament => StatusCode::BAD_REQUEST,
-            AppError::TokenInvalid => StatusCode::UNAUTHORIZED,
-            AppError::InvalidAuthHeader => StatusCode::UNAUTHORIZED,
-        };
-        AxumJson(AppResponse::Error {
-            error: ErrorResponse {
-                code: self.code(),
-                message: format!("{}", self),
-                status_code,
-            },
-        })
-        .into_response()
+        let status_code = self.status();
+        let mut response = (
+            status_code,
+            AxumJson(AppResponse::Error {
+                error: ErrorResponse {
+                    code: self.code(),
+                    message: format!("{}", self),
+                    details: self.details(),
+                    status_code,
+                },
+            }),
+        )
+            .into_response();
+        response.extensions_mut().insert(ErrorMarker);
+        if let AppError::AccountLocked(retry_after) = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
     }
 }
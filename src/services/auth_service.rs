@@ -0,0 +1,117 @@
+use crate::{
+    auth::{
+        email::{EmailSender, generate_verification_token},
+        hasher::hash_password,
+    },
+    errors::AppError,
+    payloads::NewUser,
+    repositories::auth_repo::{self, get_user},
+};
+
+pub async fn create_user(
+    pool: &sqlx::SqlitePool,
+    mailer: &dyn EmailSender,
+    payload: NewUser,
+) -> Result<i64, AppError> {
+    match get_user(pool, &payload.username).await {
+        Err(AppError::LoginFailed(_)) => {}
+        Ok(_) => return Err(AppError::UsernameTaken(payload.username)),
+        Err(_) => return Err(AppError::Unknown),
+    }
+    let password_hash = hash_password(&payload.password)?;
+    // Users without an email get no link, they have nothing to verify
+    let token = payload
+        .email
+        .as_ref()
+        .map(|_| generate_verification_token());
+    let id = auth_repo::create_user(
+        pool,
+        &payload.username,
+        &password_hash,
+        payload.email.clone(),
+        token.as_deref(),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("create_user: {:?}", e);
+        AppError::Unknown
+    })?;
+    if let (Some(email), Some(token)) = (payload.email, token)
+        && let Err(e) = mailer.send_verification(&email, &token)
+    {
+        // The user can ask for a new link later, registration still succeeds
+        tracing::error!("send_verification: {:?}", e);
+    }
+    Ok(id)
+}
+
+pub async fn verify_email(pool: &sqlx::SqlitePool, token: &str) -> Result<(), AppError> {
+    let verified = auth_repo::verify_email(pool, token).await?;
+    if verified == 0 {
+        return Err(AppError::InvalidVerificationToken);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::{
+        auth::email::EmailSender, errors::AppError, payloads::NewUser,
+        repositories::auth_repo::is_email_verified,
+    };
+
+    use super::{create_user, verify_email};
+
+    #[derive(Default)]
+    struct MockEmailSender {
+        sent: Mutex<Vec<(String, String)>>,
+    }
+
+    impl EmailSender for MockEmailSender {
+        fn send_verification(&self, email: &str, token: &str) -> Result<(), AppError> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((email.to_string(), token.to_string()));
+            Ok(())
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_email_verification(pool: sqlx::SqlitePool) {
+        let mailer = MockEmailSender::default();
+        let payload = NewUser {
+            username: "new_user".to_string(),
+            password: "password".to_string(),
+            email: Some("new@mail.com".to_string()),
+        };
+        let id = create_user(&pool, &mailer, payload)
+            .await
+            .expect("failed to create user") as u32;
+        assert!(!is_email_verified(&pool, id).await.unwrap());
+        let (email, token) = mailer.sent.lock().unwrap().pop().expect("no email sent");
+        assert_eq!(email, "new@mail.com");
+        assert!(matches!(
+            verify_email(&pool, "wrong").await,
+            Err(AppError::InvalidVerificationToken)
+        ));
+        verify_email(&pool, &token)
+            .await
+            .expect("failed to verify email");
+        assert!(is_email_verified(&pool, id).await.unwrap());
+        // Tokens can only be used once
+        assert!(verify_email(&pool, &token).await.is_err());
+        let payload = NewUser {
+            username: "no_email".to_string(),
+            password: "password".to_string(),
+            email: None,
+        };
+        let id = create_user(&pool, &mailer, payload)
+            .await
+            .expect("failed to create user") as u32;
+        assert!(mailer.sent.lock().unwrap().is_empty());
+        assert!(is_email_verified(&pool, id).await.unwrap());
+    }
+}
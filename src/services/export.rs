@@ -0,0 +1,82 @@
+use std::fmt::Write;
+
+use chrono::DateTime;
+
+use crate::models::tournament::{Tiebreak, Tournament, escape_html};
+
+// Scores and tiebreaks are stored in half points
+fn points(half_points: u32) -> String {
+    match (half_points / 2, half_points % 2) {
+        (0, 1) => "½".to_string(),
+        (points, 1) => format!("{}½", points),
+        (points, _) => points.to_string(),
+    }
+}
+
+fn tiebreak_label(tiebreak: Tiebreak) -> &'static str {
+    match tiebreak {
+        Tiebreak::MedianBuchholz => "Median Buchholz",
+        Tiebreak::CutOneBuchholz => "Buchholz Cut 1",
+        Tiebreak::Buchholz => "Buchholz",
+        Tiebreak::Progressive => "Progressive",
+        Tiebreak::CumulativeCut1 => "Progressive Cut 1",
+    }
+}
+
+// Self-contained printable page with the standings after the last round, None before the
+// first round is paired
+pub fn standings_html(tournament: &Tournament) -> Option<String> {
+    let standings = tournament.standings();
+    let last = standings.last()?;
+    let name = escape_html(&tournament.name);
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{name}</title>\n\
+         <style>\n\
+         body {{ font-family: sans-serif; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th, td {{ border: 1px solid #999; padding: 2px 8px; }}\n\
+         td.number {{ text-align: right; }}\n\
+         </style>\n</head>\n<body>\n<h1>{name}</h1>\n<h2>Standings after round {round}</h2>\n",
+        round = standings.len()
+    );
+    html.push_str("<table>\n<tr><th>Rank</th><th>Name</th><th>Score</th>");
+    for tiebreak in tournament.tiebreaks.iter() {
+        let _ = write!(html, "<th>{}</th>", tiebreak_label(*tiebreak));
+    }
+    html.push_str("</tr>\n");
+    for (rank, standing) in last.iter().enumerate() {
+        let player = &tournament.players[&standing.player_id];
+        let title = player.title.to_string();
+        let player_name = if title.is_empty() {
+            player.name.to_html()
+        } else {
+            format!("{} {}", title, player.name.to_html())
+        };
+        let _ = write!(
+            html,
+            "<tr><td class=\"number\">{}</td><td>{}</td><td class=\"number\">{}</td>",
+            rank + 1,
+            player_name,
+            points(standing.score)
+        );
+        for tiebreak in tournament.tiebreaks.iter() {
+            let _ = write!(
+                html,
+                "<td class=\"number\">{}</td>",
+                points(tiebreak.value(standing))
+            );
+        }
+        html.push_str("</tr>\n");
+    }
+    let updated_at = DateTime::from_timestamp(tournament.updated_at as i64, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d %H:%M UTC");
+    let _ = write!(
+        html,
+        "</table>\n<p>Updated {}</p>\n</body>\n</html>\n",
+        updated_at
+    );
+    Some(html)
+}
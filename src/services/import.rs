@@ -0,0 +1,502 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDate;
+
+use crate::{
+    errors::AppError,
+    models::{
+        import::{ImportedGame, ImportedGap, ImportedPlayer, ImportedTournament},
+        tournament::{GameResult, is_valid_time_control},
+    },
+    payloads::NewTournament,
+};
+
+// Column of the first round of a player line, every round takes 10 columns
+const FIRST_ROUND_COLUMN: usize = 92;
+const ROUND_WIDTH: usize = 10;
+
+fn invalid(line: usize, message: impl Into<String>) -> AppError {
+    AppError::InvalidTrf {
+        line,
+        message: message.into(),
+    }
+}
+
+// Trimmed text between two 1-based columns, both included, empty past the end of the line
+fn field(chars: &[char], from: usize, to: usize) -> String {
+    chars
+        .iter()
+        .skip(from - 1)
+        .take(to + 1 - from)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+// TRF titles are abbreviated, g for GM, wm for WIM...
+fn title(code: &str) -> Option<String> {
+    let title = match code.to_lowercase().as_str() {
+        "" => return None,
+        "g" => "GM",
+        "m" => "IM",
+        "f" => "FM",
+        "c" => "CM",
+        "wg" => "WGM",
+        "wm" => "WIM",
+        "wf" => "WFM",
+        "wc" => "WCM",
+        _ => return Some(code.to_uppercase()),
+    };
+    Some(title.to_string())
+}
+
+// One round of a player line: opponent start rank, color and result code
+struct RoundEntry {
+    opponent: Option<u32>,
+    color: char,
+    result: char,
+}
+
+struct PlayerLine {
+    line: usize,
+    player: ImportedPlayer,
+    rounds: Vec<Option<RoundEntry>>,
+}
+
+fn parse_player(line: usize, chars: &[char]) -> Result<PlayerLine, AppError> {
+    let start_rank: u32 = field(chars, 5, 8)
+        .parse()
+        .map_err(|_| invalid(line, "start rank (columns 5-8) is not a number"))?;
+    let name = field(chars, 15, 47);
+    if name.is_empty() {
+        return Err(invalid(line, "player name (columns 15-47) is empty"));
+    }
+    let (last_name, first_name) = match name.split_once(',') {
+        Some((last, first)) => (last.trim().to_string(), first.trim().to_string()),
+        None => (name, String::new()),
+    };
+    let number = |from, to, what: &str| -> Result<Option<u32>, AppError> {
+        match field(chars, from, to).as_str() {
+            "" => Ok(None),
+            value => value
+                .parse()
+                .map(|n| Some(n).filter(|n| *n > 0))
+                .map_err(|_| invalid(line, format!("{} is not a number", what))),
+        }
+    };
+    let rating = number(49, 52, "rating (columns 49-52)")?;
+    let fide_id = number(58, 68, "FIDE id (columns 58-68)")?.map(i64::from);
+    let federation = Some(field(chars, 54, 56)).filter(|f| !f.is_empty());
+    let mut rounds = Vec::new();
+    let mut column = FIRST_ROUND_COLUMN;
+    while column <= chars.len() {
+        let round = rounds.len() + 1;
+        let opponent = field(chars, column, column + 3);
+        let color = chars.get(column + 4).copied().unwrap_or(' ');
+        let result = chars.get(column + 6).copied().unwrap_or(' ');
+        let opponent = match opponent.as_str() {
+            "" | "0000" => None,
+            rank => Some(rank.parse().map_err(|_| {
+                invalid(line, format!("opponent of round {} is not a number", round))
+            })?),
+        };
+        rounds.push(match (opponent, color, result) {
+            (None, _, ' ') => None,
+            (Some(_), 'w' | 'b', '1' | '0' | '=' | '+' | '-' | 'W' | 'D' | 'L' | ' ') => {
+                Some(RoundEntry {
+                    opponent,
+                    color,
+                    result,
+                })
+            }
+            (None, _, 'U' | 'F' | 'H' | 'Z' | '+' | '-') => Some(RoundEntry {
+                opponent,
+                color,
+                result,
+            }),
+            _ => {
+                return Err(invalid(
+                    line,
+                    format!(
+                        "round {} has an invalid color `{}` or result `{}`",
+                        round, color, result
+                    ),
+                ));
+            }
+        });
+        column += ROUND_WIDTH;
+    }
+    Ok(PlayerLine {
+        line,
+        player: ImportedPlayer {
+            start_rank,
+            first_name,
+            last_name,
+            title: title(&field(chars, 11, 13)),
+            federation,
+            fide_id,
+            rating,
+        },
+        rounds,
+    })
+}
+
+// Result of a game from the result codes of the white and the black player
+fn game_result(white: char, black: char) -> Option<GameResult> {
+    match (white, black) {
+        ('1' | 'W', '0' | 'L') | ('+', '-') => Some(GameResult::WhiteWins),
+        ('0' | 'L', '1' | 'W') | ('-', '+') => Some(GameResult::BlackWins),
+        ('=' | 'D', '=' | 'D') => Some(GameResult::Draw),
+        ('-', '-') => Some(GameResult::DoubleForfeit),
+        (' ', ' ') => Some(GameResult::Ongoing),
+        _ => None,
+    }
+}
+
+// Reads the tournament name (012), federation (032), start date (042), player lines (001)
+// and the optional number of rounds (XXR) of a FIDE TRF16 file. Errors on problems that are
+// not tied to a single line are reported at line 0
+pub fn from_trf16(input: &str) -> Result<ImportedTournament, AppError> {
+    let mut name = None;
+    let mut federation = None;
+    let mut start_date = None;
+    let mut num_rounds = None;
+    let mut time_control = None;
+    let mut lines = Vec::new();
+    for (index, text) in input.lines().enumerate() {
+        let line = index + 1;
+        let chars: Vec<char> = text.trim_end().chars().collect();
+        let code: String = chars.iter().take(3).collect();
+        let value = field(&chars, 5, chars.len().max(5));
+        match code.as_str() {
+            "012" => name = Some(value),
+            "032" => federation = Some(value).filter(|f| !f.is_empty()),
+            // Free text in TRF, only kept when it is a time control the API understands
+            "122" => time_control = Some(value).filter(|t| is_valid_time_control(t)),
+            "042" => {
+                let date = ["%Y/%m/%d", "%Y-%m-%d", "%Y.%m.%d"]
+                    .iter()
+                    .find_map(|format| NaiveDate::parse_from_str(&value, format).ok())
+                    .ok_or_else(|| invalid(line, format!("start date `{}` is not valid", value)))?;
+                let timestamp = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
+                start_date = Some(u32::try_from(timestamp).map_err(|_| {
+                    invalid(line, format!("start date `{}` is out of range", value))
+                })?);
+            }
+            "XXR" => {
+                num_rounds = Some(value.parse::<u32>().map_err(|_| {
+                    invalid(line, format!("number of rounds `{}` is not valid", value))
+                })?)
+            }
+            "001" => lines.push(parse_player(line, &chars)?),
+            _ => (),
+        }
+    }
+    let name = name
+        .filter(|n| !n.is_empty())
+        .ok_or_else(|| invalid(0, "missing tournament name (012)"))?;
+    let start_date = start_date.ok_or_else(|| invalid(0, "missing start date (042)"))?;
+    if lines.is_empty() {
+        return Err(invalid(0, "no player lines (001)"));
+    }
+    let mut by_rank: HashMap<u32, &PlayerLine> = HashMap::new();
+    let mut fide_ids = HashSet::new();
+    for player in lines.iter() {
+        if by_rank.insert(player.player.start_rank, player).is_some() {
+            return Err(invalid(
+                player.line,
+                format!("start rank {} is used twice", player.player.start_rank),
+            ));
+        }
+        // Both lines would be registrations of the same stored player
+        if let Some(fide_id) = player.player.fide_id
+            && !fide_ids.insert(fide_id)
+        {
+            return Err(invalid(
+                player.line,
+                format!("FIDE id {} is used twice", fide_id),
+            ));
+        }
+    }
+    let current_round = lines
+        .iter()
+        .filter_map(|p| p.rounds.iter().rposition(Option::is_some))
+        .max()
+        .map_or(0, |round| round + 1);
+    let mut games = Vec::new();
+    let mut gaps = Vec::new();
+    for player in lines.iter() {
+        let rank = player.player.start_rank;
+        for round in 0..current_round {
+            let entry = match player.rounds.get(round).and_then(Option::as_ref) {
+                Some(entry) => entry,
+                // Not paired in a round that was played, the player was absent
+                None => {
+                    gaps.push(ImportedGap {
+                        round_number: round as u32,
+                        player: rank,
+                        score: 0,
+                        is_bye: false,
+                    });
+                    continue;
+                }
+            };
+            let Some(opponent) = entry.opponent else {
+                let (score, is_bye) = match entry.result {
+                    'U' => (2, true),
+                    'F' | '+' => (2, false),
+                    'H' => (1, false),
+                    _ => (0, false),
+                };
+                gaps.push(ImportedGap {
+                    round_number: round as u32,
+                    player: rank,
+                    score,
+                    is_bye,
+                });
+                continue;
+            };
+            let other = by_rank
+                .get(&opponent)
+                .and_then(|o| o.rounds.get(round).and_then(Option::as_ref).map(|e| (o, e)));
+            let (other, other_entry) = match other {
+                Some((other, other_entry)) if other_entry.opponent == Some(rank) => {
+                    (other, other_entry)
+                }
+                Some(_) => {
+                    return Err(invalid(
+                        player.line,
+                        format!(
+                            "opponent {} of round {} is not paired against {}",
+                            opponent,
+                            round + 1,
+                            rank
+                        ),
+                    ));
+                }
+                None if by_rank.contains_key(&opponent) => {
+                    return Err(invalid(
+                        player.line,
+                        format!(
+                            "opponent {} of round {} has no game that round",
+                            opponent,
+                            round + 1
+                        ),
+                    ));
+                }
+                None => {
+                    return Err(invalid(
+                        player.line,
+                        format!(
+                            "opponent {} of round {} does not exist",
+                            opponent,
+                            round + 1
+                        ),
+                    ));
+                }
+            };
+            if entry.color == other_entry.color {
+                return Err(invalid(
+                    player.line,
+                    format!(
+                        "both players of round {} have the same color as start rank {}",
+                        round + 1,
+                        other.player.start_rank
+                    ),
+                ));
+            }
+            if entry.color == 'b' {
+                continue;
+            }
+            let result = game_result(entry.result, other_entry.result).ok_or_else(|| {
+                invalid(
+                    player.line,
+                    format!(
+                        "results `{}` and `{}` of round {} do not match",
+                        entry.result,
+                        other_entry.result,
+                        round + 1
+                    ),
+                )
+            })?;
+            games.push(ImportedGame {
+                round_number: round as u32,
+                board_number: 0,
+                white: rank,
+                black: opponent,
+                result,
+            });
+        }
+    }
+    // Boards are not part of the format, the best start rank gets the first board
+    games.sort_by_key(|g| (g.round_number, g.white.min(g.black)));
+    for round in 0..current_round as u32 {
+        for (board, game) in games
+            .iter_mut()
+            .filter(|g| g.round_number == round)
+            .enumerate()
+        {
+            game.board_number = board as u32;
+        }
+    }
+    let players = lines.into_iter().map(|p| p.player).collect();
+    Ok(ImportedTournament {
+        tournament: NewTournament {
+            name,
+            rounds: num_rounds.unwrap_or(current_round as u32),
+            time_category: "standard".to_string(),
+            start_date,
+            federation: federation.unwrap_or_else(|| "FID".to_string()),
+            url: None,
+            max_float_gap: None,
+            late_entry_default_score: None,
+            unrated_default_rating: None,
+            board_offset: None,
+            allow_repeat_pairings: None,
+            visibility: None,
+            requested_bye_score: None,
+            games_per_pairing: None,
+            tiebreaks: None,
+            max_players: None,
+            broadcast_boards: None,
+            self_registration: None,
+            allow_three_same_colors: None,
+            time_control,
+            avoid_recent_opponents: None,
+            allow_rematch_reversed_colors: None,
+            avoid_last_round_contender_bye: None,
+        },
+        players,
+        games,
+        gaps,
+        current_round: current_round as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{errors::AppError, models::tournament::GameResult};
+
+    use super::from_trf16;
+
+    fn player_line(rank: u32, title: &str, name: &str, rating: u32, rounds: &[&str]) -> String {
+        let mut line = format!(
+            "001 {:>4} m{:>3} {:<33} {:>4} {:>3} {:>11} {:>10} {:>4} {:>4}",
+            rank, title, name, rating, "NOR", 0, "1990/01/01", "0.0", rank
+        );
+        for round in rounds {
+            line.push_str(&format!("  {}", round));
+        }
+        line
+    }
+
+    fn trf(rounds: [[&str; 2]; 4]) -> String {
+        let names = ["Alpha, Anna", "Bravo, Ben", "Charlie, Carl", "Delta, Dora"];
+        let mut lines = vec![
+            "012 Club Championship".to_string(),
+            "032 NOR".to_string(),
+            "042 2024/03/01".to_string(),
+            "XXR 3".to_string(),
+        ];
+        for (i, name) in names.iter().enumerate() {
+            let title = if i == 0 { "g" } else { "" };
+            lines.push(player_line(
+                i as u32 + 1,
+                title,
+                name,
+                2000 - i as u32 * 100,
+                &rounds[i],
+            ));
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn test_from_trf16() {
+        let input = trf([
+            ["   3 w 1", "   4 b +"],
+            ["   4 w =", "0000 - H"],
+            ["   1 b 0", "0000 - U"],
+            ["   2 b =", "   1 w -"],
+        ]);
+        let imported = from_trf16(&format!("122 90+30\n{}", input)).unwrap();
+        assert_eq!(imported.tournament.name, "Club Championship");
+        assert_eq!(imported.tournament.rounds, 3);
+        assert_eq!(imported.tournament.start_date, 1_709_251_200);
+        assert_eq!(imported.tournament.time_control.as_deref(), Some("90+30"));
+        assert_eq!(imported.current_round, 2);
+        assert_eq!(imported.players.len(), 4);
+        assert_eq!(imported.players[0].title.as_deref(), Some("GM"));
+        assert_eq!(imported.players[0].last_name, "Alpha");
+        assert_eq!(imported.players[0].first_name, "Anna");
+        assert_eq!(imported.players[3].rating, Some(1700));
+        assert_eq!(imported.players[3].fide_id, None);
+        let games: Vec<_> = imported
+            .games
+            .iter()
+            .map(|g| (g.round_number, g.board_number, g.white, g.black, g.result))
+            .collect();
+        assert_eq!(
+            games,
+            vec![
+                (0, 0, 1, 3, GameResult::WhiteWins),
+                (0, 1, 2, 4, GameResult::Draw),
+                (1, 0, 4, 1, GameResult::BlackWins),
+            ]
+        );
+        let gaps: Vec<_> = imported
+            .gaps
+            .iter()
+            .map(|g| (g.round_number, g.player, g.score, g.is_bye))
+            .collect();
+        assert_eq!(gaps, vec![(1, 2, 1, false), (1, 3, 2, true)]);
+    }
+
+    #[test]
+    fn test_from_trf16_errors() {
+        // Player 1 says they played 3, who says they played 2
+        let error = from_trf16(&trf([
+            ["   3 w 1", "    "],
+            ["   4 w =", "    "],
+            ["   2 b 0", "    "],
+            ["   2 b =", "    "],
+        ]));
+        assert!(matches!(error, Err(AppError::InvalidTrf { line: 5, .. })));
+        // Player 2 plays a start rank that does not exist
+        let error = from_trf16(&trf([
+            ["   3 w 1", "    "],
+            ["   5 w =", "    "],
+            ["   1 b 0", "    "],
+            ["0000 - Z", "    "],
+        ]));
+        assert!(matches!(error, Err(AppError::InvalidTrf { line: 6, .. })));
+        // A win against a win
+        let error = from_trf16(&trf([
+            ["   3 w 1", "    "],
+            ["   4 w =", "    "],
+            ["   1 b 1", "    "],
+            ["   2 b =", "    "],
+        ]));
+        assert!(matches!(error, Err(AppError::InvalidTrf { line: 5, .. })));
+        let error = from_trf16("001    1");
+        assert!(matches!(error, Err(AppError::InvalidTrf { line: 1, .. })));
+        let unpaired = [["    ", "    "]; 4];
+        // Before 1970, the start date does not fit a timestamp
+        let error = from_trf16(&trf(unpaired).replace("2024/03/01", "1969/12/31"));
+        assert!(matches!(error, Err(AppError::InvalidTrf { line: 3, .. })));
+        // Start ranks 1 and 2 with the same FIDE id
+        let duplicated = trf(unpaired)
+            .lines()
+            .map(|line| {
+                if line.starts_with("001    1") || line.starts_with("001    2") {
+                    line.replacen("          0 1990", "    1503014 1990", 1)
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let error = from_trf16(&duplicated);
+        assert!(matches!(error, Err(AppError::InvalidTrf { line: 6, .. })));
+    }
+}
@@ -1,2 +1,6 @@
+pub mod auth_service;
+pub mod export;
+pub mod import;
 pub mod player_service;
+pub mod rating_provider;
 pub mod tournament_service;
@@ -1,8 +1,14 @@
 use crate::{
+    auth::jwt::Claims,
     errors::AppError,
     models::tournament::Title,
-    repositories::player_repo::{self, DbPlayer, update_fide_player},
-    responses::FidePlayer,
+    payloads::Pagination,
+    repositories::{
+        pairing_repo,
+        player_repo::{self, DbPlayer, update_fide_player},
+    },
+    responses::{FidePlayer, HeadToHead, PlayerGame},
+    services::rating_provider::RatingProvider,
 };
 use chrono::{DateTime, Datelike, TimeDelta, Utc};
 use reqwest::Client;
@@ -20,6 +26,81 @@ fn split_name(full_name: String) -> (String, String) {
     }
 }
 
+pub async fn list_player_games(
+    pool: &sqlx::SqlitePool,
+    player_id: u32,
+    pagination: &Pagination,
+) -> Result<Vec<PlayerGame>, AppError> {
+    let games = pairing_repo::select_player_games(
+        pool,
+        player_id,
+        pagination.page_size(),
+        pagination.offset(),
+    )
+    .await?;
+    Ok(games.into_iter().map(PlayerGame::from).collect())
+}
+
+pub async fn head_to_head(
+    pool: &sqlx::SqlitePool,
+    player_id: u32,
+    opponent_id: u32,
+) -> Result<HeadToHead, AppError> {
+    let games = pairing_repo::head_to_head(pool, player_id, opponent_id).await?;
+    Ok(HeadToHead::new(player_id, opponent_id, games))
+}
+
+// Stored player only, never reaches out to FIDE
+pub async fn get_player_by_fide_id(
+    pool: &sqlx::SqlitePool,
+    fide_id: i64,
+) -> Result<DbPlayer, AppError> {
+    player_repo::get_player_by_fide_id(pool, fide_id)
+        .await?
+        .ok_or(AppError::FidePlayerNotFound(fide_id))
+}
+
+// Players created by hand that the FIDE player would duplicate if imported
+pub async fn possible_duplicates(
+    pool: &sqlx::SqlitePool,
+    player: &FidePlayer,
+) -> Result<Vec<DbPlayer>, AppError> {
+    let similar = player_repo::find_similar(
+        pool,
+        &player.first_name,
+        &player.last_name,
+        player.federation.as_deref(),
+    )
+    .await?;
+    Ok(similar)
+}
+
+// Admin only, `remove_id` is deleted once its registrations belong to `keep_id`
+pub async fn merge_players(
+    pool: &sqlx::SqlitePool,
+    claims: &Claims,
+    keep_id: u32,
+    remove_id: u32,
+) -> Result<u64, AppError> {
+    if claims.role != "admin" {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let moved = player_repo::merge_players(pool, keep_id, remove_id).await?;
+    Ok(moved)
+}
+
+// Stored FIDE data is refreshed once per calendar month, a timestamp that cannot be read is
+// refreshed as well
+fn needs_fide_refresh(updated_at: i64, now: DateTime<Utc>) -> bool {
+    match DateTime::from_timestamp_secs(updated_at) {
+        Some(last_update) => (now.year(), now.month()) != (last_update.year(), last_update.month()),
+        None => {
+            tracing::warn!("unreadable player update timestamp {}", updated_at);
+            true
+        }
+    }
+}
+
 pub enum FidePlayerCheck {
     Exists(u32),
     Updated(DbPlayer),
@@ -28,40 +109,16 @@ pub enum FidePlayerCheck {
 pub async fn check_fide_player_exists(
     pool: &sqlx::SqlitePool,
     fide_id: i64,
-    client: &Client,
+    provider: &dyn RatingProvider,
 ) -> Result<Option<FidePlayerCheck>, AppError> {
     match player_repo::get_player_by_fide_id(pool, fide_id)
         .await
         .map_err(|e| Into::<AppError>::into(e))?
     {
         Some(player) => {
-            let now = Utc::now();
-            let last_update = DateTime::from_timestamp_secs(player.updated_at as i64).unwrap();
-            tracing::info!(
-                "now - last_update of {:?} = {:?}",
-                player.fide_id,
-                now - last_update
-            );
-            let should_update = if now.year() == last_update.year() {
-                now.month() != last_update.month()
-            } else {
-                true
-            };
-            if should_update {
-                let updated_player = scrape_fide_player(client, fide_id).await?;
-                let updated_at = update_fide_player(pool, updated_player.into()).await?;
-                Ok(Some(FidePlayerCheck::Updated(DbPlayer {
-                    id: player.id,
-                    first_name: player.first_name,
-                    last_name: player.last_name,
-                    updated_at: updated_at as u32,
-                    federation: player.federation,
-                    fide_id: player.fide_id,
-                    title: player.title,
-                    rating: player.rating,
-                    rating_rapid: player.rating_rapid,
-                    rating_blitz: player.rating_blitz,
-                })))
+            if needs_fide_refresh(player.updated_at as i64, Utc::now()) {
+                let player = update_from_fide(pool, player.id, fide_id, provider).await?;
+                Ok(Some(FidePlayerCheck::Updated(player)))
             } else {
                 Ok(Some(FidePlayerCheck::Exists(player.id as u32)))
             }
@@ -70,8 +127,51 @@ pub async fn check_fide_player_exists(
     }
 }
 
-pub async fn scrape_fide_player(client: &Client, fide_id: i64) -> Result<FidePlayer, AppError> {
-    let url = format!("https://ratings.fide.com/profile/{}", fide_id);
+// Scrapes the player again and stores the result, returns what was just stored, not the
+// stale row
+async fn update_from_fide(
+    pool: &sqlx::SqlitePool,
+    id: i64,
+    fide_id: i64,
+    provider: &dyn RatingProvider,
+) -> Result<DbPlayer, AppError> {
+    let updated_player = provider.fetch(fide_id).await?;
+    let updated_at = update_fide_player(pool, updated_player.clone().into()).await?;
+    Ok(DbPlayer {
+        id,
+        first_name: updated_player.first_name,
+        last_name: updated_player.last_name,
+        updated_at: updated_at as u32,
+        federation: updated_player.federation,
+        fide_id: Some(fide_id),
+        title: updated_player.title,
+        rating: updated_player.rating,
+        rating_rapid: updated_player.rating_rapid,
+        rating_blitz: updated_player.rating_blitz,
+    })
+}
+
+// Unlike the FIDE lookup, ignores the monthly cache, for when a new rating list is out
+pub async fn refresh_fide_player(
+    pool: &sqlx::SqlitePool,
+    player_id: u32,
+    provider: &dyn RatingProvider,
+) -> Result<DbPlayer, AppError> {
+    let player = player_repo::get_player(pool, player_id)
+        .await?
+        .ok_or(AppError::PlayerNotFound(player_id as usize))?;
+    let fide_id = player
+        .fide_id
+        .ok_or(AppError::PlayerWithoutFideId(player_id))?;
+    update_from_fide(pool, player.id, fide_id, provider).await
+}
+
+pub async fn scrape_fide_player(
+    client: &Client,
+    profile_url: &str,
+    fide_id: i64,
+) -> Result<FidePlayer, AppError> {
+    let url = format!("{}/{}", profile_url, fide_id);
 
     let res = client
         .get(&url)
@@ -375,3 +475,221 @@ fn full_name_to_fide_code(full_name: &str) -> Option<String> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        auth::jwt::Claims, errors::AppError, models::tournament::TimeCategory, payloads::NewPlayer,
+        repositories::player_repo, responses::FidePlayer,
+        services::rating_provider::MockRatingProvider,
+    };
+
+    use super::{
+        FidePlayerCheck, check_fide_player_exists, get_player_by_fide_id, merge_players,
+        needs_fide_refresh, refresh_fide_player,
+    };
+    use chrono::DateTime;
+
+    #[sqlx::test(fixtures(path = "../../fixtures", scripts("create_players")))]
+    async fn test_fide_player_updated(pool: sqlx::SqlitePool) {
+        let provider = MockRatingProvider {
+            players: vec![FidePlayer {
+                fide_id: 1503014,
+                first_name: "Magnus".to_string(),
+                last_name: "Carlsen".to_string(),
+                federation: Some("NOR".to_string()),
+                title: Some("GM".to_string()),
+                rating: Some(2833),
+                rating_rapid: Some(2820),
+                rating_blitz: Some(2880),
+            }],
+        };
+        // The fixture ratings are from a previous month, so they are refreshed
+        match check_fide_player_exists(&pool, 1503014, &provider).await {
+            Ok(Some(FidePlayerCheck::Updated(player))) => {
+                assert_eq!(player.rating, Some(2833));
+                assert_eq!(player.rating_rapid, Some(2820));
+            }
+            _ => panic!("expected the player to be updated"),
+        }
+        // Checked again in the same month, the stored player is used
+        assert!(matches!(
+            check_fide_player_exists(&pool, 1503014, &provider).await,
+            Ok(Some(FidePlayerCheck::Exists(_)))
+        ));
+        // Unknown players are left to the caller
+        assert!(matches!(
+            check_fide_player_exists(&pool, 1, &provider).await,
+            Ok(None)
+        ));
+    }
+
+    #[test]
+    fn test_needs_fide_refresh() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+        let cases = [
+            ("2025-12-31T23:59:59Z", "2026-01-01T00:00:00Z", true),
+            ("2026-01-01T00:00:00Z", "2026-01-31T23:59:59Z", false),
+            ("2026-03-15T12:00:00Z", "2026-03-01T00:00:00Z", false),
+            ("2026-03-31T23:59:59Z", "2026-04-01T00:00:00Z", true),
+            // Same month of another year
+            ("2025-05-10T00:00:00Z", "2026-05-10T00:00:00Z", true),
+        ];
+        for (last_update, now, expected) in cases {
+            assert_eq!(
+                needs_fide_refresh(at(last_update).timestamp(), at(now)),
+                expected,
+                "{} -> {}",
+                last_update,
+                now
+            );
+        }
+        // Out of the range chrono can represent
+        assert!(needs_fide_refresh(i64::MAX, at("2026-01-01T00:00:00Z")));
+    }
+
+    #[sqlx::test(fixtures(path = "../../fixtures", scripts("create_players")))]
+    async fn test_refresh_fide_player(pool: sqlx::SqlitePool) {
+        let provider = |rating| MockRatingProvider {
+            players: vec![FidePlayer {
+                fide_id: 1503014,
+                first_name: "Magnus".to_string(),
+                last_name: "Carlsen".to_string(),
+                federation: Some("NOR".to_string()),
+                title: Some("GM".to_string()),
+                rating: Some(rating),
+                rating_rapid: None,
+                rating_blitz: None,
+            }],
+        };
+        let player = refresh_fide_player(&pool, 1, &provider(2833))
+            .await
+            .expect("failed to refresh player");
+        assert_eq!(player.rating, Some(2833));
+        // Refreshed again within the same month, the monthly cache is ignored
+        refresh_fide_player(&pool, 1, &provider(2837))
+            .await
+            .expect("failed to refresh player");
+        let stored = get_player_by_fide_id(&pool, 1503014).await.unwrap();
+        assert_eq!(stored.rating, Some(2837));
+        assert!(matches!(
+            refresh_fide_player(&pool, 9999, &provider(2837)).await,
+            Err(AppError::PlayerNotFound(9999))
+        ));
+        let manual = NewPlayer {
+            first_name: "Club".to_string(),
+            last_name: "Player".to_string(),
+            federation: None,
+            fide_id: None,
+            title: None,
+            rating: None,
+            rating_rapid: None,
+            rating_blitz: None,
+        };
+        let id = player_repo::create_player(&pool, manual).await.unwrap() as u32;
+        assert!(matches!(
+            refresh_fide_player(&pool, id, &provider(2837)).await,
+            Err(AppError::PlayerWithoutFideId(player_id)) if player_id == id
+        ));
+    }
+
+    #[sqlx::test(fixtures(path = "../../fixtures", scripts("create_players")))]
+    async fn test_get_stored_fide_player(pool: sqlx::SqlitePool) {
+        // The stale fixture ratings are returned as stored
+        let player = get_player_by_fide_id(&pool, 1503014)
+            .await
+            .expect("failed to get player");
+        assert_eq!(player.last_name, "Carlsen");
+        assert!(matches!(
+            get_player_by_fide_id(&pool, 1).await,
+            Err(AppError::FidePlayerNotFound(1))
+        ));
+    }
+
+    #[sqlx::test(fixtures(
+        path = "../../fixtures",
+        scripts(
+            "create_players",
+            "create_user",
+            "create_tournament",
+            "register_players"
+        )
+    ))]
+    async fn test_merge_players(pool: sqlx::SqlitePool) {
+        let claims = |role: &str| Claims {
+            sub: 1,
+            username: "user".to_string(),
+            role: role.to_string(),
+            exp: 0,
+        };
+        assert!(matches!(
+            merge_players(&pool, &claims("standard"), 1, 2).await,
+            Err(AppError::InsufficientPermissions)
+        ));
+        assert!(matches!(
+            merge_players(&pool, &claims("admin"), 1, 9999).await,
+            Err(AppError::PlayerNotFound(9999))
+        ));
+        assert!(matches!(
+            merge_players(&pool, &claims("admin"), 1, 1).await,
+            Err(AppError::MergeSamePlayer(1))
+        ));
+        // Both are registered in tournament 1
+        assert!(matches!(
+            merge_players(&pool, &claims("admin"), 1, 2).await,
+            Err(AppError::MergeRegistrationConflict(1))
+        ));
+        // A player created by hand, without FIDE id nor rating, duplicating player 2
+        let manual = NewPlayer {
+            first_name: "Hikaru".to_string(),
+            last_name: "Nakamura".to_string(),
+            federation: None,
+            fide_id: None,
+            title: None,
+            rating: None,
+            rating_rapid: None,
+            rating_blitz: None,
+        };
+        let keep_id = player_repo::create_player(&pool, manual).await.unwrap() as u32;
+        let moved = merge_players(&pool, &claims("admin"), keep_id, 2)
+            .await
+            .expect("failed to merge players");
+        assert_eq!(moved, 1);
+        let registered: u32 =
+            sqlx::query_scalar("select count(*) from registrations where player_id = ?1")
+                .bind(keep_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(registered, 1);
+        assert!(player_repo::get_player(&pool, 2).await.unwrap().is_none());
+        let kept = player_repo::get_player(&pool, keep_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(kept.fide_id, Some(2016192));
+        assert_eq!(kept.rating, Some(2810));
+    }
+
+    #[test]
+    fn test_fide_rating_for_category() {
+        let player = FidePlayer {
+            fide_id: 1503014,
+            first_name: "Magnus".to_string(),
+            last_name: "Carlsen".to_string(),
+            federation: Some("NOR".to_string()),
+            title: Some("GM".to_string()),
+            rating: Some(2833),
+            rating_rapid: Some(2820),
+            rating_blitz: None,
+        };
+        assert_eq!(player.rating_for(None), Some(2833));
+        assert_eq!(player.rating_for(Some(TimeCategory::Rapid)), Some(2820));
+        // Without a blitz rating the rapid one is the closest estimate
+        assert_eq!(player.rating_for(Some(TimeCategory::Blitz)), Some(2820));
+        let new_player = player.into_new_player(Some(TimeCategory::Rapid));
+        assert_eq!(new_player.rating, Some(2820));
+        assert_eq!(new_player.rating_rapid, Some(2820));
+        assert_eq!(new_player.fide_id, Some(1503014));
+    }
+}
@@ -0,0 +1,109 @@
+use std::env;
+
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::{errors::AppError, responses::FidePlayer, services::player_service};
+
+// Source of player ratings, swapped for a mock in tests so nothing hits the network
+#[async_trait]
+pub trait RatingProvider: Send + Sync {
+    async fn fetch(&self, fide_id: i64) -> Result<FidePlayer, AppError>;
+}
+
+const DEFAULT_PROFILE_URL: &str = "https://ratings.fide.com/profile";
+
+// Scrapes the public FIDE profile page, found at `{profile_url}/{fide_id}`
+pub struct FideScraper {
+    client: Client,
+    profile_url: String,
+}
+
+impl FideScraper {
+    pub fn new(client: Client, profile_url: String) -> Self {
+        Self {
+            client,
+            profile_url: profile_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    // FIDE_PROFILE_URL points the scraper at a mirror or a local server, the FIDE ratings
+    // website by default
+    pub fn from_env(client: Client) -> Self {
+        let profile_url =
+            env::var("FIDE_PROFILE_URL").unwrap_or_else(|_| DEFAULT_PROFILE_URL.to_string());
+        Self::new(client, profile_url)
+    }
+}
+
+#[async_trait]
+impl RatingProvider for FideScraper {
+    async fn fetch(&self, fide_id: i64) -> Result<FidePlayer, AppError> {
+        player_service::scrape_fide_player(&self.client, &self.profile_url, fide_id).await
+    }
+}
+
+// Knows a fixed list of players, every other id is reported as not found
+#[cfg(test)]
+pub struct MockRatingProvider {
+    pub players: Vec<FidePlayer>,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl RatingProvider for MockRatingProvider {
+    async fn fetch(&self, fide_id: i64) -> Result<FidePlayer, AppError> {
+        self.players
+            .iter()
+            .find(|p| p.fide_id == fide_id)
+            .cloned()
+            .ok_or(AppError::FideScrapeFailed("Player not found".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        extract::Path,
+        http::StatusCode,
+        response::{Html, IntoResponse},
+        routing::get,
+    };
+    use reqwest::Client;
+    use tokio::net::TcpListener;
+
+    use super::{FideScraper, RatingProvider};
+
+    #[tokio::test]
+    async fn test_fide_scraper_profile_url() {
+        let app = Router::new().route(
+            "/profile/{fide_id}",
+            get(|Path(fide_id): Path<i64>| async move {
+                match fide_id {
+                    1503014 => {
+                        Html(include_str!("../../fixtures/html/fide_profile.html")).into_response()
+                    }
+                    _ => StatusCode::NOT_FOUND.into_response(),
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = Client::builder().no_proxy().build().unwrap();
+        let scraper = FideScraper::new(client, format!("http://{}/profile/", addr));
+        let player = scraper
+            .fetch(1503014)
+            .await
+            .expect("failed to scrape player");
+        assert_eq!(player.first_name, "Magnus");
+        assert_eq!(player.last_name, "Carlsen");
+        assert_eq!(player.federation.as_deref(), Some("NOR"));
+        assert_eq!(player.title.as_deref(), Some("GM"));
+        assert_eq!(player.rating, Some(2833));
+        assert_eq!(player.rating_rapid, Some(2820));
+        assert_eq!(player.rating_blitz, None);
+        assert!(scraper.fetch(1).await.is_err());
+    }
+}
@@ -1,8 +1,10 @@
 use std::{
-    collections::HashMap,
+    cmp::{Ordering, Reverse},
+    collections::{HashMap, HashSet},
     ops::{Deref, DerefMut},
 };
 
+use chrono::Utc;
 use itertools::Itertools;
 use rustworkx_core::{
     max_weight_matching::max_weight_matching,
@@ -13,49 +15,159 @@ use crate::{
     auth::jwt::Claims,
     errors::AppError,
     models::tournament::{
-        Color, GameResult, HistoryItem, NewPairings, Player, PlayerResult, PlayerStanding,
-        PlayerStatus, Title, Tournament, TournamentDbData,
+        BYE_POINTS, Color, ColorCandidate, ColorDecision, ColorRule, GameResult, HistoryItem,
+        MAX_PLAYERS, NewPairings, Player, PlayerName, PlayerResult, PlayerStanding, PlayerStatus,
+        Tiebreak, TimeCategory, Title, Tournament, TournamentDbData, TournamentRole, Visibility,
+        allocate_colors, color_balance, history_item_points, is_valid_time_control, seeding_order,
+    },
+    payloads::{
+        NewOrganizer, NewRegistration, NewSection, NewTournament, NextPairings,
+        PlayerStatusPayload, PlayoffResult, RoundResult, SimulatedResults, TimeControlPayload,
+        VisibilityPayload,
     },
-    payloads::{NewRegistration, NewTournament, NextPairings, PlayerStatusPayload, RoundResult},
     repositories::{
+        audit_repo::{self, DbAuditEntry},
+        auth_repo,
+        organizer_repo::{self, select_organizers},
         pairing_repo::{
-            NewDbPairing, NewDbPairingGap, select_pairing_gaps, select_pairings, update_game_result,
+            self, DbAgreedDraws, DbPairing, NewDbPairing, NewDbPairingGap, select_pairing_gaps,
+            select_pairings, update_game_result,
+        },
+        player_repo,
+        playoff_repo::{self, DbPlayoffGame, select_playoff_games},
+        registration_repo::{self, DbRegistration, select_registrations},
+        section_repo::{self, select_sections},
+        tournament_repo::{
+            self, DbTournament, DbTournamentSummary, check_user_is_organizer,
+            check_user_tournament_ownership, check_user_tournament_permissions, get_tournament,
         },
-        registration_repo::{self, select_registrations},
-        tournament_repo::{self, DbTournament, check_user_tournament_permissions, get_tournament},
     },
-    responses::AppResponse,
+    responses::{
+        CurrentRound, EdgeWeight, OrganizerData, PairingConstraints, PairingGraph,
+        PairingGraphEdge, PendingBoards, PlayerConnectivity, RegisteredPlayer, TournamentStats,
+    },
+    services::{export, import},
 };
 
-enum TimeCategory {
-    Blitz,
-    Rapid,
-    Standard,
-}
-
-impl TryFrom<&String> for TimeCategory {
-    type Error = AppError;
+// 2000-01-01, anything earlier is most likely a date sent in days or left at 0
+const MIN_START_DATE: u32 = 946_684_800;
 
-    fn try_from(value: &String) -> Result<Self, Self::Error> {
-        match value.trim().to_lowercase().as_str() {
-            "blitz" => Ok(Self::Blitz),
-            "rapid" => Ok(Self::Rapid),
-            "standard" => Ok(Self::Standard),
-            _ => Err(AppError::InvalidTimeCategory(value.to_string())),
-        }
+// Rounds a field can be paired for without forcing rematches, log2 of the number of players
+// rounded up
+fn max_recommended_rounds(players: u32) -> u32 {
+    match players {
+        0 | 1 => 0,
+        n => u32::BITS - (n - 1).leading_zeros(),
     }
 }
 
 pub async fn create_tournament(
     pool: &sqlx::Pool<sqlx::Sqlite>,
-    user_id: u32,
-    payload: NewTournament,
-) -> Result<i64, AppError> {
+    claims: &Claims,
+    mut payload: NewTournament,
+) -> Result<(i64, Vec<String>), AppError> {
+    if claims.role != "admin" && !auth_repo::is_email_verified(pool, claims.sub).await? {
+        return Err(AppError::EmailNotVerified);
+    }
     TimeCategory::try_from(&payload.time_category)?;
+    payload.time_control = validate_time_control(payload.time_control.take())?;
     if payload.rounds < 2 || payload.rounds > 30 {
         return Err(AppError::InvalidNumberOfRounds(payload.rounds));
     }
-    let id = tournament_repo::create_tournament(pool, user_id, payload).await?;
+    if payload.start_date < MIN_START_DATE {
+        return Err(AppError::InvalidStartDate(payload.start_date));
+    }
+    if let Some(score) = payload.late_entry_default_score
+        && score > 2
+    {
+        return Err(AppError::InvalidLateEntryScore(score));
+    }
+    if let Some(score) = payload.requested_bye_score
+        && score > 2
+    {
+        return Err(AppError::InvalidByeScore(score));
+    }
+    if let Some(games) = payload.games_per_pairing
+        && !(1..=10).contains(&games)
+    {
+        return Err(AppError::InvalidGamesPerPairing(games));
+    }
+    if let Some(max_players) = payload.max_players
+        && !(2..=MAX_PLAYERS).contains(&max_players)
+    {
+        return Err(AppError::InvalidMaxPlayers(max_players));
+    }
+    if let Some(visibility) = payload.visibility.as_deref() {
+        payload.visibility = Some(Visibility::try_from(visibility)?.to_string());
+    }
+    if let Some(tiebreaks) = payload.tiebreaks.as_deref() {
+        let tiebreaks: Vec<Tiebreak> = tiebreaks
+            .iter()
+            .map(|t| Tiebreak::try_from(t.as_str()))
+            .collect::<Result<_, _>>()?;
+        if tiebreaks.is_empty() || !tiebreaks.iter().all_unique() {
+            return Err(AppError::InvalidTiebreakOrder);
+        }
+        payload.tiebreaks = Some(tiebreaks.iter().map(Tiebreak::to_string).collect());
+    }
+    // Non-fatal, the field size is only known once the registrations are in
+    let mut warnings = Vec::new();
+    let expected_players = payload.max_players.unwrap_or(MAX_PLAYERS);
+    if payload.rounds > max_recommended_rounds(expected_players) {
+        warnings.push(format!(
+            "{} rounds are too many for {} players, repeat pairings may be needed. At most {} rounds are recommended",
+            payload.rounds,
+            expected_players,
+            max_recommended_rounds(expected_players)
+        ));
+    }
+    let id = tournament_repo::create_tournament(pool, claims.sub, payload).await?;
+    Ok((id, warnings))
+}
+
+pub async fn import_trf(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    claims: &Claims,
+    input: &str,
+) -> Result<i64, AppError> {
+    if claims.role != "admin" && !auth_repo::is_email_verified(pool, claims.sub).await? {
+        return Err(AppError::EmailNotVerified);
+    }
+    let imported = import::from_trf16(input)?;
+    let rounds = imported.tournament.rounds;
+    if !(2..=30).contains(&rounds) || rounds < imported.current_round {
+        return Err(AppError::InvalidNumberOfRounds(rounds));
+    }
+    if imported.tournament.start_date < MIN_START_DATE {
+        return Err(AppError::InvalidStartDate(imported.tournament.start_date));
+    }
+    if imported.players.len() as u32 > MAX_PLAYERS {
+        return Err(AppError::TournamentFull(MAX_PLAYERS));
+    }
+    let id = tournament_repo::import_tournament(pool, claims.sub, imported).await?;
+    Ok(id)
+}
+
+// Any organizer of the source tournament can reuse its settings and roster, the copy belongs to
+// the requester alone
+pub async fn clone_tournament(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: &Claims,
+    registrations: bool,
+) -> Result<i64, AppError> {
+    if !check_user_is_organizer(pool, tournament_id, claims).await? {
+        return Err(AppError::InsufficientPermissions);
+    }
+    // The roster is the owner's to copy, arbiters and viewers only get the settings
+    if registrations && !tournament_repo::check_user_is_owner(pool, tournament_id, claims).await? {
+        return Err(AppError::InsufficientPermissions);
+    }
+    if claims.role != "admin" && !auth_repo::is_email_verified(pool, claims.sub).await? {
+        return Err(AppError::EmailNotVerified);
+    }
+    let id =
+        tournament_repo::clone_tournament(pool, claims.sub, tournament_id, registrations).await?;
     Ok(id)
 }
 
@@ -63,34 +175,96 @@ pub async fn register_player(
     pool: &sqlx::Pool<sqlx::Sqlite>,
     tournament_id: u32,
     claims: Claims,
-    payload: NewRegistration,
+    mut payload: NewRegistration,
 ) -> Result<i64, AppError> {
-    let has_permission = check_user_tournament_permissions(pool, tournament_id, claims).await?;
-    if !has_permission {
+    let user_id = claims.sub;
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    let tournament = get_tournament(pool, tournament_id).await?;
+    // Players registering themselves can only register their own player, with its stored
+    // rating, and cannot set their status, section or results of missed rounds
+    let self_registered = if role == Some(TournamentRole::Owner) {
+        None
+    } else if tournament.end_date.is_some() {
         return Err(AppError::InsufficientPermissions);
+    } else if !tournament.self_registration {
+        return Err(AppError::SelfRegistrationDisabled);
+    } else if registration_repo::is_user_registered(pool, tournament_id, user_id).await? {
+        return Err(AppError::AlreadyRegistered);
+    } else {
+        let player_id = u32::try_from(payload.player_id)
+            .map_err(|_| AppError::PlayerNotFound(payload.player_id as usize))?;
+        let player = player_repo::get_player(pool, player_id)
+            .await?
+            .ok_or(AppError::PlayerNotFound(player_id as usize))?;
+        let owner = player_repo::get_player_user(pool, player_id).await?;
+        let own_player = player_repo::get_user_player(pool, user_id).await?;
+        if owner.is_some_and(|owner| owner != user_id)
+            || own_player.is_some_and(|own| own != player_id)
+        {
+            return Err(AppError::NotOwnPlayer(player_id));
+        }
+        payload.rating = match TimeCategory::try_from(&tournament.time_category)? {
+            TimeCategory::Standard => player.rating,
+            TimeCategory::Rapid => player.rating_rapid.or(player.rating),
+            TimeCategory::Blitz => player
+                .rating_blitz
+                .or(player.rating_rapid)
+                .or(player.rating),
+        }
+        .unwrap_or_default();
+        payload.status = PlayerStatus::Active.to_string();
+        payload.absent_results.clear();
+        payload.section_id = None;
+        Some(user_id)
+    };
+    if !tournament.registration_open {
+        return Err(AppError::RegistrationClosed);
     }
-    registration_repo::create_tournament_registration(pool, tournament_id, payload)
-        .await
-        .map_err(|e| Into::<AppError>::into(e))
+    for (field, len) in [
+        ("absentResults", payload.absent_results.len()),
+        ("requestedByeRounds", payload.requested_bye_rounds.len()),
+    ] {
+        if len > tournament.num_rounds as usize {
+            return Err(AppError::TooManyItems {
+                field: field.to_string(),
+                max: tournament.num_rounds as usize,
+            });
+        }
+    }
+    // Byes can only be requested for rounds that have not been paired yet
+    if let Some(round) = payload
+        .requested_bye_rounds
+        .iter()
+        .find(|r| **r < tournament.current_round || **r >= tournament.num_rounds)
+    {
+        return Err(AppError::InvalidByeRequest(*round));
+    }
+    if let Some(section_id) = payload.section_id {
+        let sections = select_sections(pool, tournament_id).await?;
+        if !sections.iter().any(|s| s.id == section_id) {
+            return Err(AppError::SectionNotFound(section_id));
+        }
+    }
+    let max_players = tournament
+        .max_players
+        .unwrap_or(MAX_PLAYERS)
+        .min(MAX_PLAYERS);
+    registration_repo::create_tournament_registration(
+        pool,
+        tournament_id,
+        payload,
+        self_registered,
+        max_players,
+    )
+    .await
 }
 
 impl Player {
     fn tournament_score(&self) -> u32 {
-        self.history.iter().fold(0, |acc, item| match item {
-            HistoryItem::NotPaired { score } => acc + *score,
-            HistoryItem::Bye => acc + 2,
-            HistoryItem::Game {
-                opponent_id: _,
-                color,
-                result,
-            } => match (color, result) {
-                (Color::White, GameResult::WhiteWins) => acc + 2,
-                (Color::White, GameResult::Draw) => acc + 1,
-                (Color::Black, GameResult::Draw) => acc + 1,
-                (Color::Black, GameResult::BlackWins) => acc + 2,
-                _ => acc,
-            },
-        })
+        self.score_before(self.history.len())
+    }
+    fn requested_bye(&self, round: usize) -> bool {
+        self.requested_bye_rounds.contains(&(round as u32))
     }
     fn byes(&self) -> usize {
         self.history
@@ -100,6 +274,26 @@ impl Player {
     }
 }
 
+// Without any history, the games are added from the pairings
+impl From<DbRegistration> for Player {
+    fn from(p: DbRegistration) -> Self {
+        Self {
+            id: p.id,
+            db_id: p.player_id,
+            name: PlayerName::new(&p.first_name, &p.last_name),
+            rating: p.rating,
+            title: Title::from_str(p.title),
+            history: Vec::new(),
+            floats: 0,
+            fide_id: p.fide_id.map(|id| id as usize),
+            federation: p.federation,
+            status: PlayerStatus::from_str(p.status),
+            section_id: p.section_id,
+            requested_bye_rounds: Vec::new(),
+        }
+    }
+}
+
 impl From<TournamentDbData> for Tournament {
     fn from(value: TournamentDbData) -> Self {
         let mut players: HashMap<u32, Player> = value
@@ -109,18 +303,10 @@ impl From<TournamentDbData> for Tournament {
                 (
                     p.id,
                     Player {
-                        id: p.id,
-                        db_id: p.player_id,
-                        name: format!("{}, {}", p.last_name, p.first_name),
-                        rating: p.rating,
-                        title: Title::from_str(p.title),
                         history: (0..value.tournament.current_round)
                             .map(|_| HistoryItem::NotPaired { score: 0 })
                             .collect(),
-                        floats: p.floats as usize,
-                        fide_id: p.fide_id.map(|id| id as usize),
-                        federation: p.federation,
-                        status: PlayerStatus::from_str(p.status),
+                        ..Player::from(p)
                     },
                 )
             })
@@ -131,6 +317,11 @@ impl From<TournamentDbData> for Tournament {
         let mut byes: Vec<Vec<u32>> = (0..value.tournament.current_round)
             .map(|_| Vec::new())
             .collect();
+        for request in value.bye_requests {
+            if let Some(player) = players.get_mut(&request.registration_id) {
+                player.requested_bye_rounds.push(request.round_number);
+            }
+        }
         for gap in value.pairing_gaps.iter() {
             let history_item = match gap.is_bye {
                 true => {
@@ -177,6 +368,13 @@ impl From<TournamentDbData> for Tournament {
         for round_pairing in round_pairings.iter_mut() {
             round_pairing.sort_by(|a, b| a.0.cmp(&b.0));
         }
+        // Floats are not stored, they follow from the scores before every paired round
+        for (round, boards) in round_pairings.iter().enumerate() {
+            let pairs: Vec<(usize, usize)> = boards.iter().map(|(_, w, b)| (*w, *b)).collect();
+            for id in round_floats(&pairs, |id| players[&id].score_before(round)) {
+                players.get_mut(&id).unwrap().floats += 1;
+            }
+        }
         for result in results.iter_mut() {
             result.sort_by(|a, b| a.0.cmp(&b.0));
         }
@@ -201,16 +399,198 @@ impl From<TournamentDbData> for Tournament {
                 .map(|round| round.into_iter().map(|(_, res)| res).collect())
                 .collect(),
             federation: value.tournament.federation,
-            start_date: value.tournament.start_date as usize,
+            start_date: value.tournament.start_date,
             end_date: value.tournament.end_date,
             url: value.tournament.url,
             user_id: value.tournament.user_id,
             username: value.tournament.username,
+            organizers: value.organizers,
+            sections: value.sections,
+            playoff: value.playoff,
+            max_float_gap: value.tournament.max_float_gap,
+            registration_open: value.tournament.registration_open,
+            late_entry_default_score: value.tournament.late_entry_default_score,
+            unrated_default_rating: value.tournament.unrated_default_rating,
+            board_offset: value.tournament.board_offset,
+            allow_repeat_pairings: value.tournament.allow_repeat_pairings,
+            visibility: Visibility::try_from(value.tournament.visibility.as_str())
+                .unwrap_or_default(),
+            requested_bye_score: value.tournament.requested_bye_score,
+            games_per_pairing: value.tournament.games_per_pairing,
+            tiebreaks: value
+                .tournament
+                .tiebreaks
+                .split(',')
+                .filter_map(|t| Tiebreak::try_from(t).ok())
+                .collect(),
+            max_players: value.tournament.max_players,
+            broadcast_boards: value.tournament.broadcast_boards,
+            self_registration: value.tournament.self_registration,
+            allow_three_same_colors: value.tournament.allow_three_same_colors,
+            time_control: value.tournament.time_control,
+            avoid_recent_opponents: value.tournament.avoid_recent_opponents,
+            allow_rematch_reversed_colors: value.tournament.allow_rematch_reversed_colors,
+            avoid_last_round_contender_bye: value.tournament.avoid_last_round_contender_bye,
+            recent_meetings: value
+                .recent_meetings
+                .into_iter()
+                .map(|m| ((m.player_a, m.player_b), m.games))
+                .collect(),
             updated_at: value.tournament.updated_at,
         }
     }
 }
 
+// Once play has started players must be set inactive instead
+pub async fn unregister_player(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    registration_id: u32,
+) -> Result<(), AppError> {
+    let user_id = claims.sub;
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if role != Some(TournamentRole::Owner) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let tournament = get_tournament(pool, tournament_id).await?;
+    if tournament.current_round > 0 {
+        return Err(AppError::CannotUnregisterAfterStart);
+    }
+    let deleted =
+        registration_repo::delete_registration(pool, user_id, tournament_id, registration_id)
+            .await?;
+    if deleted == 0 {
+        return Err(AppError::RegistrationNotFound(registration_id));
+    }
+    Ok(())
+}
+
+// Registration can be closed at any time but only reopened before the first round is paired
+pub async fn set_registration_open(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    registration_open: bool,
+) -> Result<(), AppError> {
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if role != Some(TournamentRole::Owner) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let tournament = get_tournament(pool, tournament_id).await?;
+    if registration_open && tournament.current_round > 0 {
+        return Err(AppError::CannotReopenRegistration);
+    }
+    tournament_repo::set_registration_open(pool, tournament_id, registration_open).await?;
+    Ok(())
+}
+
+pub async fn set_visibility(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    payload: &VisibilityPayload,
+) -> Result<Visibility, AppError> {
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if role != Some(TournamentRole::Owner) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let visibility = Visibility::try_from(payload.visibility.as_str())?;
+    tournament_repo::set_visibility(pool, tournament_id, visibility).await?;
+    Ok(visibility)
+}
+
+// Trimmed, None when empty
+fn validate_time_control(time_control: Option<String>) -> Result<Option<String>, AppError> {
+    match time_control.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) if is_valid_time_control(value) => Ok(Some(value.to_string())),
+        Some(value) => Err(AppError::InvalidTimeControl(value.to_string())),
+    }
+}
+
+pub async fn set_time_control(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    payload: TimeControlPayload,
+) -> Result<Option<String>, AppError> {
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if role != Some(TournamentRole::Owner) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let time_control = validate_time_control(payload.time_control)?;
+    tournament_repo::set_time_control(pool, tournament_id, time_control.as_deref()).await?;
+    Ok(time_control)
+}
+
+pub async fn create_section(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    payload: NewSection,
+) -> Result<i64, AppError> {
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if role != Some(TournamentRole::Owner) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let id = section_repo::create_section(pool, tournament_id, &payload.name).await?;
+    Ok(id)
+}
+
+pub async fn create_playoff_round(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+) -> Result<(u32, Vec<(u32, u32)>), AppError> {
+    let user_id = claims.sub;
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if role != Some(TournamentRole::Owner) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let tournament: Tournament = read_tournament(pool, tournament_id).await?.into();
+    let (round, games) = tournament.next_playoff_round()?;
+    playoff_repo::insert_playoff_round(pool, user_id, tournament_id, round, &games).await?;
+    Ok((round, games))
+}
+
+pub async fn update_playoff_result(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    payload: &PlayoffResult,
+) -> Result<(), AppError> {
+    let user_id = claims.sub;
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if !role.is_some_and(|role| role.can_enter_results()) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let result = GameResult::from_str(&payload.result);
+    if result == GameResult::Ongoing {
+        return Err(AppError::InvalidResult(payload.result.clone()));
+    }
+    let games = select_playoff_games(pool, tournament_id).await?;
+    let game = games
+        .iter()
+        .find(|g| g.id == payload.game_id)
+        .ok_or(AppError::PlayoffGameNotFound(payload.game_id))?;
+    // Earlier rounds already decided who plays the later ones
+    let last_round = games.iter().map(|g| g.round_number).max().unwrap_or(0);
+    if game.round_number < last_round {
+        return Err(AppError::InvalidRound(game.round_number as usize));
+    }
+    let updated =
+        playoff_repo::update_playoff_result(pool, user_id, tournament_id, payload.game_id, result)
+            .await?;
+    if updated == 0 {
+        return Err(AppError::PlayoffGameNotFound(payload.game_id));
+    }
+    Ok(())
+}
+
+// Organizer's tournaments looked back at with `avoid_recent_opponents`
+const RECENT_TOURNAMENTS: u32 = 10;
+
 pub async fn read_tournament(
     pool: &sqlx::Pool<sqlx::Sqlite>,
     id: u32,
@@ -223,50 +603,169 @@ pub async fn read_tournament(
     let registrations = select_registrations(pool, id).await?;
     let pairings = select_pairings(pool, id).await?;
     let gaps = select_pairing_gaps(pool, id).await?;
+    let organizers = select_organizers(pool, id).await?;
+    let sections = select_sections(pool, id).await?;
+    let playoff = select_playoff_games(pool, id).await?;
+    let bye_requests = registration_repo::select_bye_requests(pool, id).await?;
+    // Only the pairing engine needs them
+    let recent_meetings = if tournament.avoid_recent_opponents {
+        pairing_repo::select_recent_meetings(pool, id, RECENT_TOURNAMENTS).await?
+    } else {
+        Vec::new()
+    };
     let tournament_data = TournamentDbData {
         tournament,
         players: registrations,
         pairings,
         pairing_gaps: gaps,
+        organizers,
+        sections,
+        playoff,
+        bye_requests,
+        recent_meetings,
     };
     Ok(tournament_data)
 }
 
 pub async fn list_tournaments(
     pool: &sqlx::Pool<sqlx::Sqlite>,
-) -> Result<Vec<DbTournament>, AppError> {
+) -> Result<Vec<DbTournamentSummary>, AppError> {
     tournament_repo::list_tournaments(pool)
         .await
         .map_err(|e| Into::<AppError>::into(e))
 }
 
+// Subtracted from the weight of a rematch, large enough that any legal pairing is preferred
+const REPEAT_PAIRING_PENALTY: isize = 100_000;
+
+// Subtracted from the weight of a pair giving one player the same color a third time in a
+// row. Lower than the rematch penalty: an unbalanced color history is the lesser evil
+const COLOR_STREAK_PENALTY: isize = 50_000;
+
+// Subtracted from the weight of a rematch with reversed colors, with
+// `allow_rematch_reversed_colors`. Still well above any score difference so new opponents
+// come first, but far below an ordinary rematch
+const REVERSED_REMATCH_PENALTY: isize = 10_000;
+
+// Subtracted for every game the two players played in the organizer's recent tournaments,
+// with `avoid_recent_opponents`. Mild, about a natural float for two earlier games
+const RECENT_OPPONENT_PENALTY: isize = 40;
+
+// Score gaps are measured in half points, like every score in the api
+fn exceeds_float_gap(p1: &Player, p2: &Player, max_float_gap: Option<u32>) -> bool {
+    match max_float_gap {
+        Some(max_gap) => p1.tournament_score().abs_diff(p2.tournament_score()) > max_gap,
+        None => false,
+    }
+}
+
+// Both players had the same color in each of their last two games, one of them would get
+// that color a third time in a row
+fn repeats_color_three_times(p1: &Player, p2: &Player) -> bool {
+    match (
+        p1.color_history().last_chunk::<2>(),
+        p2.color_history().last_chunk::<2>(),
+    ) {
+        (Some(p1_last_2), Some(p2_last_2)) => {
+            p1_last_2[0] == p1_last_2[1] && p1_last_2 == p2_last_2
+        }
+        _ => false,
+    }
+}
+
+// Colors for a pair of the next round as (white, black), `tpn_cmp` compares the starting
+// rank of p1 to the one of p2. See `allocate_colors` for the rules
+fn assign_colors(p1: &Player, p2: &Player, tpn_cmp: Ordering) -> (u32, u32) {
+    let (p1_colors, p2_colors) = (p1.color_history(), p2.color_history());
+    let (white, black, _) = allocate_colors(
+        ColorCandidate {
+            id: p1.id,
+            colors: &p1_colors,
+        },
+        ColorCandidate {
+            id: p2.id,
+            colors: &p2_colors,
+        },
+        tpn_cmp,
+    );
+    (white, black)
+}
+
+// Bye of a group paired together, None when it has an even number of active players. The bye
+// goes to the lowest standing player who has not had a bye yet (FIDE C.04.1 c): lowest score
+// first, then lowest seed (highest `tpn`). Players who already had a bye are only considered
+// when every candidate had one, then the fewest byes wins with the same order.
+// `last_round` applies the last round rule of `avoid_last_round_contender_bye`, a house rule
+// that is not part of the FIDE regulations: players who can still tie for first (at most one
+// point, 2 half points, behind the leading score of `players`) are left out, even when that
+// gives a player out of contention a second bye. The usual order then applies among the
+// others, when everybody is in contention it applies to all of them
+fn select_bye(
+    players: &HashMap<u32, &Player>,
+    tpn: impl Fn(u32) -> usize,
+    last_round: bool,
+) -> Option<u32> {
+    let mut active: Vec<&Player> = players
+        .values()
+        .filter(|p| p.status == PlayerStatus::Active)
+        .copied()
+        .collect();
+    if active.len().is_multiple_of(2) {
+        return None;
+    }
+    if last_round {
+        let leader = active.iter().map(|p| p.tournament_score()).max()?;
+        let out_of_contention: Vec<&Player> = active
+            .iter()
+            .filter(|p| p.tournament_score() + 2 < leader)
+            .copied()
+            .collect();
+        if !out_of_contention.is_empty() {
+            active = out_of_contention;
+        }
+    }
+    let fewest_byes = active.iter().map(|p| p.byes()).min()?;
+    active
+        .iter()
+        .filter(|p| p.byes() == fewest_byes)
+        .min_by(|a, b| {
+            a.tournament_score()
+                .cmp(&b.tournament_score())
+                .then_with(|| tpn(b.id).cmp(&tpn(a.id)))
+        })
+        .map(|p| p.id)
+}
+
+// Candidates left out of the matching, sorted by id
+fn unpaired_players(candidates: &[u32], pairings: &[(usize, usize)]) -> Vec<u32> {
+    candidates
+        .iter()
+        .filter(|id| {
+            !pairings
+                .iter()
+                .any(|(white, black)| *white as u32 == **id || *black as u32 == **id)
+        })
+        .copied()
+        .collect()
+}
+
+// Weight of the edge between two players in the matching graph, the higher the better. None
+// when they cannot be paired at all
 fn edge_weight(
     p1: &Player,
     p2: &Player,
     group_ranks: (usize, usize),
     group_len: (usize, usize),
     min_score: u32,
-) -> isize {
+    max_float_gap: Option<u32>,
+    recent_meetings: u32,
+) -> Option<EdgeWeight> {
+    // Players cannot float across more than the configured score gap
+    if exceeds_float_gap(p1, p2, max_float_gap) {
+        return None;
+    }
     let p1_colors = p1.color_history();
     let p2_colors = p2.color_history();
-    // Players cannot play 3 times with the same color
-    if let (Some(p1_last_2_colors), Some(p2_last_2_colors)) =
-        (p1_colors.last_chunk::<2>(), p2_colors.last_chunk::<2>())
-    {
-        if p1_last_2_colors[0] == p1_last_2_colors[1] && p1_last_2_colors == p2_last_2_colors {
-            tracing::debug!(
-                "\n----- Paring calculation for {} vs {}-----\n",
-                p1.name,
-                p2.name
-            );
-            tracing::debug!(
-                "Cannot repeat colors three times, returning min value: {}",
-                isize::MIN
-            );
-            return isize::MIN;
-        }
-    }
-    let mut weight: isize = 5_000;
     let scores = (p1.tournament_score(), p2.tournament_score());
     let score_diff = scores.0.abs_diff(scores.1);
     // Score similarity (main criterion)
@@ -278,10 +777,9 @@ fn edge_weight(
         4 => 2250, // 2.0 traditional points
         _ => 2250 + (score_diff as isize) * 200,
     };
-    weight -= score_penalty;
 
     // Small bonus for higher combined score (tends to pair leaders together)
-    weight += ((scores.0 + scores.1) as isize) * 5;
+    let combined_score_bonus = ((scores.0 + scores.1) as isize) * 5;
 
     // Color balance
     let color_penalty = if let (Some(p1_last), Some(p2_last)) = (p1_colors.last(), p2_colors.last())
@@ -290,7 +788,6 @@ fn edge_weight(
     } else {
         0
     };
-    weight -= color_penalty;
 
     // Within same score group: prefer top-half vs bottom-half
     let half_pair_deviation_penalty = if scores.0 == scores.1 {
@@ -303,11 +800,9 @@ fn edge_weight(
     } else {
         0
     };
-    weight -= half_pair_deviation_penalty;
 
     // Penalize repeated floats
     let repeated_float_penalty = (p1.floats as isize + p2.floats as isize) * 20;
-    weight -= repeated_float_penalty;
 
     // Isolation bonus
     let isolation_bonus = if scores.0 != min_score && scores.1 != min_score {
@@ -315,7 +810,6 @@ fn edge_weight(
     } else {
         0
     };
-    weight += isolation_bonus;
 
     // Floating behavior: discourage pairing floated-up player with top of higher group
     let float_rank_penalty = if scores.0 != scores.1 {
@@ -337,23 +831,22 @@ fn edge_weight(
         0
     };
 
-    weight -= float_rank_penalty;
-
-    // tracing::debug!(
-    //     "\n----- Paring calculation for {} vs {}-----\n",
-    //     p1.name,
-    //     p2.name
-    // );
-    // tracing::debug!("score penalty: {} (diff was {})", score_penalty, score_diff);
-    // tracing::debug!("color penalty: {}", color_penalty);
-    // tracing::debug!("repeated float penalty: {}", repeated_float_penalty);
-    // tracing::debug!("isolation bonus: {}", isolation_bonus);
-    // tracing::debug!(
-    //     "half pair deviation penalty: {}",
-    //     half_pair_deviation_penalty
-    // );
-    // tracing::debug!("weight: {}", weight);
-    weight
+    // Opponents already met in the organizer's recent tournaments, 0 meetings unless the
+    // tournament avoids them
+    let recent_opponent_penalty = recent_meetings as isize * RECENT_OPPONENT_PENALTY;
+
+    Some(EdgeWeight {
+        base: 5_000,
+        score_penalty,
+        combined_score_bonus,
+        color_penalty,
+        half_pair_deviation_penalty,
+        repeated_float_penalty,
+        isolation_bonus,
+        float_rank_penalty,
+        recent_opponent_penalty,
+        ..Default::default()
+    })
 }
 
 #[derive(Debug)]
@@ -363,6 +856,17 @@ impl InactiveScores {
     pub fn new() -> Self {
         Self(HashMap::new())
     }
+
+    // Scores can only be given to players who are inactive in the tournament
+    fn validate(&self, tournament: &Tournament) -> Result<(), AppError> {
+        for id in self.keys() {
+            match tournament.players.get(id) {
+                Some(player) if player.status == PlayerStatus::Inactive => {}
+                _ => return Err(AppError::InvalidPlayerId(*id)),
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Deref for InactiveScores {
@@ -389,7 +893,7 @@ impl TryFrom<Vec<(u32, String)>> for InactiveScores {
                 "win" => PlayerResult::Win,
                 "draw" => PlayerResult::Draw,
                 "loss" => PlayerResult::Lose,
-                _ => return Err(Self::Error::DuplicatePlayerResult(player_id)),
+                _ => return Err(Self::Error::InvalidPlayerScore(result_str)),
             };
             let old = inner.insert(player_id, result);
             if old.is_some() {
@@ -400,20 +904,130 @@ impl TryFrom<Vec<(u32, String)>> for InactiveScores {
     }
 }
 
+// Pairings fixed by hand by the arbiter, the engine only pairs the remaining players
+#[derive(Debug, Default)]
+pub struct FixedPairings {
+    pub pairs: Vec<(u32, u32)>,
+    pub byes: Vec<u32>,
+    // Colors forced on paired players, applied in order after the normal color allocation
+    pub colors: Vec<(u32, Color)>,
+}
+
+impl FixedPairings {
+    // Returns the ids of every player fixed to a board or a bye
+    fn validate(&self, tournament: &Tournament) -> Result<HashSet<u32>, AppError> {
+        let mut fixed_ids = HashSet::new();
+        let ids = self
+            .pairs
+            .iter()
+            .flat_map(|(white, black)| [*white, *black])
+            .chain(self.byes.iter().copied());
+        for id in ids {
+            match tournament.players.get(&id) {
+                Some(player) if player.status == PlayerStatus::Active => {}
+                _ => return Err(AppError::InvalidPlayerId(id)),
+            }
+            if !fixed_ids.insert(id) {
+                return Err(AppError::DuplicateFixedPlayer(id));
+            }
+        }
+        for (white, black) in self.pairs.iter() {
+            if tournament.players[white].has_played(*black) {
+                return Err(AppError::FixedPairingAlreadyPlayed {
+                    white: *white,
+                    black: *black,
+                });
+            }
+            // Sections are paired apart, a board never mixes them
+            if tournament.players[white].section_id != tournament.players[black].section_id {
+                return Err(AppError::FixedPairingAcrossSections {
+                    white: *white,
+                    black: *black,
+                });
+            }
+        }
+        Ok(fixed_ids)
+    }
+    // Swaps the boards so every overridden player gets the requested color, ignoring
+    // the color history, this can leave players with an imbalance or three in a row
+    fn apply_colors(&self, pairings: &mut [(usize, usize)]) -> Result<(), AppError> {
+        for (player_id, color) in self.colors.iter() {
+            let id = *player_id as usize;
+            let pair = pairings
+                .iter_mut()
+                .find(|(white, black)| *white == id || *black == id)
+                .ok_or(AppError::ColorOverrideNotPaired(*player_id))?;
+            let is_white = pair.0 == id;
+            if is_white != (*color == Color::White) {
+                (pair.0, pair.1) = (pair.1, pair.0);
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Tournament {
+    // Players without a section are paired together as if they were one more section
+    fn section_ids(&self) -> Vec<Option<u32>> {
+        std::iter::once(None)
+            .chain(self.sections.iter().map(|s| Some(s.id)))
+            .collect()
+    }
+    // Standings of every section, each ranking only contains the players of its section
+    pub fn section_standings(&self) -> Vec<(u32, Vec<Vec<PlayerStanding>>)> {
+        let standings = self.standings();
+        self.sections
+            .iter()
+            .map(|section| {
+                let rounds = standings
+                    .iter()
+                    .map(|round| {
+                        round
+                            .iter()
+                            .filter(|s| self.players[&s.player_id].section_id == Some(section.id))
+                            .copied()
+                            .collect()
+                    })
+                    .collect();
+                (section.id, rounds)
+            })
+            .collect()
+    }
+    // Starting rank (0-based), see `seeding_order`
     fn player_tpn(&self, player_id: u32) -> usize {
-        self.players
-            .values()
-            .sorted_by(|a, b| b.rating.cmp(&a.rating).then_with(|| a.title.cmp(&b.title)))
-            .map(|player| player.id)
+        seeding_order(self.players.values(), self.unrated_default_rating)
+            .into_iter()
             .position(|id| id == player_id)
             .unwrap()
     }
-    fn group_players_by_score(&self) -> HashMap<u32, Vec<&Player>> {
-        let mut groups: HashMap<u32, Vec<&Player>> = HashMap::new();
+    // Byes come from the bottom: nobody scoring less than the bye recipient could have taken it
+    // instead. Only players who had more byes are skipped, in tiny fields the bye then moves
+    // up the table
+    fn check_bye_from_bottom(&self, candidates: &[&Player], bye: u32) -> Result<(), AppError> {
+        let recipient = &self.players[&bye];
+        let lower: Vec<u32> = candidates
+            .iter()
+            .filter(|p| {
+                p.tournament_score() < recipient.tournament_score() && p.byes() <= recipient.byes()
+            })
+            .map(|p| p.id)
+            .sorted()
+            .collect();
+        if lower.is_empty() {
+            return Ok(());
+        }
+        tracing::error!("bye given to {} while {:?} score less", bye, lower);
+        Err(AppError::ByeNotFromBottom {
+            player_id: bye,
+            lower,
+        })
+    }
+    // Score groups are kept apart for each section
+    fn group_players_by_score(&self) -> HashMap<(Option<u32>, u32), Vec<&Player>> {
+        let mut groups: HashMap<(Option<u32>, u32), Vec<&Player>> = HashMap::new();
         for player in self.players.values() {
             groups
-                .entry(player.tournament_score())
+                .entry((player.section_id, player.tournament_score()))
                 .and_modify(|g| g.push(player))
                 .or_insert(vec![player]);
         }
@@ -422,122 +1036,444 @@ impl Tournament {
         }
         groups
     }
-    fn prepare_pairings(&self) -> Result<(Vec<(usize, usize)>, Vec<u32>, Vec<u32>), AppError> {
-        let active_players_count = self
-            .players
-            .values()
-            .filter(|p| p.status == PlayerStatus::Active)
-            .count();
-        let byes = if active_players_count % 2 != 0 {
-            let bottom = self
-                .players
-                .values()
-                .filter(|p| p.status == PlayerStatus::Active)
-                .sorted_unstable_by(|a, b| {
-                    b.byes()
-                        .cmp(&a.byes())
-                        .then_with(|| b.tournament_score().cmp(&a.tournament_score()))
-                        .then_with(|| self.player_tpn(a.id).cmp(&self.player_tpn(b.id)))
-                })
-                .last()
-                .unwrap();
-            vec![bottom.id]
-        } else {
-            Vec::new()
+    // Games between the two players in the organizer's recent tournaments
+    fn recent_meetings(&self, p1: &Player, p2: &Player) -> u32 {
+        let key = (p1.db_id.min(p2.db_id), p1.db_id.max(p2.db_id));
+        self.recent_meetings.get(&key).copied().unwrap_or(0)
+    }
+    // With `allow_rematch_reversed_colors`, colors (white, black) of the second game of two
+    // players who met exactly once before `round`: the colors of their first game reversed.
+    // None when that would give one of them the same color three times in a row
+    fn reversed_rematch_colors(&self, p1: u32, p2: u32, round: usize) -> Option<(u32, u32)> {
+        if !self.allow_rematch_reversed_colors {
+            return None;
+        }
+        let meetings: Vec<Color> = self.players[&p1]
+            .history
+            .iter()
+            .take(round)
+            .filter_map(|item| match item {
+                HistoryItem::Game {
+                    opponent_id, color, ..
+                } if *opponent_id == p2 => Some(*color),
+                _ => None,
+            })
+            .collect();
+        let [first] = meetings[..] else {
+            return None;
         };
-        if self.pairings.len() == self.num_rounds {
-            return Err(AppError::TournamentEnded);
+        let (white, black) = match first {
+            Color::White => (p2, p1),
+            Color::Black => (p1, p2),
+        };
+        let third_in_a_row = |id: u32, color: Color| {
+            self.players[&id]
+                .colors_before(round)
+                .last_chunk::<2>()
+                .is_some_and(|last| last.iter().all(|c| *c == color))
+        };
+        if third_in_a_row(white, Color::White) || third_in_a_row(black, Color::Black) {
+            return None;
         }
-        let groups = self.group_players_by_score();
+        Some((white, black))
+    }
+    fn is_reversed_rematch(&self, p1: u32, p2: u32) -> bool {
+        self.reversed_rematch_colors(p1, p2, self.current_round())
+            .is_some()
+    }
+    fn has_played_each_other(&self, p1: u32, p2: u32) -> bool {
+        self.players[&p1].has_played(p2) || self.players[&p2].has_played(p1)
+    }
+    // Names the constraint that rules out most of the possible opponents of the unpaired players
+    fn pairing_failure_reason(&self, candidates: &[u32], unpaired: &[u32]) -> String {
+        let (mut played, mut colors, mut float_cap, mut opponents) = (0, 0, 0, 0);
+        for id in unpaired {
+            let player = &self.players[id];
+            for other in candidates.iter().filter(|c| *c != id) {
+                let other = &self.players[other];
+                if other.section_id != player.section_id {
+                    continue;
+                }
+                opponents += 1;
+                if self.has_played_each_other(player.id, other.id) {
+                    played += 1;
+                } else if exceeds_float_gap(player, other, self.max_float_gap) {
+                    float_cap += 1;
+                } else if repeats_color_three_times(player, other) {
+                    colors += 1;
+                }
+            }
+        }
+        if opponents == 0 {
+            return String::from("no opponents available");
+        }
+        if played == opponents {
+            return String::from("all remaining players have already played each other");
+        }
+        let dominant = [
+            (
+                played,
+                "most remaining players have already played each other",
+            ),
+            (
+                float_cap,
+                "remaining players are further apart than the float cap",
+            ),
+            (
+                colors,
+                "color deadlock, players would get the same color three times in a row",
+            ),
+        ]
+        .into_iter()
+        .max_by_key(|(count, _)| *count)
+        .unwrap();
+        match dominant {
+            (0, _) => String::from("no perfect matching between the remaining players"),
+            (_, reason) => String::from(reason),
+        }
+    }
+    // Pairs of candidates that may play each other this round, the edges of the matching graph
+    fn allowed_pairs(
+        &self,
+        candidates: &[u32],
+        allow_repeats: bool,
+        allow_color_streaks: bool,
+    ) -> Vec<(u32, u32)> {
         let mut edges = Vec::new();
-        for (p1, p2) in self.players.keys().tuple_combinations() {
-            if self.players[p1].status == PlayerStatus::Inactive
-                || self.players[p2].status == PlayerStatus::Inactive
-                || byes.contains(p1)
-                || byes.contains(p2)
+        for (p1, p2) in candidates.iter().tuple_combinations() {
+            if self.players[p1].section_id != self.players[p2].section_id {
+                continue;
+            }
+            // skip players that have already played, unless repeats are allowed as a last resort
+            // or it is the second game of the pair with the colors reversed
+            if !allow_repeats
+                && self.has_played_each_other(*p1, *p2)
+                && !self.is_reversed_rematch(*p1, *p2)
             {
                 continue;
             }
-            // skip players that have already played
-            if self.players[p1].has_played(*p2) || self.players[p2].has_played(*p1) {
+            // skip pairs floating across more than the configured score gap, an over-strict
+            // cap can leave players unpaired or the whole round unpairable (PairingFailed)
+            if exceeds_float_gap(&self.players[p1], &self.players[p2], self.max_float_gap) {
+                continue;
+            }
+            // players cannot get the same color three times in a row, unless the tournament
+            // allows it as a last resort
+            if !allow_color_streaks
+                && repeats_color_three_times(&self.players[p1], &self.players[p2])
+            {
                 continue;
             }
             edges.push((*p1, *p2));
         }
-        let g = graph::UnGraph::<u32, u32>::from_edges(edges);
+        edges
+    }
+    // Weight of the edge between two candidates, with the rematch and color streak penalties
+    // of the fallback edges
+    fn pair_weight(
+        &self,
+        groups: &HashMap<(Option<u32>, u32), Vec<&Player>>,
+        p1_id: u32,
+        p2_id: u32,
+    ) -> Option<EdgeWeight> {
+        let p1 = &self.players[&p1_id];
+        let p2 = &self.players[&p2_id];
+        let min_score = groups
+            .keys()
+            .filter(|(section_id, _)| *section_id == p1.section_id)
+            .map(|(_, score)| score)
+            .min();
+        let p1_group = &groups[&(p1.section_id, p1.tournament_score())];
+        let p2_group = &groups[&(p2.section_id, p2.tournament_score())];
+        let ranks = (
+            p1_group.iter().position(|p| p.id == p1_id).unwrap(),
+            p2_group.iter().position(|p| p.id == p2_id).unwrap(),
+        );
+        let mut weight = edge_weight(
+            p1,
+            p2,
+            ranks,
+            (p1_group.len(), p2_group.len()),
+            *min_score.unwrap(),
+            self.max_float_gap,
+            self.recent_meetings(p1, p2),
+        )?;
+        if self.has_played_each_other(p1_id, p2_id) {
+            weight.repeat_pairing_penalty = if self.is_reversed_rematch(p1_id, p2_id) {
+                REVERSED_REMATCH_PENALTY
+            } else {
+                REPEAT_PAIRING_PENALTY
+            };
+        }
+        if repeats_color_three_times(p1, p2) {
+            weight.color_streak_penalty = COLOR_STREAK_PENALTY;
+        }
+        Some(weight)
+    }
+    // The matching result depends on the edge order when several matchings have the same
+    // weight, edges are listed by lower tpn, then lower id, so equal inputs pair the same
+    fn sort_edges(&self, candidates: &[u32], edges: &mut [(u32, u32)]) {
+        let tpns: HashMap<u32, usize> = candidates
+            .iter()
+            .map(|id| (*id, self.player_tpn(*id)))
+            .collect();
+        edges.sort_by_key(|(p1, p2)| {
+            let (tpn1, tpn2) = (tpns[p1], tpns[p2]);
+            (tpn1.min(tpn2), tpn1.max(tpn2), *p1.min(p2), *p1.max(p2))
+        });
+    }
+    // Max weight matching of the candidates, the players not on a bye or in a fixed pairing
+    fn match_players(
+        &self,
+        candidates: &[u32],
+        allow_repeats: bool,
+        allow_color_streaks: bool,
+    ) -> Result<Vec<(usize, usize)>, AppError> {
+        let groups = self.group_players_by_score();
+        let mut edges = self.allowed_pairs(candidates, allow_repeats, allow_color_streaks);
+        self.sort_edges(candidates, &mut edges);
+        let g = graph::UnGraph::<u32, u32>::from_edges(edges);
         let pairings = max_weight_matching(
             &g,
             true,
             |edge| {
-                let p1_id = edge.source().index() as u32;
-                let p2_id = edge.target().index() as u32;
-                let p1 = &self.players[&p1_id];
-                let p2 = &self.players[&p2_id];
-                let min_score = groups.keys().min();
-                let ranks = (
-                    groups
-                        .get(&p1.tournament_score())
-                        .unwrap()
-                        .iter()
-                        .position(|p| p.id == edge.source().index() as u32)
-                        .unwrap(),
-                    groups
-                        .get(&p2.tournament_score())
-                        .unwrap()
-                        .iter()
-                        .position(|p| p.id == edge.target().index() as u32)
-                        .unwrap(),
-                );
-                let weight = edge_weight(
-                    p1,
-                    p2,
-                    ranks,
-                    (
-                        groups.get(&p1.tournament_score()).unwrap().len(),
-                        groups.get(&p2.tournament_score()).unwrap().len(),
-                    ),
-                    *min_score.unwrap(),
-                );
+                let weight = self
+                    .pair_weight(
+                        &groups,
+                        edge.source().index() as u32,
+                        edge.target().index() as u32,
+                    )
+                    .map_or(isize::MIN, |weight| weight.total());
                 i128::try_from(weight)
             },
             true,
         )
         .map_err(|e| {
             tracing::error!("prepare_pairings: {:?}", e);
-            AppError::Unknown
+            AppError::PairingFailed {
+                reason: format!("matching failed: {:?}", e),
+                unpaired: candidates.to_vec(),
+            }
         })?;
-        let mut pairings: Vec<(usize, usize)> = pairings.into_iter().collect();
-        pairings.sort_by(|a, b| {
-            let w1 = &self.players[&(a.0 as u32)];
-            let b1 = &self.players[&(a.1 as u32)];
-            let w2 = &self.players[&(b.0 as u32)];
-            let b2 = &self.players[&(b.1 as u32)];
-            (std::cmp::max(w2.tournament_score(), b2.tournament_score()))
-                .cmp(&(std::cmp::max(w1.tournament_score(), b1.tournament_score())))
-                .then_with(|| {
-                    std::cmp::min(w2.tournament_score(), b2.tournament_score())
-                        .cmp(&(std::cmp::min(w1.tournament_score(), b1.tournament_score())))
+        Ok(pairings.into_iter().collect())
+    }
+    // Graph of the next round as `match_players` builds it with the fallbacks the tournament
+    // allows, nothing is paired
+    pub fn pairing_graph(&self) -> Result<PairingGraph, AppError> {
+        if self.players.len() < 2 {
+            return Err(AppError::InsufficientPlayers);
+        }
+        if self
+            .results
+            .last()
+            .is_some_and(|round| round.contains(&GameResult::Ongoing))
+        {
+            return Err(AppError::RoundNotDone);
+        }
+        let (byes, candidates) = self.round_candidates(&FixedPairings::default())?;
+        let groups = self.group_players_by_score();
+        let mut pairs = self.allowed_pairs(
+            &candidates,
+            self.allow_repeat_pairings,
+            self.allow_three_same_colors,
+        );
+        self.sort_edges(&candidates, &mut pairs);
+        let edges = pairs
+            .into_iter()
+            .filter_map(|(p1, p2)| {
+                let breakdown = self.pair_weight(&groups, p1, p2)?;
+                Some(PairingGraphEdge {
+                    players: (p1, p2),
+                    weight: breakdown.total(),
+                    breakdown,
                 })
-                .then_with(|| {
-                    std::cmp::min(self.player_tpn(w1.id), self.player_tpn(b1.id)).cmp(
-                        &std::cmp::min(self.player_tpn(w2.id), self.player_tpn(b2.id)),
-                    )
+            })
+            .collect();
+        Ok(PairingGraph {
+            round: self.pairings.len() as u32,
+            byes,
+            candidates,
+            edges,
+        })
+    }
+    // Whether every candidate can be paired with the allowed pairs, ignoring the weights
+    fn has_perfect_matching(candidates: &[u32], pairs: Vec<(u32, u32)>) -> bool {
+        if candidates.is_empty() {
+            return true;
+        }
+        let g = graph::UnGraph::<u32, u32>::from_edges(pairs);
+        match max_weight_matching(&g, true, |_| Ok::<i128, AppError>(1), true) {
+            Ok(matching) => matching.len() * 2 == candidates.len(),
+            Err(_) => false,
+        }
+    }
+    // Dry run of the graph construction of the next round, see `PairingConstraints`
+    pub fn pairing_constraints(&self) -> Result<PairingConstraints, AppError> {
+        if self.players.len() < 2 {
+            return Err(AppError::InsufficientPlayers);
+        }
+        if self
+            .results
+            .last()
+            .is_some_and(|round| round.contains(&GameResult::Ongoing))
+        {
+            return Err(AppError::RoundNotDone);
+        }
+        let (byes, candidates) = self.round_candidates(&FixedPairings::default())?;
+        let strict = self.allowed_pairs(&candidates, false, false);
+        let connectivity = candidates
+            .iter()
+            .map(|id| PlayerConnectivity {
+                registration_id: *id,
+                played: candidates
+                    .iter()
+                    .filter(|other| self.has_played_each_other(*id, **other))
+                    .count() as u32,
+                available: strict
+                    .iter()
+                    .filter(|(p1, p2)| p1 == id || p2 == id)
+                    .count() as u32,
+            })
+            .sorted_by_key(|c| (c.available, c.registration_id))
+            .collect();
+        let color_balance = candidates
+            .iter()
+            .map(|id| color_balance(&self.players[id].color_history()))
+            .counts()
+            .into_iter()
+            .map(|(balance, count)| (balance, count as u32))
+            .collect();
+        let perfect_matching = Self::has_perfect_matching(&candidates, strict);
+        let perfect_matching_with_fallbacks = perfect_matching
+            || Self::has_perfect_matching(
+                &candidates,
+                self.allowed_pairs(
+                    &candidates,
+                    self.allow_repeat_pairings,
+                    self.allow_three_same_colors,
+                ),
+            );
+        Ok(PairingConstraints {
+            round: self.pairings.len() as u32,
+            players: candidates.len() as u32,
+            byes,
+            connectivity,
+            color_balance,
+            perfect_matching,
+            perfect_matching_with_fallbacks,
+        })
+    }
+    // Sections are paired independently, each one with an odd number of players gets a bye.
+    // Players in `fixed_ids` are paired by the arbiter and never get it
+    fn select_byes(&self, fixed_ids: &HashSet<u32>) -> Result<Vec<u32>, AppError> {
+        let mut byes = Vec::new();
+        let last_round =
+            self.avoid_last_round_contender_bye && self.current_round() + 1 == self.num_rounds;
+        for section_id in self.section_ids() {
+            let section: HashMap<u32, &Player> = self
+                .players
+                .values()
+                .filter(|p| {
+                    p.section_id == section_id
+                        && !fixed_ids.contains(&p.id)
+                        && !p.requested_bye(self.current_round())
                 })
-        });
-        // Check for floats
-        let mut floats = Vec::new();
-        for (w, b) in pairings.iter() {
-            let score_w = self.players[&(*w as u32)].tournament_score();
-            let score_b = self.players[&(*b as u32)].tournament_score();
-            if score_w > score_b {
-                floats.push(*b as u32);
-            }
-            if score_b > score_w {
-                floats.push(*w as u32);
-            }
+                .map(|p| (p.id, p))
+                .collect();
+            let Some(bye) = select_bye(&section, |id| self.player_tpn(id), last_round) else {
+                continue;
+            };
+            let available: Vec<&Player> = section
+                .values()
+                .filter(|p| p.status == PlayerStatus::Active)
+                .copied()
+                .collect();
+            self.check_bye_from_bottom(&available, bye)?;
+            byes.push(bye);
+        }
+        Ok(byes)
+    }
+    // Byes the next round would get with the current scores, without matching anybody. While
+    // results of the latest round are missing they are counted as not played, so the actual
+    // bye may differ
+    pub fn predicted_byes(&self) -> Result<Vec<u32>, AppError> {
+        if self.pairings.len() == self.num_rounds {
+            return Err(AppError::TournamentEnded);
+        }
+        self.select_byes(&HashSet::new())
+    }
+    // Byes of the round (odd sections and fixed byes) and the players left to be matched
+    fn round_candidates(&self, fixed: &FixedPairings) -> Result<(Vec<u32>, Vec<u32>), AppError> {
+        let fixed_ids = fixed.validate(self)?;
+        let mut byes = self.select_byes(&fixed_ids)?;
+        byes.extend(fixed.byes.iter().copied());
+        if self.pairings.len() == self.num_rounds {
+            return Err(AppError::TournamentEnded);
+        }
+        let candidates: Vec<u32> = self
+            .players
+            .values()
+            .filter(|p| {
+                p.status == PlayerStatus::Active
+                    && !byes.contains(&p.id)
+                    && !fixed_ids.contains(&p.id)
+                    && !p.requested_bye(self.current_round())
+            })
+            .map(|p| p.id)
+            .sorted()
+            .collect();
+        Ok((byes, candidates))
+    }
+    // Fixed pairs are placed on the top boards, in the given order, ahead of the generated ones
+    // Deterministic: the same tournament state always gives the same pairings, byes and
+    // floats, whatever the iteration order of the players map
+    fn prepare_pairings(
+        &self,
+        fixed: &FixedPairings,
+    ) -> Result<(Vec<(usize, usize)>, Vec<u32>), AppError> {
+        let (byes, candidates) = self.round_candidates(fixed)?;
+        let mut pairings = self.match_players(&candidates, false, false)?;
+        let mut unpaired = unpaired_players(&candidates, &pairings);
+        // Relaxations only apply when the round could not be paired otherwise, a third color
+        // in a row first, since it is preferred over a rematch
+        if !unpaired.is_empty() && self.allow_three_same_colors {
+            tracing::warn!("no pairings without color streaks, retrying with them allowed");
+            pairings = self.match_players(&candidates, false, true)?;
+            unpaired = unpaired_players(&candidates, &pairings);
+        }
+        if !unpaired.is_empty() && self.allow_repeat_pairings {
+            tracing::warn!("no pairings without repeats, retrying with repeat pairings allowed");
+            pairings = self.match_players(&candidates, true, self.allow_three_same_colors)?;
+            unpaired = unpaired_players(&candidates, &pairings);
+        }
+        if !unpaired.is_empty() {
+            return Err(AppError::PairingFailed {
+                reason: self.pairing_failure_reason(&candidates, &unpaired),
+                unpaired,
+            });
         }
+        self.sort_boards(&mut pairings);
+        let pairings: Vec<(usize, usize)> = fixed
+            .pairs
+            .iter()
+            .map(|(white, black)| (*white as usize, *black as usize))
+            .chain(pairings)
+            .collect();
         let byes = byes.into_iter().collect_vec();
-        Ok((pairings, byes, floats))
+        Ok((pairings, byes))
+    }
+    // Strongest games go to the top boards: highest combined score first, then the pair
+    // with the best seeded player. Sections follow each other in creation order
+    fn sort_boards(&self, pairings: &mut [(usize, usize)]) {
+        pairings.sort_by_cached_key(|(white, black)| {
+            let white = &self.players[&(*white as u32)];
+            let black = &self.players[&(*black as u32)];
+            (
+                white.section_id,
+                Reverse(white.tournament_score() + black.tournament_score()),
+                std::cmp::min(self.player_tpn(white.id), self.player_tpn(black.id)),
+            )
+        });
     }
     fn process_pairings(
         &self,
@@ -570,6 +1506,7 @@ impl Tournament {
             self.players
                 .values()
                 .filter(|p| p.status == PlayerStatus::Inactive)
+                .sorted_by_key(|p| p.id)
                 .map(|player| match inactive_scores.get(&player.id) {
                     Some(result) => NewDbPairingGap {
                         player_id: player.id,
@@ -592,21 +1529,122 @@ impl Tournament {
                 })
                 .collect::<Vec<NewDbPairingGap>>(),
         );
+        // Requested byes are scored like an absence, they do not count as a pairing bye
+        db_gaps.extend(
+            self.players
+                .values()
+                .filter(|p| {
+                    p.status == PlayerStatus::Active
+                        && p.requested_bye(self.current_round())
+                        && !byes.contains(&p.id)
+                        && !db_pairings
+                            .iter()
+                            .any(|pair| pair.white_id == p.id || pair.black_id == p.id)
+                })
+                .sorted_by_key(|p| p.id)
+                .map(|player| NewDbPairingGap {
+                    player_id: player.id,
+                    tournament_id: self.id,
+                    round_id: self.pairings.len() as u32,
+                    score: self.requested_bye_score,
+                    is_bye: false,
+                }),
+        );
         (db_pairings, db_gaps)
     }
+    // Every registered player must be on exactly one board or gap of the round, catches a
+    // player silently dropped (or placed twice) by the matching before anything is stored
+    fn check_round_coverage(
+        &self,
+        pairings: &[NewDbPairing],
+        gaps: &[NewDbPairingGap],
+    ) -> Result<(), AppError> {
+        let appearances = pairings
+            .iter()
+            .flat_map(|pair| [pair.white_id, pair.black_id])
+            .chain(gaps.iter().map(|gap| gap.player_id))
+            .counts();
+        let missing: Vec<u32> = self
+            .players
+            .keys()
+            .filter(|id| !appearances.contains_key(id))
+            .copied()
+            .sorted()
+            .collect();
+        let duplicated: Vec<u32> = appearances
+            .into_iter()
+            .filter(|(id, count)| *count > 1 || !self.players.contains_key(id))
+            .map(|(id, _)| id)
+            .sorted()
+            .collect();
+        if missing.is_empty() && duplicated.is_empty() {
+            return Ok(());
+        }
+        tracing::error!(
+            "round {} does not cover the roster, missing: {:?}, duplicated: {:?}",
+            self.current_round(),
+            missing,
+            duplicated
+        );
+        Err(AppError::PairingInvariantViolated {
+            missing,
+            duplicated,
+        })
+    }
     pub fn current_round(&self) -> usize {
         self.pairings.len()
     }
+    // Changes the result of a board in memory only, `board` is 0-based without the board
+    // offset. For mini-matches the result is the one of the whole match
+    pub fn set_result(
+        &mut self,
+        round: usize,
+        board: usize,
+        result: GameResult,
+    ) -> Result<(), AppError> {
+        let (white, black) = *self
+            .pairings
+            .get(round)
+            .ok_or(AppError::RoundNotFound(round))?
+            .get(board)
+            .ok_or(AppError::BoardNotFound(board as u32))?;
+        self.results[round][board] = result;
+        for (id, opponent_id, color) in [(white, black, Color::White), (black, white, Color::Black)]
+        {
+            self.players.get_mut(&(id as u32)).unwrap().history[round] = HistoryItem::Game {
+                opponent_id: opponent_id as u32,
+                color,
+                result,
+            };
+        }
+        Ok(())
+    }
+    // Builder form of `set_result`, `tournament.clone().with_result(..)` leaves the original
+    // untouched for what-if comparisons
+    pub fn with_result(
+        mut self,
+        round: usize,
+        board: usize,
+        result: GameResult,
+    ) -> Result<Self, AppError> {
+        self.set_result(round, board, result)?;
+        Ok(self)
+    }
+    pub fn max_players(&self) -> u32 {
+        self.max_players.unwrap_or(MAX_PLAYERS).min(MAX_PLAYERS)
+    }
     pub fn generate_first_round_pairings(
         &self,
         inactive_scores: InactiveScores,
         first_color: Color,
+        fixed: &FixedPairings,
     ) -> Result<NewPairings, AppError> {
-        let (mut pairings, byes, floats) = self.prepare_pairings()?;
+        let (mut pairings, byes) = self.prepare_pairings(fixed)?;
         // Assign colors in round 1 according to first_color variable
         // Use it to assign the color to the top seed and alternate
+        // Fixed pairs keep the colors chosen by the arbiter
         let mut current_color = first_color;
-        for pair in pairings.iter_mut() {
+        for pair in pairings.iter_mut().skip(fixed.pairs.len()) {
             if current_color == Color::White && pair.0 > pair.1 {
                 (pair.0, pair.1) = (pair.1, pair.0);
             }
@@ -615,321 +1653,2370 @@ impl Tournament {
             }
             current_color = current_color.other();
         }
+        fixed.apply_colors(&mut pairings)?;
         let (pairings, gaps) = self.process_pairings(pairings, byes, inactive_scores);
+        if pairings.is_empty() {
+            return Err(AppError::EmptyPairingsGenerated);
+        }
+        self.check_round_coverage(&pairings, &gaps)?;
         Ok(NewPairings {
             round: 0,
             pairings,
             gaps,
-            floats,
         })
     }
     pub fn generate_next_round_pairings(
         &self,
         inactive_scores: InactiveScores,
+        fixed: &FixedPairings,
     ) -> Result<NewPairings, AppError> {
-        let (mut pairings, byes, floats) = self.prepare_pairings()?;
+        let (mut pairings, byes) = self.prepare_pairings(fixed)?;
         // Assing colors in subsequent rounds
-        for pair in pairings.iter_mut() {
+        // Fixed pairs keep the colors chosen by the arbiter
+        for pair in pairings.iter_mut().skip(fixed.pairs.len()) {
             let p1 = &self.players[&(pair.0 as u32)];
             let p2 = &self.players[&(pair.1 as u32)];
-            let p1_colors = p1.color_history();
-            let p2_colors = p2.color_history();
-            match (p1_colors.last(), p2_colors.last()) {
-                (None, None) => {}
-                (None, Some(p2_last_color)) => {
-                    if p2_last_color == &Color::Black {
-                        pair.0 = p2.id as usize;
-                        pair.1 = p1.id as usize;
-                    }
-                }
-                (Some(p1_last_color), None) => {
-                    if p1_last_color == &Color::White {
-                        pair.0 = p2.id as usize;
-                        pair.1 = p1.id as usize;
-                    }
-                }
-                (Some(p1_last_color), Some(p2_last_color)) => {
-                    // If players played with different colors in the last round, switch colors for both
-                    if p1_last_color != p2_last_color && p1_last_color == &Color::White {
-                        // if pair.0 was black last round the order is already correct
-                        pair.0 = p2.id as usize;
-                        pair.1 = p1.id as usize;
-                    }
-                    // Both players played with the same color last round
-                    if p1_last_color == p2_last_color {
-                        // Check for color imbalances in color history
-                        let p1_color_balance = p1_colors.iter().fold(0, |acc, c| match c {
-                            Color::White => acc + 1,
-                            Color::Black => acc - 1,
-                        });
-                        let p2_color_balance = p2_colors.iter().fold(0, |acc, c| match c {
-                            Color::White => acc + 1,
-                            Color::Black => acc - 1,
-                        });
-                        // If pair.0 has more whites he should play as black now
-                        if p1_color_balance > p2_color_balance {
-                            pair.0 = p2.id as usize;
-                            pair.1 = p1.id as usize;
-                        }
-                        // If both color balances are the same the player with better (lower)
-                        // starting rank (tpn) should play as black
-                        if p1_color_balance == p2_color_balance
-                            && self.player_tpn(p1.id) < self.player_tpn(p2.id)
-                        {
-                            pair.0 = p2.id as usize;
-                            pair.1 = p1.id as usize;
-                        }
-                    }
-                }
-            }
+            let (white, black) = self
+                .reversed_rematch_colors(p1.id, p2.id, self.current_round())
+                .unwrap_or_else(|| {
+                    assign_colors(p1, p2, self.player_tpn(p1.id).cmp(&self.player_tpn(p2.id)))
+                });
+            *pair = (white as usize, black as usize);
         }
+        fixed.apply_colors(&mut pairings)?;
         let (pairings, gaps) = self.process_pairings(pairings, byes, inactive_scores);
         if pairings.is_empty() {
             return Err(AppError::EmptyPairingsGenerated);
         }
+        self.check_round_coverage(&pairings, &gaps)?;
         Ok(NewPairings {
             round: self.current_round() as u32,
             pairings,
             gaps,
-            floats,
         })
     }
+    // Colors of a pair from the games played before the given round
+    fn allocate_pair_colors(&self, p1: u32, p2: u32, round: usize) -> (u32, u32, ColorRule) {
+        let (p1_colors, p2_colors) = (
+            self.players[&p1].colors_before(round),
+            self.players[&p2].colors_before(round),
+        );
+        allocate_colors(
+            ColorCandidate {
+                id: p1,
+                colors: &p1_colors,
+            },
+            ColorCandidate {
+                id: p2,
+                colors: &p2_colors,
+            },
+            self.player_tpn(p1).cmp(&self.player_tpn(p2)),
+        )
+    }
+    // Why each board of a paired round got its colors, boards that do not match the
+    // allocation rules or the reversed colors of a rematch were fixed or overridden by the
+    // arbiter
+    pub fn color_decisions(&self, round: usize) -> Result<Vec<ColorDecision>, AppError> {
+        let pairings = self
+            .pairings
+            .get(round)
+            .ok_or(AppError::RoundNotFound(round))?;
+        Ok(pairings
+            .iter()
+            .enumerate()
+            .map(|(board, (white, black))| {
+                let (white, black) = (*white as u32, *black as u32);
+                let reversed_rematch = self.reversed_rematch_colors(white, black, round);
+                let rule = match self.allocate_pair_colors(white, black, round) {
+                    _ if round == 0 => ColorRule::InitialColor,
+                    _ if reversed_rematch == Some((white, black)) => ColorRule::ReversedRematch,
+                    (_, _, ColorRule::Unconstrained) => ColorRule::Unconstrained,
+                    (w, b, rule) if (w, b) == (white, black) => rule,
+                    _ => ColorRule::ArbiterDecision,
+                };
+                ColorDecision {
+                    board_number: board as u32 + self.board_offset,
+                    white_id: white,
+                    black_id: black,
+                    white_balance: color_balance(&self.players[&white].colors_before(round)),
+                    black_balance: color_balance(&self.players[&black].colors_before(round)),
+                    rule,
+                }
+            })
+            .collect())
+    }
     pub fn standings(&self) -> Vec<Vec<PlayerStanding>> {
+        let mut standings = self.swiss_standings();
+        // Playoff games decide the final order among the tied leaders who played them
+        if !self.playoff.is_empty()
+            && let Some(last) = standings.last_mut()
+        {
+            let order = self.playoff_order();
+            last.sort_by_key(|s| {
+                order
+                    .iter()
+                    .position(|id| *id == s.player_id)
+                    .unwrap_or(usize::MAX)
+            });
+        }
+        standings
+    }
+    // Armageddon rules, a draw counts as a win for black
+    fn playoff_winner(game: &DbPlayoffGame) -> Option<u32> {
+        match game.result.as_ref().map(GameResult::from_str) {
+            Some(GameResult::WhiteWins) => Some(game.white_id),
+            Some(GameResult::BlackWins) | Some(GameResult::Draw) => Some(game.black_id),
+            _ => None,
+        }
+    }
+    // Players tied for first place, in the order of the swiss standings
+    fn playoff_entrants(&self) -> Vec<u32> {
+        let standings = self.swiss_standings();
+        let Some(last) = standings.last() else {
+            return Vec::new();
+        };
+        let top_score = last.first().map(|s| s.score);
+        last.iter()
+            .take_while(|s| Some(s.score) == top_score)
+            .map(|s| s.player_id)
+            .collect()
+    }
+    // Round in which the player lost a playoff game, None if never eliminated
+    fn playoff_elimination_round(&self, player_id: u32) -> Option<u32> {
+        self.playoff
+            .iter()
+            .find(|g| {
+                (g.white_id == player_id || g.black_id == player_id)
+                    && Self::playoff_winner(g).is_some_and(|winner| winner != player_id)
+            })
+            .map(|g| g.round_number)
+    }
+    // Playoff participants from best to worst: players eliminated later rank higher,
+    // players eliminated in the same round keep their swiss order
+    fn playoff_order(&self) -> Vec<u32> {
+        self.playoff_entrants()
+            .into_iter()
+            .sorted_by_key(|id| {
+                Reverse(
+                    self.playoff_elimination_round(*id)
+                        .map(|r| r as i64)
+                        .unwrap_or(i64::MAX),
+                )
+            })
+            .collect()
+    }
+    // Knockout bracket among the tied leaders, returns the round number and its games.
+    // With an odd number of players the best seed gets a pass, the rest play top against bottom
+    pub fn next_playoff_round(&self) -> Result<(u32, Vec<(u32, u32)>), AppError> {
+        let round_ongoing = self
+            .results
+            .iter()
+            .flatten()
+            .any(|r| *r == GameResult::Ongoing);
+        if self.current_round() < self.num_rounds || round_ongoing {
+            return Err(AppError::PlayoffNotAvailable);
+        }
+        if self
+            .playoff
+            .iter()
+            .any(|g| Self::playoff_winner(g).is_none())
+        {
+            return Err(AppError::RoundNotDone);
+        }
+        let survivors: Vec<u32> = self
+            .playoff_entrants()
+            .into_iter()
+            .filter(|id| self.playoff_elimination_round(*id).is_none())
+            .collect();
+        if survivors.len() < 2 {
+            return Err(if self.playoff.is_empty() {
+                AppError::NoPlayoffNeeded
+            } else {
+                AppError::PlayoffFinished
+            });
+        }
+        let round = self
+            .playoff
+            .iter()
+            .map(|g| g.round_number + 1)
+            .max()
+            .unwrap_or(0);
+        let paired = &survivors[survivors.len() % 2..];
+        let games = (0..paired.len() / 2)
+            .map(|i| (paired[i], paired[paired.len() - 1 - i]))
+            .collect();
+        Ok((round, games))
+    }
+    // Standings from the swiss rounds only, without playoff games
+    fn swiss_standings(&self) -> Vec<Vec<PlayerStanding>> {
         let mut standings = Vec::new();
         let mut prev_scores: HashMap<u32, PlayerStanding> = self
             .players
             .keys()
             .map(|id| (*id, PlayerStanding::new(*id)))
             .collect();
-        for round in 0..self.current_round() {
-            let mut ranking = Vec::new();
-            for player in self.players.values() {
-                let prev = prev_scores.get(&player.id).unwrap();
-                let round_score = match player.history.get(round) {
-                    Some(HistoryItem::NotPaired { score }) => *score,
-                    Some(HistoryItem::Bye) => 2,
-                    Some(HistoryItem::Game {
-                        opponent_id: _,
-                        color,
-                        result,
-                    }) => match (color, result) {
-                        (Color::White, GameResult::WhiteWins) => 2,
-                        (Color::Black, GameResult::BlackWins) => 2,
-                        (_, GameResult::Draw) => 1,
-                        _ => 0,
-                    },
-                    _ => 0,
-                };
-                let mut standing = PlayerStanding::new(player.id);
-                standing.score = prev.score + round_score;
-                standing.progressive = prev.progressive + standing.score;
+        for round in 0..self.current_round() {
+            let mut ranking = Vec::new();
+            for player in self.players.values() {
+                let prev = prev_scores.get(&player.id).unwrap();
+                let round_score = player
+                    .history
+                    .get(round)
+                    .map_or(0, |item| history_item_points(item, BYE_POINTS));
+                let mut standing = PlayerStanding::new(player.id);
+                standing.score = prev.score + round_score;
+                standing.progressive = prev.progressive + standing.score;
+                // The running score after the first round is the first round score itself
+                standing.cumulative_cut1 = if round == 0 {
+                    0
+                } else {
+                    prev.cumulative_cut1 + standing.score
+                };
+
+                ranking.push(standing);
+                prev_scores.insert(player.id, standing);
+            }
+            for standing in ranking.iter_mut() {
+                let player = &self.players[&standing.player_id];
+                let opponents: Vec<&Player> = player
+                    .history
+                    .iter()
+                    .take(round as usize + 1)
+                    .filter_map(|item| item.opponent(false))
+                    .filter_map(|id| self.players.get(&id))
+                    .collect();
+                let mut opponent_scores: Vec<u32> = opponents
+                    .iter()
+                    .map(|player| {
+                        player
+                            .history
+                            .iter()
+                            .take(round as usize + 1)
+                            .map(|item| history_item_points(item, BYE_POINTS))
+                            .sum()
+                    })
+                    .collect();
+                opponent_scores.sort();
+                standing.buchholz = opponent_scores.iter().sum();
+                standing.cut_one_buchholz = opponent_scores.iter().skip(1).sum();
+                if opponent_scores.pop().is_some() {
+                    standing.median_buchholz = opponent_scores.iter().skip(1).sum();
+                } else {
+                    standing.median_buchholz = 0;
+                }
+            }
+            ranking.sort_by(|a, b| {
+                self.tiebreaks
+                    .iter()
+                    .fold(b.score.cmp(&a.score), |ordering, tiebreak| {
+                        ordering.then_with(|| tiebreak.value(b).cmp(&tiebreak.value(a)))
+                    })
+                    // Players still tied keep a stable order between requests
+                    .then_with(|| a.player_id.cmp(&b.player_id))
+            });
+            standings.push(ranking);
+        }
+        standings
+    }
+}
+
+pub async fn end_tournament(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+) -> Result<i64, AppError> {
+    let user_id = claims.sub;
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if role != Some(TournamentRole::Owner) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let tournament = get_tournament(pool, tournament_id).await.map_err(|e| {
+        tracing::error!("end_tournament (get_tournament): {:?}", e);
+        AppError::Unknown
+    })?;
+    if tournament.current_round < tournament.num_rounds {
+        return Err(AppError::CannotEndTournament);
+    }
+    // The end date is the current time, it cannot come before the start
+    if Utc::now().timestamp() < i64::from(tournament.start_date) {
+        return Err(AppError::InvalidStartDate(tournament.start_date));
+    }
+    let pairings = select_pairings(pool, tournament_id).await.map_err(|e| {
+        tracing::error!("end_tournament (select_tournament): {:?}", e);
+        AppError::Unknown
+    })?;
+    // Double forfeits are a final result, only boards without one block the end
+    if pairings
+        .iter()
+        .map(|p| GameResult::from_str(p.result.as_ref().unwrap_or(&"*".to_string())))
+        .any(|r| r == GameResult::Ongoing)
+    {
+        return Err(AppError::RoundNotDone);
+    }
+    tournament_repo::end_tournament(pool, user_id, tournament_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("end_tournament (end_tournament): {:?}", e);
+            AppError::Unknown
+        })
+}
+
+// Private tournaments look like they do not exist to anyone but their organizers
+async fn check_visible(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament: &DbTournament,
+    claims: Option<&Claims>,
+) -> Result<(), AppError> {
+    if Visibility::try_from(tournament.visibility.as_str())? == Visibility::Private {
+        let is_organizer = match claims {
+            Some(claims) => check_user_is_organizer(pool, tournament.id, claims).await?,
+            None => false,
+        };
+        if !is_organizer {
+            return Err(AppError::TournamentNotFound);
+        }
+    }
+    Ok(())
+}
+
+pub async fn read_visible_tournament(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    id: u32,
+    claims: Option<&Claims>,
+) -> Result<TournamentDbData, AppError> {
+    let tournament = read_tournament(pool, id).await?;
+    check_visible(pool, &tournament.tournament, claims).await?;
+    Ok(tournament)
+}
+
+// Only the roster, without loading the pairings. `active` keeps the players with (or without)
+// the active status
+pub async fn registrations(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    id: u32,
+    active: Option<bool>,
+    claims: Option<&Claims>,
+) -> Result<Vec<RegisteredPlayer>, AppError> {
+    let tournament = match get_tournament(pool, id).await {
+        Ok(t) => t,
+        Err(sqlx::Error::RowNotFound) => return Err(AppError::TournamentNotFound),
+        Err(e) => return Err(AppError::Database(e)),
+    };
+    check_visible(pool, &tournament, claims).await?;
+    let mut players: HashMap<u32, Player> = select_registrations(pool, id)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, Player::from(p)))
+        .collect();
+    for request in registration_repo::select_bye_requests(pool, id).await? {
+        if let Some(player) = players.get_mut(&request.registration_id) {
+            player.requested_bye_rounds.push(request.round_number);
+        }
+    }
+    let registrations = seeding_order(players.values(), tournament.unrated_default_rating)
+        .into_iter()
+        .zip(1..)
+        .sorted_unstable()
+        .map(|(id, seed)| (&players[&id], seed))
+        .filter(|(p, _)| active.is_none_or(|active| (p.status == PlayerStatus::Active) == active))
+        .map(|(p, seed)| RegisteredPlayer::new(p, seed))
+        .collect();
+    Ok(registrations)
+}
+
+pub async fn current_round(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Option<&Claims>,
+) -> Result<CurrentRound, AppError> {
+    let tournament: Tournament = read_visible_tournament(pool, tournament_id, claims)
+        .await?
+        .into();
+    CurrentRound::new(&tournament)
+}
+
+// Standings after the given round, or after the latest one
+pub async fn standings(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    round: Option<u32>,
+    claims: Option<&Claims>,
+) -> Result<(u32, Vec<PlayerStanding>, u32), AppError> {
+    let tournament: Tournament = read_visible_tournament(pool, tournament_id, claims)
+        .await?
+        .into();
+    let mut standings = tournament.standings();
+    if standings.is_empty() {
+        return Err(AppError::TournamentNotStarted);
+    }
+    let round = round.unwrap_or(standings.len() as u32 - 1);
+    if round as usize >= standings.len() {
+        return Err(AppError::RoundNotFound(round as usize));
+    }
+    Ok((
+        round,
+        standings.swap_remove(round as usize),
+        tournament.updated_at,
+    ))
+}
+
+// Standings after the latest round with the given results applied to the tournament in
+// memory, nothing is written to the database
+pub async fn simulate_standings(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    payload: SimulatedResults,
+) -> Result<(u32, Vec<PlayerStanding>), AppError> {
+    if !check_user_is_organizer(pool, tournament_id, &claims).await? {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let mut tournament: Tournament = read_tournament(pool, tournament_id).await?.into();
+    let boards: usize = tournament.pairings.iter().map(|round| round.len()).sum();
+    if payload.results.len() > boards {
+        return Err(AppError::TooManyItems {
+            field: "results".to_string(),
+            max: boards,
+        });
+    }
+    for simulated in payload.results.iter() {
+        let board = simulated
+            .board_id
+            .checked_sub(tournament.board_offset)
+            .ok_or(AppError::BoardNotFound(simulated.board_id))?;
+        // `*` takes a game back, any other notation that parses as ongoing is a typo
+        let result = GameResult::from_str(&simulated.result);
+        if result == GameResult::Ongoing && simulated.result.trim() != "*" {
+            return Err(AppError::InvalidResult(simulated.result.clone()));
+        }
+        // Reported with the board id the client sent
+        tournament = tournament
+            .with_result(simulated.round_id as usize, board as usize, result)
+            .map_err(|e| match e {
+                AppError::BoardNotFound(_) => AppError::BoardNotFound(simulated.board_id),
+                e => e,
+            })?;
+    }
+    let mut standings = tournament.standings();
+    let round = standings
+        .len()
+        .checked_sub(1)
+        .ok_or(AppError::TournamentNotStarted)?;
+    Ok((round as u32, standings.swap_remove(round)))
+}
+
+pub async fn standings_html(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Option<&Claims>,
+) -> Result<String, AppError> {
+    let tournament: Tournament = read_visible_tournament(pool, tournament_id, claims)
+        .await?
+        .into();
+    export::standings_html(&tournament).ok_or(AppError::TournamentNotStarted)
+}
+
+pub async fn bye_prediction(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Option<&Claims>,
+) -> Result<(u32, Vec<u32>), AppError> {
+    let tournament: Tournament = read_visible_tournament(pool, tournament_id, claims)
+        .await?
+        .into();
+    let byes = tournament.predicted_byes()?;
+    Ok((tournament.current_round() as u32, byes))
+}
+
+pub async fn color_decisions(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    round: u32,
+    claims: Option<&Claims>,
+) -> Result<Vec<ColorDecision>, AppError> {
+    let tournament: Tournament = read_visible_tournament(pool, tournament_id, claims)
+        .await?
+        .into();
+    tournament.color_decisions(round as usize)
+}
+
+pub async fn pending_boards(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    round: u32,
+    claims: Option<&Claims>,
+) -> Result<PendingBoards, AppError> {
+    let tournament: Tournament = read_visible_tournament(pool, tournament_id, claims)
+        .await?
+        .into();
+    PendingBoards::new(&tournament, round as usize)
+}
+
+pub async fn stats(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Option<&Claims>,
+) -> Result<TournamentStats, AppError> {
+    let tournament: Tournament = read_visible_tournament(pool, tournament_id, claims)
+        .await?
+        .into();
+    Ok(TournamentStats::new(&tournament))
+}
+
+// The lower scored player of every board floats up, given the scores before the round
+fn round_floats(pairings: &[(usize, usize)], score: impl Fn(u32) -> u32) -> Vec<u32> {
+    let mut floats = Vec::new();
+    for (w, b) in pairings.iter() {
+        let score_w = score(*w as u32);
+        let score_b = score(*b as u32);
+        if score_w > score_b {
+            floats.push(*b as u32);
+        }
+        if score_b > score_w {
+            floats.push(*w as u32);
+        }
+    }
+    floats
+}
+
+pub async fn generate_next_pairings(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    payload: NextPairings,
+) -> Result<NewPairings, AppError> {
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if role != Some(TournamentRole::Owner) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let tournament: Tournament = read_tournament(pool, tournament_id).await?.into();
+    pair_round(&tournament, payload)
+}
+
+// Pairs the current round again, as long as no result has been entered. The new pairings
+// replace the current ones with `NewPairings::replace`
+pub async fn regenerate_round(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    payload: NextPairings,
+) -> Result<NewPairings, AppError> {
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if role != Some(TournamentRole::Owner) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let mut data = read_tournament(pool, tournament_id).await?;
+    let Some(round) = data.tournament.current_round.checked_sub(1) else {
+        return Err(AppError::TournamentNotStarted);
+    };
+    let (discarded, kept): (Vec<DbPairing>, Vec<DbPairing>) = data
+        .pairings
+        .into_iter()
+        .partition(|p| p.round_number == round);
+    if discarded
+        .iter()
+        .any(|p| p.result.is_some() || p.games.is_some())
+    {
+        return Err(AppError::RoundHasResults(round));
+    }
+    data.pairings = kept;
+    data.pairing_gaps.retain(|gap| gap.round_id != round);
+    data.tournament.current_round = round;
+    pair_round(&data.into(), payload)
+}
+
+fn pair_round(tournament: &Tournament, payload: NextPairings) -> Result<NewPairings, AppError> {
+    // Every list names each player at most once, checked before building anything from them
+    let players = tournament.players.len();
+    for (field, len) in [
+        ("inactiveScores", payload.inactive_scores.len()),
+        ("fixedPairings", payload.fixed_pairings.len()),
+        ("fixedByes", payload.fixed_byes.len()),
+        ("colorOverrides", payload.color_overrides.len()),
+    ] {
+        if len > players {
+            return Err(AppError::TooManyItems {
+                field: field.to_string(),
+                max: players,
+            });
+        }
+    }
+    let scores: InactiveScores = payload.inactive_scores.try_into()?;
+    let colors = payload
+        .color_overrides
+        .iter()
+        .map(|(id, color)| Ok((*id, Color::try_from(color.as_str())?)))
+        .collect::<Result<Vec<(u32, Color)>, AppError>>()?;
+    let fixed = FixedPairings {
+        pairs: payload.fixed_pairings,
+        byes: payload.fixed_byes,
+        colors,
+    };
+    if tournament.players.len() < 2 {
+        return Err(AppError::InsufficientPlayers);
+    }
+    scores.validate(tournament)?;
+    if tournament.current_round() == 0 {
+        let color = match payload.first_color.as_ref().map(|s| s.as_str()) {
+            Some("black") => Color::Black,
+            Some("white") => Color::White,
+            _ => Color::White,
+        };
+        tournament.generate_first_round_pairings(scores, color, &fixed)
+    } else {
+        let round_ongoing = tournament
+            .results
+            .last()
+            .unwrap()
+            .iter()
+            .any(|r| *r == GameResult::Ongoing);
+        if round_ongoing {
+            return Err(AppError::RoundNotDone);
+        }
+        tournament.generate_next_round_pairings(scores, &fixed)
+    }
+}
+
+pub async fn update_player_status(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    payload: &PlayerStatusPayload,
+) -> Result<(), AppError> {
+    let user_id = claims.sub;
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if role != Some(TournamentRole::Owner) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let status: PlayerStatus = payload.status.as_str().try_into()?;
+    registration_repo::update_registration_status(pool, user_id, tournament_id, payload.id, status)
+        .await
+        .map_err(|e| Into::<AppError>::into(e))
+}
+
+pub async fn update_result(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    payload: &RoundResult,
+) -> Result<(), AppError> {
+    let user_id = claims.sub;
+    let role = check_user_tournament_permissions(pool, tournament_id, claims).await?;
+    if !role.is_some_and(|role| role.can_enter_results()) {
+        return Err(AppError::InsufficientPermissions);
+    }
+    // Unrecognized notations parse as ongoing, which cannot be entered either
+    let result = GameResult::from_str(payload.result.clone());
+    if result == GameResult::Ongoing {
+        return Err(AppError::InvalidResult(payload.result.clone()));
+    }
+    let tournament = read_tournament(pool, tournament_id).await?;
+    let tournament: Tournament = tournament.into();
+    if tournament.pairings.is_empty() {
+        return Err(AppError::TournamentNotStarted);
+    }
+    let round = match tournament.results.get(payload.round_id as usize) {
+        Some(r) => r,
+        None => return Err(AppError::RoundNotFound(payload.round_id as usize)),
+    };
+    // Board ids sent by clients include the tournament board offset
+    let board_id = match payload.board_id.checked_sub(tournament.board_offset) {
+        Some(board_id) if round.get(board_id as usize).is_some() => board_id,
+        _ => return Err(AppError::BoardNotFound(payload.board_id)),
+    };
+    if (payload.round_id as usize) < tournament.current_round() - 1 {
+        return Err(AppError::InvalidRound(payload.round_id as usize));
+    }
+    if tournament.games_per_pairing > 1 {
+        let game_number = match payload.game_number {
+            Some(n) if n < tournament.games_per_pairing => n,
+            n => return Err(AppError::InvalidGameNumber(n.unwrap_or_default())),
+        };
+        // The agreed flag belongs to the board, it is not kept per game
+        if payload.agreed.unwrap_or_default() {
+            return Err(AppError::AgreedDrawInMatch);
+        }
+        let updated = pairing_repo::update_match_game_result(
+            pool,
+            user_id,
+            tournament_id,
+            payload.round_id,
+            board_id,
+            tournament.games_per_pairing as usize,
+            game_number,
+            result,
+            payload.expected_result.as_deref().map(GameResult::from_str),
+        )
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => AppError::BoardNotFound(payload.board_id),
+            e => e.into(),
+        })?;
+        if !updated {
+            return Err(AppError::ResultConflict);
+        }
+        return Ok(());
+    }
+    if let Some(n) = payload.game_number.filter(|n| *n > 0) {
+        return Err(AppError::InvalidGameNumber(n));
+    }
+    let agreed = payload.agreed.unwrap_or_default();
+    if agreed && result != GameResult::Draw {
+        return Err(AppError::InvalidAgreedDraw(payload.result.clone()));
+    }
+    let updated = update_game_result(
+        pool,
+        user_id,
+        tournament_id,
+        payload.round_id,
+        board_id,
+        result,
+        agreed,
+        payload.expected_result.as_deref().map(GameResult::from_str),
+    )
+    .await?;
+    if updated == 0 {
+        return Err(AppError::ResultConflict);
+    }
+    Ok(())
+}
+
+pub async fn add_organizer(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    payload: NewOrganizer,
+) -> Result<u32, AppError> {
+    let is_owner = check_user_tournament_ownership(pool, tournament_id, &claims).await?;
+    if !is_owner {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let role = match &payload.role {
+        Some(role) => TournamentRole::try_from(role.as_str())?,
+        None => TournamentRole::default(),
+    };
+    let user_id = auth_repo::find_user_id(pool, &payload.username)
+        .await?
+        .ok_or(AppError::UserNotFound(payload.username))?;
+    organizer_repo::add_organizer(pool, tournament_id, user_id, role).await?;
+    Ok(user_id)
+}
+
+pub async fn remove_organizer(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    user_id: u32,
+) -> Result<(), AppError> {
+    let is_owner = check_user_tournament_ownership(pool, tournament_id, &claims).await?;
+    if !is_owner {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let removed = organizer_repo::remove_organizer(pool, tournament_id, user_id).await?;
+    if removed == 0 {
+        return Err(AppError::OrganizerNotFound(user_id));
+    }
+    Ok(())
+}
+
+// Annulling results is reserved to admins, typically after a disqualification for cheating
+pub async fn annul_player_results(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    registration_id: u32,
+) -> Result<u64, AppError> {
+    if claims.role != "admin" {
+        return Err(AppError::InsufficientPermissions);
+    }
+    pairing_repo::annul_player_results(pool, claims.sub, tournament_id, registration_id)
+        .await?
+        .ok_or(AppError::RegistrationNotFound(registration_id))
+}
+
+pub async fn restore_player_results(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+    registration_id: u32,
+) -> Result<u64, AppError> {
+    if claims.role != "admin" {
+        return Err(AppError::InsufficientPermissions);
+    }
+    pairing_repo::restore_player_results(pool, claims.sub, tournament_id, registration_id)
+        .await?
+        .ok_or(AppError::RegistrationNotFound(registration_id))
+}
+
+pub async fn read_audit_log(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+) -> Result<Vec<DbAuditEntry>, AppError> {
+    let is_organizer = check_user_is_organizer(pool, tournament_id, &claims).await?;
+    if !is_organizer {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let entries = audit_repo::select_audit_log(pool, tournament_id).await?;
+    Ok(entries)
+}
+
+pub async fn pairing_constraints(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+) -> Result<PairingConstraints, AppError> {
+    if !check_user_is_organizer(pool, tournament_id, &claims).await? {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let tournament: Tournament = read_tournament(pool, tournament_id).await?.into();
+    tournament.pairing_constraints()
+}
+
+// Debugging aid for tuning the edge weights, it exposes the internals of the pairing engine
+pub async fn pairing_graph(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+) -> Result<PairingGraph, AppError> {
+    if claims.role != "admin" {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let tournament: Tournament = read_tournament(pool, tournament_id).await?.into();
+    tournament.pairing_graph()
+}
+
+pub async fn read_agreed_draws(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Claims,
+) -> Result<Vec<DbAgreedDraws>, AppError> {
+    let is_organizer = check_user_is_organizer(pool, tournament_id, &claims).await?;
+    if !is_organizer {
+        return Err(AppError::InsufficientPermissions);
+    }
+    let draws = pairing_repo::select_agreed_draws(pool, tournament_id).await?;
+    Ok(draws)
+}
+
+// Extra data for the tournament page, None unless the viewer organizes the tournament
+pub async fn read_organizer_data(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    tournament_id: u32,
+    claims: Option<&Claims>,
+) -> Result<Option<OrganizerData>, AppError> {
+    let Some(claims) = claims else {
+        return Ok(None);
+    };
+    if !check_user_is_organizer(pool, tournament_id, claims).await? {
+        return Ok(None);
+    }
+    let entries = audit_repo::select_audit_log(pool, tournament_id).await?;
+    Ok(Some(OrganizerData::new(entries)))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{
+        cmp::Ordering,
+        collections::{BTreeMap, HashMap},
+    };
+
+    use itertools::Itertools;
+
+    use crate::{
+        errors::AppError,
+        models::tournament::{
+            Color, ColorCandidate, ColorRule, GameResult, HistoryItem, NewPairings, Player,
+            PlayerName, PlayerStanding, PlayerStatus, Tiebreak, Title, Tournament, Visibility,
+            allocate_colors, is_valid_time_control,
+        },
+        repositories::{playoff_repo::DbPlayoffGame, section_repo::DbSection},
+        responses::{EdgeWeight, TournamentStats},
+    };
+
+    use super::{
+        FixedPairings, InactiveScores, RECENT_OPPONENT_PENALTY, assign_colors, edge_weight, export,
+        max_recommended_rounds, select_bye, validate_time_control,
+    };
+
+    fn test_tournament(players: Vec<Player>) -> Tournament {
+        Tournament {
+            id: 1,
+            name: "Test Tournament".to_string(),
+            time_category: "standard".to_string(),
+            players: players.into_iter().map(|p| (p.id, p)).collect(),
+            pairings: vec![],
+            byes: vec![],
+            results: vec![],
+            num_rounds: 5,
+            start_date: 0,
+            federation: "FID".to_string(),
+            user_id: 0,
+            username: "test".to_string(),
+            organizers: vec![],
+            sections: vec![],
+            playoff: vec![],
+            max_float_gap: None,
+            registration_open: true,
+            late_entry_default_score: 0,
+            unrated_default_rating: None,
+            board_offset: 0,
+            allow_repeat_pairings: false,
+            visibility: Visibility::Public,
+            requested_bye_score: 1,
+            games_per_pairing: 1,
+            tiebreaks: Tiebreak::DEFAULT.to_vec(),
+            max_players: None,
+            broadcast_boards: 0,
+            self_registration: false,
+            allow_three_same_colors: false,
+            time_control: None,
+            avoid_recent_opponents: false,
+            allow_rematch_reversed_colors: false,
+            avoid_last_round_contender_bye: false,
+            recent_meetings: HashMap::new(),
+            updated_at: 0,
+            end_date: None,
+            url: None,
+        }
+    }
+
+    fn seeded_player(id: u32, name: &str, rating: u32) -> Player {
+        Player {
+            id,
+            name: PlayerName::from(name),
+            rating,
+            ..Default::default()
+        }
+    }
+
+    // Players 1 to `count`, rated in id order
+    fn numbered_players(count: u32) -> Vec<Player> {
+        (1..=count)
+            .map(|id| seeded_player(id, &format!("Player, {}", id), 2000 - id * 10))
+            .collect()
+    }
+
+    #[test]
+    fn test_inactive_scores_invalid_result() {
+        let scores = InactiveScores::try_from(vec![(1, "forfeit".to_string())]);
+        assert!(matches!(
+            scores,
+            Err(AppError::InvalidPlayerScore(s)) if s == "forfeit"
+        ));
+    }
+
+    #[test]
+    fn test_inactive_scores_duplicate_player() {
+        let scores =
+            InactiveScores::try_from(vec![(1, "win".to_string()), (1, "draw".to_string())]);
+        assert!(matches!(scores, Err(AppError::DuplicatePlayerResult(1))));
+    }
+
+    #[test]
+    fn test_inactive_scores_player_ids() {
+        let mut inactive = seeded_player(2, "Inactive, Ian", 1800);
+        inactive.status = PlayerStatus::Inactive;
+        let tournament = test_tournament(vec![seeded_player(1, "Active, Ann", 2000), inactive]);
+        let scores = InactiveScores::try_from(vec![(2, "draw".to_string())]).unwrap();
+        assert!(scores.validate(&tournament).is_ok());
+        // Active player
+        let scores = InactiveScores::try_from(vec![(1, "draw".to_string())]).unwrap();
+        assert!(matches!(
+            scores.validate(&tournament),
+            Err(AppError::InvalidPlayerId(1))
+        ));
+        // Unknown player
+        let scores = InactiveScores::try_from(vec![(3, "loss".to_string())]).unwrap();
+        assert!(matches!(
+            scores.validate(&tournament),
+            Err(AppError::InvalidPlayerId(3))
+        ));
+    }
+
+    #[test]
+    fn test_tpn_unrated_players() {
+        let mut titled = seeded_player(5, "Omega, Olaf", 0);
+        titled.title = Title::FM;
+        let mut tournament = test_tournament(vec![
+            seeded_player(1, "Zeta, Zed", 0),
+            seeded_player(2, "Rated, Low", 1500),
+            seeded_player(3, "Alpha, Adam", 0),
+            seeded_player(4, "Rated, High", 2100),
+            titled,
+        ]);
+        // Unrated players go last, sorted by name regardless of title
+        let order: Vec<usize> = (1..=5).map(|id| tournament.player_tpn(id)).collect();
+        assert_eq!(order, vec![4, 1, 2, 0, 3]);
+        // With a default rating they are seeded among the rated players, title first
+        tournament.unrated_default_rating = Some(1800);
+        let order: Vec<usize> = (1..=5).map(|id| tournament.player_tpn(id)).collect();
+        assert_eq!(order, vec![3, 4, 2, 0, 1]);
+    }
+
+    #[test]
+    fn test_max_recommended_rounds() {
+        let rounds: Vec<u32> = [1, 2, 3, 8, 9, 100, 1000]
+            .into_iter()
+            .map(max_recommended_rounds)
+            .collect();
+        assert_eq!(rounds, vec![0, 1, 2, 3, 4, 7, 10]);
+    }
+
+    #[test]
+    fn test_tpn_title_on_equal_rating() {
+        let mut gm = seeded_player(2, "Zeta, Zed", 2400);
+        gm.title = Title::GM;
+        let tournament = test_tournament(vec![seeded_player(1, "Alpha, Adam", 2400), gm]);
+        assert_eq!(tournament.player_tpn(2), 0);
+        assert_eq!(tournament.player_tpn(1), 1);
+    }
+
+    #[test]
+    fn test_time_control() {
+        for valid in ["90+30", "15+10", "3+2", "60", "40/90+30:30+30", "40/120:60"] {
+            assert!(is_valid_time_control(valid), "{}", valid);
+        }
+        for invalid in ["", "G/90", "90 + 30", "90+", "+30", "90+30:", "90min+30sec"] {
+            assert!(!is_valid_time_control(invalid), "{}", invalid);
+        }
+        assert_eq!(
+            validate_time_control(Some(" 90+30 ".to_string())).unwrap(),
+            Some("90+30".to_string())
+        );
+        assert_eq!(validate_time_control(Some(" ".to_string())).unwrap(), None);
+        assert!(matches!(
+            validate_time_control(Some("90'".to_string())),
+            Err(AppError::InvalidTimeControl(t)) if t == "90'"
+        ));
+    }
+
+    #[test]
+    fn test_title_prestige() {
+        let ranking = [
+            "GM", "IM", "WGM", "FM", "WIM", "CM", "WFM", "WCM", "NM", "WNM", "",
+        ]
+        .map(|t| Title::from_str(t).prestige());
+        assert!(ranking.windows(2).all(|w| w[0] > w[1]));
+        let mut wgm = seeded_player(1, "Alpha, Adam", 2400);
+        wgm.title = Title::WGM;
+        let mut im = seeded_player(2, "Zeta, Zed", 2400);
+        im.title = Title::IM;
+        let mut fm = seeded_player(3, "Beta, Bea", 2400);
+        fm.title = Title::FM;
+        let tournament = test_tournament(vec![wgm, im, fm]);
+        let order: Vec<usize> = (1..=3).map(|id| tournament.player_tpn(id)).collect();
+        assert_eq!(order, vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn test_points_for() {
+        let cases = [
+            (GameResult::Ongoing, 0, 0),
+            (GameResult::WhiteWins, 2, 0),
+            (GameResult::Draw, 1, 1),
+            (GameResult::BlackWins, 0, 2),
+            (GameResult::DoubleLoss, 0, 0),
+            (GameResult::DoubleForfeit, 0, 0),
+        ];
+        for (result, white, black) in cases {
+            assert_eq!(result.points_for(Color::White), white, "{:?} white", result);
+            assert_eq!(result.points_for(Color::Black), black, "{:?} black", result);
+        }
+    }
+
+    #[test]
+    fn test_bye_from_bottom() {
+        let players = numbered_players(3);
+        let mut tournament = test_tournament(players);
+        let round = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            )
+            .unwrap();
+        // The lowest seed sits out the first round
+        assert!(
+            round
+                .gaps
+                .iter()
+                .any(|gap| gap.is_bye && gap.player_id == 3)
+        );
+        play_round(&mut tournament, &round);
+        let (winner, loser) = tournament.pairings[0][0];
+        let (winner, loser) = (winner as u32, loser as u32);
+        let round = tournament
+            .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+            .unwrap();
+        // Both the winner and player 3 have a point, the loser alone is at the bottom
+        let bye: Vec<u32> = round
+            .gaps
+            .iter()
+            .filter(|gap| gap.is_bye)
+            .map(|gap| gap.player_id)
+            .collect();
+        assert_eq!(bye, vec![loser]);
+        let candidates: Vec<&Player> = tournament.players.values().collect();
+        assert!(tournament.check_bye_from_bottom(&candidates, loser).is_ok());
+        assert!(matches!(
+            tournament.check_bye_from_bottom(&candidates, winner),
+            Err(AppError::ByeNotFromBottom { player_id, lower })
+                if player_id == winner && lower == vec![loser]
+        ));
+        // Player 3 already had a bye, the loser cannot replace them
+        assert!(tournament.check_bye_from_bottom(&candidates, 3).is_err());
+    }
+
+    #[test]
+    fn test_predicted_byes() {
+        let players = numbered_players(5);
+        let mut tournament = test_tournament(players);
+        for _ in 0..3 {
+            let predicted = tournament.predicted_byes().unwrap();
+            let round = if tournament.pairings.is_empty() {
+                tournament.generate_first_round_pairings(
+                    InactiveScores::new(),
+                    Color::White,
+                    &FixedPairings::default(),
+                )
+            } else {
+                tournament
+                    .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+            }
+            .unwrap();
+            let byes: Vec<u32> = round
+                .gaps
+                .iter()
+                .filter(|gap| gap.is_bye)
+                .map(|gap| gap.player_id)
+                .collect();
+            assert_eq!(predicted, byes);
+            play_round(&mut tournament, &round);
+        }
+        tournament.players.get_mut(&5).unwrap().status = PlayerStatus::Inactive;
+        assert!(tournament.predicted_byes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_double_round_robin() {
+        let players = numbered_players(4);
+        let mut tournament = test_tournament(players);
+        tournament.num_rounds = 6;
+        tournament.allow_rematch_reversed_colors = true;
+        let round = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            )
+            .unwrap();
+        play_round(&mut tournament, &round);
+        for _ in 1..6 {
+            let round = tournament
+                .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+                .unwrap();
+            assert_eq!(round.pairings.len(), 2);
+            play_round(&mut tournament, &round);
+            if tournament.current_round() == 3 {
+                // Every pair met once, without the flag the second cycle cannot be paired
+                let mut single = tournament.clone();
+                single.allow_rematch_reversed_colors = false;
+                assert!(matches!(
+                    single.generate_next_round_pairings(
+                        InactiveScores::new(),
+                        &FixedPairings::default()
+                    ),
+                    Err(AppError::PairingFailed { .. })
+                ));
+            }
+        }
+        // Every pair met twice, once with each color
+        for (p1, p2) in (1..=4).tuple_combinations() {
+            let games: Vec<Color> = tournament.players[&p1]
+                .history
+                .iter()
+                .filter_map(|item| match item {
+                    HistoryItem::Game {
+                        opponent_id, color, ..
+                    } if *opponent_id == p2 => Some(*color),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(games.len(), 2, "{} vs {}", p1, p2);
+            assert_ne!(games[0], games[1], "{} vs {}", p1, p2);
+        }
+        // The second cycle is explained as reversed rematches, not as arbiter decisions
+        for round in 3..6 {
+            let decisions = tournament.color_decisions(round).unwrap();
+            assert!(
+                decisions
+                    .iter()
+                    .all(|d| d.rule == ColorRule::ReversedRematch),
+                "round {}: {:?}",
+                round,
+                decisions.iter().map(|d| d.rule).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_with_result() {
+        let players = numbered_players(4);
+        let mut tournament = test_tournament(players);
+        let round = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            )
+            .unwrap();
+        play_round(&mut tournament, &round);
+        let (white, black) = tournament.pairings[0][0];
+        let simulated = tournament
+            .clone()
+            .with_result(0, 0, GameResult::BlackWins)
+            .unwrap();
+        assert_eq!(simulated.results[0][0], GameResult::BlackWins);
+        assert_eq!(
+            simulated.players[&(white as u32)].history[0],
+            HistoryItem::Game {
+                opponent_id: black as u32,
+                color: Color::White,
+                result: GameResult::BlackWins,
+            }
+        );
+        let top = |t: &Tournament| t.standings().last().unwrap()[0].player_id;
+        assert_eq!(top(&simulated), black as u32);
+        // The original is left as played
+        assert_eq!(tournament.results[0][0], GameResult::WhiteWins);
+        assert_eq!(top(&tournament), white as u32);
+        assert!(matches!(
+            tournament.clone().with_result(0, 2, GameResult::Draw),
+            Err(AppError::BoardNotFound(2))
+        ));
+        assert!(matches!(
+            tournament.with_result(1, 0, GameResult::Draw),
+            Err(AppError::RoundNotFound(1))
+        ));
+    }
+
+    #[test]
+    fn test_round_coverage() {
+        let players = numbered_players(5);
+        let mut tournament = test_tournament(players);
+        tournament.players.get_mut(&5).unwrap().status = PlayerStatus::Inactive;
+        let round = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            )
+            .unwrap();
+        assert!(
+            tournament
+                .check_round_coverage(&round.pairings, &round.gaps)
+                .is_ok()
+        );
+        // A matching dropping players 3 and 4
+        let (pairings, gaps) =
+            tournament.process_pairings(vec![(1, 2)], vec![], InactiveScores::new());
+        assert!(matches!(
+            tournament.check_round_coverage(&pairings, &gaps),
+            Err(AppError::PairingInvariantViolated { missing, duplicated })
+                if missing == vec![3, 4] && duplicated.is_empty()
+        ));
+        // Player 2 on two boards, an unknown player and player 4 left out
+        let (pairings, gaps) = tournament.process_pairings(
+            vec![(1, 2), (3, 2), (9, 4)],
+            vec![],
+            InactiveScores::new(),
+        );
+        assert!(matches!(
+            tournament.check_round_coverage(&pairings, &gaps),
+            Err(AppError::PairingInvariantViolated { missing, duplicated })
+                if missing.is_empty() && duplicated == vec![2, 9]
+        ));
+        let (pairings, gaps) =
+            tournament.process_pairings(vec![(1, 2), (3, 2)], vec![], InactiveScores::new());
+        assert!(matches!(
+            tournament.check_round_coverage(&pairings, &gaps),
+            Err(AppError::PairingInvariantViolated { missing, duplicated })
+                if missing == vec![4] && duplicated == vec![2]
+        ));
+    }
+
+    #[test]
+    fn test_draw_notation() {
+        assert_eq!(GameResult::Draw.to_string(), "1/2-1/2");
+        assert_eq!(GameResult::from_str("1/2-1/2"), GameResult::Draw);
+        assert_eq!(GameResult::from_str("1/2-1/2").to_string(), "1/2-1/2");
+        // Stored before the canonical notation changed
+        assert_eq!(GameResult::from_str("=-=").to_string(), "1/2-1/2");
+    }
+
+    #[test]
+    fn test_result_notations() {
+        let cases = [
+            ("1-0", GameResult::WhiteWins),
+            ("1 - 0", GameResult::WhiteWins),
+            ("1:0", GameResult::WhiteWins),
+            ("White", GameResult::WhiteWins),
+            ("1/2-1/2", GameResult::Draw),
+            ("½ - ½", GameResult::Draw),
+            ("0.5-0.5", GameResult::Draw),
+            ("0.5:0.5", GameResult::Draw),
+            ("=-=", GameResult::Draw),
+            ("DRAW", GameResult::Draw),
+            ("0-1", GameResult::BlackWins),
+            ("0 : 1", GameResult::BlackWins),
+            ("black", GameResult::BlackWins),
+            ("0-0", GameResult::DoubleLoss),
+            ("0F-0F", GameResult::DoubleForfeit),
+            ("0f - 0f", GameResult::DoubleForfeit),
+            ("*", GameResult::Ongoing),
+            ("", GameResult::Ongoing),
+            // Ambiguous, the side that won is unknown
+            ("1", GameResult::Ongoing),
+            ("+", GameResult::Ongoing),
+            ("+/-", GameResult::Ongoing),
+            ("W", GameResult::Ongoing),
+            ("½", GameResult::Ongoing),
+        ];
+        for (notation, result) in cases {
+            assert_eq!(GameResult::from_str(notation), result, "{:?}", notation);
+        }
+    }
+
+    #[test]
+    fn test_scores_agree_with_standings() {
+        let results = [
+            GameResult::WhiteWins,
+            GameResult::Draw,
+            GameResult::BlackWins,
+            GameResult::DoubleLoss,
+            GameResult::DoubleForfeit,
+        ];
+        let mut players: Vec<Player> = numbered_players(4);
+        // 1 and 2 play every result with 1 as white, 3 and 4 with 3 as black
+        for result in results {
+            for (white, black) in [(0, 1), (3, 2)] {
+                let (white_id, black_id) = (players[white].id, players[black].id);
+                players[white].history.push(HistoryItem::Game {
+                    opponent_id: black_id,
+                    color: Color::White,
+                    result,
+                });
+                players[black].history.push(HistoryItem::Game {
+                    opponent_id: white_id,
+                    color: Color::Black,
+                    result,
+                });
+            }
+        }
+        let mut tournament = test_tournament(players);
+        tournament.num_rounds = results.len();
+        tournament.pairings = vec![vec![(1, 2), (4, 3)]; results.len()];
+        let scores: HashMap<u32, u32> = tournament
+            .players
+            .values()
+            .map(|p| (p.id, p.tournament_score()))
+            .collect();
+        assert_eq!(scores[&1], 3);
+        assert_eq!(scores[&2], 3);
+        let standings = tournament.standings();
+        let last = standings.last().unwrap();
+        assert_eq!(last.len(), 4);
+        for standing in last {
+            assert_eq!(standing.score, scores[&standing.player_id]);
+            // Every opponent finished on 3 points, the double forfeit is left out
+            assert_eq!(standing.buchholz, 3 * 4);
+        }
+    }
+
+    #[test]
+    fn test_scoring_call_sites_agree() {
+        let mut players = numbered_players(4);
+        let game = |opponent_id, color, result| HistoryItem::Game {
+            opponent_id,
+            color,
+            result,
+        };
+        players[0].history = vec![
+            game(2, Color::White, GameResult::Draw),
+            game(3, Color::White, GameResult::WhiteWins),
+            game(4, Color::Black, GameResult::BlackWins),
+        ];
+        players[1].history = vec![
+            game(1, Color::Black, GameResult::Draw),
+            HistoryItem::Bye,
+            game(3, Color::White, GameResult::DoubleForfeit),
+        ];
+        players[2].history = vec![
+            HistoryItem::Bye,
+            game(1, Color::Black, GameResult::WhiteWins),
+            game(2, Color::Black, GameResult::DoubleForfeit),
+        ];
+        players[3].history = vec![
+            HistoryItem::NotPaired { score: 1 },
+            HistoryItem::NotPaired { score: 0 },
+            game(1, Color::White, GameResult::BlackWins),
+        ];
+        let mut tournament = test_tournament(players);
+        tournament.num_rounds = 3;
+        tournament.pairings = vec![vec![(1, 2)], vec![(1, 3)], vec![(4, 1), (2, 3)]];
+        let scores: HashMap<u32, u32> = tournament
+            .players
+            .values()
+            .map(|p| (p.id, p.tournament_score()))
+            .collect();
+        assert_eq!(scores, HashMap::from([(1, 5), (2, 3), (3, 2), (4, 1)]));
+        let standings = tournament.standings();
+        for standing in standings.last().unwrap() {
+            let player = &tournament.players[&standing.player_id];
+            assert_eq!(standing.score, scores[&player.id]);
+            let buchholz: u32 = player
+                .history
+                .iter()
+                .filter_map(|item| item.opponent(false))
+                .map(|id| scores[&id])
+                .sum();
+            assert_eq!(standing.buchholz, buchholz);
+        }
+    }
+
+    #[test]
+    fn test_forfeit_opponent_excluded_from_buchholz() {
+        let mut players: Vec<Player> = numbered_players(4);
+        let game = |opponent_id, color, result| HistoryItem::Game {
+            opponent_id,
+            color,
+            result,
+        };
+        players[0].history = vec![
+            game(2, Color::White, GameResult::DoubleForfeit),
+            game(3, Color::White, GameResult::Draw),
+        ];
+        players[1].history = vec![
+            game(1, Color::Black, GameResult::DoubleForfeit),
+            game(4, Color::White, GameResult::WhiteWins),
+        ];
+        players[2].history = vec![
+            game(4, Color::White, GameResult::WhiteWins),
+            game(1, Color::Black, GameResult::Draw),
+        ];
+        players[3].history = vec![
+            game(3, Color::Black, GameResult::WhiteWins),
+            game(2, Color::Black, GameResult::WhiteWins),
+        ];
+        let mut tournament = test_tournament(players);
+        tournament.pairings = vec![vec![(1, 2), (3, 4)], vec![(1, 3), (2, 4)]];
+        let standings = tournament.standings();
+        let buchholz: HashMap<u32, u32> = standings
+            .last()
+            .unwrap()
+            .iter()
+            .map(|s| (s.player_id, s.buchholz))
+            .collect();
+        // Player 1 only counts player 3, player 2 only player 4
+        assert_eq!(buchholz[&1], 3);
+        assert_eq!(buchholz[&2], 0);
+        assert_eq!(buchholz[&3], 1);
+        // The forfeit still counts as played for pairing, no rematch
+        assert!(tournament.players[&1].has_played(2));
+        let round = tournament
+            .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+            .unwrap();
+        let pairs: Vec<(u32, u32)> = round
+            .pairings
+            .iter()
+            .map(|p| (p.white_id.min(p.black_id), p.white_id.max(p.black_id)))
+            .sorted()
+            .collect();
+        assert_eq!(pairs, vec![(1, 4), (2, 3)]);
+    }
+
+    #[test]
+    fn test_sort_boards_by_combined_score() {
+        let scores = [0, 3, 3, 0, 4, 0];
+        let players = scores
+            .iter()
+            .enumerate()
+            .map(|(i, score)| {
+                player_with_history(i as u32 + 1, vec![HistoryItem::NotPaired { score: *score }])
+            })
+            .collect();
+        let tournament = test_tournament(players);
+        let mut pairings = vec![(1, 4), (5, 6), (2, 3)];
+        tournament.sort_boards(&mut pairings);
+        // 3 + 3 beats 4 + 0 even though player 5 has the highest individual score
+        assert_eq!(pairings[0], (2, 3));
+        assert_eq!(pairings, vec![(2, 3), (5, 6), (1, 4)]);
+        // Equal combined scores are ordered by the best seeded player
+        let mut pairings = vec![(3, 6), (2, 4)];
+        tournament.sort_boards(&mut pairings);
+        assert_eq!(pairings, vec![(2, 4), (3, 6)]);
+    }
+
+    #[test]
+    fn test_sections_paired_independently() {
+        let players = numbered_players(6)
+            .into_iter()
+            .map(|mut player| {
+                player.section_id = Some(if player.id % 2 == 0 { 1 } else { 2 });
+                player
+            })
+            .collect();
+        let mut tournament = test_tournament(players);
+        tournament.sections = vec![
+            DbSection {
+                id: 1,
+                name: "Open".to_string(),
+            },
+            DbSection {
+                id: 2,
+                name: "U1800".to_string(),
+            },
+        ];
+        let pairings = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            )
+            .unwrap();
+        assert_eq!(pairings.pairings.len(), 2);
+        for pairing in pairings.pairings.iter() {
+            assert_eq!(
+                tournament.players[&pairing.white_id].section_id,
+                tournament.players[&pairing.black_id].section_id
+            );
+        }
+        // Boards of the first section come first
+        assert_eq!(
+            tournament.players[&pairings.pairings[0].white_id].section_id,
+            Some(1)
+        );
+        // One bye for each odd section
+        let byes: Vec<Option<u32>> = pairings
+            .gaps
+            .iter()
+            .filter(|g| g.is_bye)
+            .map(|g| tournament.players[&g.player_id].section_id)
+            .sorted()
+            .collect();
+        assert_eq!(byes, vec![Some(1), Some(2)]);
+        // Fixed pairs stay within a section too
+        let fixed = FixedPairings {
+            pairs: vec![(1, 2)],
+            ..Default::default()
+        };
+        assert!(matches!(
+            tournament.generate_first_round_pairings(InactiveScores::new(), Color::White, &fixed),
+            Err(AppError::FixedPairingAcrossSections { white: 1, black: 2 })
+        ));
+    }
+
+    fn player_with_history(id: u32, history: Vec<HistoryItem>) -> Player {
+        let mut player = seeded_player(id, &format!("Player, {}", id), 2000 - id * 10);
+        player.history = history;
+        player
+    }
+
+    #[test]
+    fn test_bye_skips_players_with_a_bye() {
+        // Player 3 is the lowest standing player but already had a bye
+        let tournament = test_tournament(vec![
+            player_with_history(1, vec![HistoryItem::NotPaired { score: 2 }]),
+            player_with_history(2, vec![HistoryItem::NotPaired { score: 0 }]),
+            player_with_history(3, vec![HistoryItem::Bye]),
+            player_with_history(4, vec![HistoryItem::NotPaired { score: 0 }]),
+        ]);
+        let tpn = |id| tournament.player_tpn(id);
+        let candidates = |ids: &[u32]| -> HashMap<u32, &Player> {
+            ids.iter()
+                .map(|id| (*id, &tournament.players[id]))
+                .collect()
+        };
+        // Same score for 2 and 4, the lowest seed gets the bye
+        assert_eq!(select_bye(&candidates(&[2, 3, 4]), tpn, false), Some(4));
+        assert_eq!(select_bye(&candidates(&[1, 2, 3]), tpn, false), Some(2));
+    }
+
+    #[test]
+    fn test_no_bye_for_even_fields() {
+        let mut tournament = test_tournament(vec![
+            player_with_history(1, vec![HistoryItem::NotPaired { score: 2 }]),
+            player_with_history(2, vec![HistoryItem::NotPaired { score: 0 }]),
+            player_with_history(3, vec![HistoryItem::NotPaired { score: 1 }]),
+            player_with_history(4, vec![HistoryItem::NotPaired { score: 0 }]),
+        ]);
+        let candidates: HashMap<u32, &Player> =
+            tournament.players.iter().map(|(id, p)| (*id, p)).collect();
+        assert_eq!(select_bye(&candidates, |id| id as usize, false), None);
+        // Withdrawn players do not count, the field becomes odd
+        tournament.players.get_mut(&4).unwrap().status = PlayerStatus::Inactive;
+        let candidates: HashMap<u32, &Player> =
+            tournament.players.iter().map(|(id, p)| (*id, p)).collect();
+        assert_eq!(select_bye(&candidates, |id| id as usize, false), Some(2));
+        assert_eq!(select_bye(&HashMap::new(), |id| id as usize, false), None);
+    }
+
+    #[test]
+    fn test_bye_when_everyone_had_a_bye() {
+        let tournament = test_tournament(vec![
+            player_with_history(1, vec![HistoryItem::Bye, HistoryItem::Bye]),
+            player_with_history(
+                2,
+                vec![HistoryItem::Bye, HistoryItem::NotPaired { score: 0 }],
+            ),
+            player_with_history(
+                3,
+                vec![HistoryItem::NotPaired { score: 1 }, HistoryItem::Bye],
+            ),
+        ]);
+        let candidates: HashMap<u32, &Player> =
+            tournament.players.iter().map(|(id, p)| (*id, p)).collect();
+        // Everyone had a bye, player 1 had two so the lowest standing of 2 and 3 gets it
+        assert_eq!(
+            select_bye(&candidates, |id| tournament.player_tpn(id), false),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_last_round_bye_avoids_contenders() {
+        let not_paired = |score| HistoryItem::NotPaired { score };
+        // Last of four rounds: 1 leads on 3 points, 2 is half a point behind. 3 is out of
+        // contention but already had a bye
+        let mut tournament = test_tournament(vec![
+            player_with_history(1, vec![not_paired(2), not_paired(2), not_paired(2)]),
+            player_with_history(2, vec![not_paired(2), not_paired(2), not_paired(1)]),
+            player_with_history(3, vec![HistoryItem::Bye, not_paired(0), not_paired(0)]),
+        ]);
+        tournament.num_rounds = 4;
+        tournament.pairings = vec![vec![]; 3];
+        tournament.results = vec![vec![]; 3];
+        assert_eq!(tournament.predicted_byes().unwrap(), vec![2]);
+        // The contender keeps playing, 3 gets a second bye
+        tournament.avoid_last_round_contender_bye = true;
+        assert_eq!(tournament.predicted_byes().unwrap(), vec![3]);
+        // Everybody in contention, the usual order applies
+        tournament.players.get_mut(&3).unwrap().history[1] = not_paired(2);
+        assert_eq!(tournament.predicted_byes().unwrap(), vec![2]);
+        // Only the last round is concerned
+        tournament.players.get_mut(&3).unwrap().history[1] = not_paired(0);
+        tournament.num_rounds = 5;
+        assert_eq!(tournament.predicted_byes().unwrap(), vec![2]);
+    }
+
+    // Plays a generated round where white always wins
+    fn play_round(tournament: &mut Tournament, round: &NewPairings) {
+        for pairing in round.pairings.iter() {
+            let white = tournament.players.get_mut(&pairing.white_id).unwrap();
+            white.history.push(HistoryItem::Game {
+                opponent_id: pairing.black_id,
+                color: Color::White,
+                result: GameResult::WhiteWins,
+            });
+            let black = tournament.players.get_mut(&pairing.black_id).unwrap();
+            black.history.push(HistoryItem::Game {
+                opponent_id: pairing.white_id,
+                color: Color::Black,
+                result: GameResult::WhiteWins,
+            });
+        }
+        let mut byes = Vec::new();
+        for gap in round.gaps.iter() {
+            let player = tournament.players.get_mut(&gap.player_id).unwrap();
+            if gap.is_bye {
+                player.history.push(HistoryItem::Bye);
+                byes.push(gap.player_id);
+            } else {
+                player
+                    .history
+                    .push(HistoryItem::NotPaired { score: gap.score });
+            }
+        }
+        tournament.pairings.push(
+            round
+                .pairings
+                .iter()
+                .map(|p| (p.white_id as usize, p.black_id as usize))
+                .collect(),
+        );
+        tournament
+            .results
+            .push(vec![GameResult::WhiteWins; round.pairings.len()]);
+        tournament.byes.push(byes);
+    }
+
+    #[test]
+    fn test_byes_rotate() {
+        let mut tournament = test_tournament(numbered_players(5));
+        let round = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            )
+            .unwrap();
+        play_round(&mut tournament, &round);
+        for _ in 0..2 {
+            let round = tournament
+                .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+                .unwrap();
+            play_round(&mut tournament, &round);
+        }
+        let bye_players: Vec<u32> = tournament.byes.iter().flatten().copied().collect();
+        assert_eq!(bye_players.len(), 3);
+        // Three different players got a bye, nobody got a second one
+        assert_eq!(bye_players.iter().unique().count(), 3);
+        assert!(tournament.players.values().all(|p| p.byes() <= 1));
+    }
+
+    #[test]
+    fn test_repeat_pairing_fallback() {
+        let mut tournament = test_tournament(vec![
+            seeded_player(1, "Player, 1", 2000),
+            seeded_player(2, "Player, 2", 1900),
+        ]);
+        let round = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            )
+            .unwrap();
+        play_round(&mut tournament, &round);
+        // The only possible pairing is a rematch
+        assert!(matches!(
+            tournament
+                .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default()),
+            Err(AppError::PairingFailed { .. })
+        ));
+        tournament.allow_repeat_pairings = true;
+        let round = tournament
+            .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+            .unwrap();
+        assert_eq!(round.pairings.len(), 1);
+        // Colors alternate in the rematch
+        assert_eq!(round.pairings[0].white_id, 2);
+        assert_eq!(round.pairings[0].black_id, 1);
+    }
+
+    #[test]
+    fn test_three_same_colors_fallback() {
+        let mut tournament = test_tournament(numbered_players(4));
+        // Players 1 and 4 get white twice, 2 and 3 black twice
+        let fixed = |pairs: Vec<(u32, u32)>| FixedPairings {
+            pairs,
+            ..FixedPairings::default()
+        };
+        let round = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &fixed(vec![(1, 2), (4, 3)]),
+            )
+            .unwrap();
+        play_round(&mut tournament, &round);
+        let round = tournament
+            .generate_next_round_pairings(InactiveScores::new(), &fixed(vec![(1, 3), (4, 2)]))
+            .unwrap();
+        play_round(&mut tournament, &round);
+        // The only new opponents have the same two colors in a row
+        assert!(matches!(
+            tournament
+                .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default()),
+            Err(AppError::PairingFailed { .. })
+        ));
+        tournament.allow_three_same_colors = true;
+        let round = tournament
+            .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+            .unwrap();
+        let pairs: Vec<(u32, u32)> = round
+            .pairings
+            .iter()
+            .map(|p| (p.white_id.min(p.black_id), p.white_id.max(p.black_id)))
+            .sorted()
+            .collect();
+        // No rematch, a third color in a row instead
+        assert_eq!(pairs, vec![(1, 4), (2, 3)]);
+        tournament.allow_three_same_colors = false;
+        let constraints = tournament.pairing_constraints().unwrap();
+        assert_eq!(constraints.round, 2);
+        assert_eq!(constraints.players, 4);
+        assert!(constraints.byes.is_empty());
+        assert!(
+            constraints
+                .connectivity
+                .iter()
+                .all(|c| c.played == 2 && c.available == 0)
+        );
+        assert_eq!(constraints.color_balance, BTreeMap::from([(-2, 2), (2, 2)]));
+        assert!(!constraints.perfect_matching);
+        assert!(!constraints.perfect_matching_with_fallbacks);
+        tournament.allow_three_same_colors = true;
+        assert!(
+            tournament
+                .pairing_constraints()
+                .unwrap()
+                .perfect_matching_with_fallbacks
+        );
+        play_round(&mut tournament, &round);
+        // The last two rounds of a 4 player, 5 round event are rematches
+        tournament.allow_repeat_pairings = true;
+        for _ in 0..2 {
+            let round = tournament
+                .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+                .unwrap();
+            assert_eq!(round.pairings.len(), 2);
+            play_round(&mut tournament, &round);
+        }
+        assert_eq!(tournament.pairings.len(), 5);
+    }
 
-                ranking.push(standing);
-                prev_scores.entry(player.id).and_modify(|prev| {
-                    prev.score += round_score;
-                    prev.progressive += standing.progressive;
-                });
-            }
-            for standing in ranking.iter_mut() {
-                let player = &self.players[&standing.player_id];
-                let opponents: Vec<&Player> = player
-                    .history
-                    .iter()
-                    .take(round as usize + 1)
-                    .filter_map(|item| match item {
-                        HistoryItem::Game {
-                            opponent_id,
-                            color: _,
-                            result: _,
-                        } => self.players.get(opponent_id),
-                        _ => None,
-                    })
-                    .collect();
-                let mut opponent_scores: Vec<u32> = opponents
-                    .iter()
-                    .map(|player| {
-                        player
-                            .history
-                            .iter()
-                            .take(round as usize + 1)
-                            .map(|item| match item {
-                                HistoryItem::NotPaired { score } => *score,
-                                HistoryItem::Bye => 2,
-                                HistoryItem::Game {
-                                    opponent_id: _,
-                                    color,
-                                    result,
-                                } => match (color, result) {
-                                    (Color::White, GameResult::WhiteWins) => 2,
-                                    (Color::Black, GameResult::BlackWins) => 2,
-                                    (_, GameResult::Draw) => 1,
-                                    _ => 0,
-                                },
-                            })
-                            .sum()
-                    })
-                    .collect();
-                opponent_scores.sort();
-                standing.buchholz = opponent_scores.iter().sum();
-                standing.cut_one_buchholz = opponent_scores.iter().skip(1).sum();
-                if opponent_scores.pop().is_some() {
-                    standing.median_buchholz = opponent_scores.iter().skip(1).sum();
-                } else {
-                    standing.median_buchholz = 0;
-                }
+    #[test]
+    fn test_pairing_failure_diagnostics() {
+        let mut tournament = test_tournament(numbered_players(4));
+        let round = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            )
+            .unwrap();
+        play_round(&mut tournament, &round);
+        for _ in 0..2 {
+            let round = tournament
+                .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+                .unwrap();
+            play_round(&mut tournament, &round);
+        }
+        // Everyone has played everyone else
+        match tournament
+            .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+        {
+            Err(AppError::PairingFailed { reason, unpaired }) => {
+                assert_eq!(
+                    reason,
+                    "all remaining players have already played each other"
+                );
+                assert_eq!(unpaired, vec![1, 2, 3, 4]);
             }
-            ranking.sort_by(|a, b| {
-                b.score
-                    .cmp(&a.score)
-                    .then_with(|| b.median_buchholz.cmp(&a.median_buchholz))
-                    .then_with(|| b.cut_one_buchholz.cmp(&a.cut_one_buchholz))
-                    .then_with(|| b.buchholz.cmp(&a.buchholz))
-                    .then_with(|| b.progressive.cmp(&a.progressive))
-            });
-            standings.push(ranking);
+            other => panic!(
+                "expected PairingFailed, got {:?}",
+                other.map(|r| r.pairings.len())
+            ),
         }
-        standings
     }
-}
 
-pub async fn end_tournament(
-    pool: &sqlx::Pool<sqlx::Sqlite>,
-    tournament_id: u32,
-    claims: Claims,
-) -> Result<i64, AppError> {
-    let has_permission = check_user_tournament_permissions(pool, tournament_id, claims).await?;
-    if !has_permission {
-        return Err(AppError::InsufficientPermissions);
+    #[test]
+    fn test_color_overrides() {
+        let mut tournament = test_tournament(numbered_players(5));
+        let round = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            )
+            .unwrap();
+        // Overrides already apply to the initial colors
+        let fixed = FixedPairings {
+            colors: vec![(round.pairings[0].white_id, Color::Black)],
+            ..Default::default()
+        };
+        let overridden = tournament
+            .generate_first_round_pairings(InactiveScores::new(), Color::White, &fixed)
+            .unwrap();
+        assert_eq!(overridden.pairings[0].white_id, round.pairings[0].black_id);
+        assert_eq!(overridden.pairings[1].white_id, round.pairings[1].white_id);
+        play_round(&mut tournament, &round);
+        let round = tournament
+            .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+            .unwrap();
+        let top = &round.pairings[0];
+        let bye = round.gaps.iter().find(|g| g.is_bye).unwrap().player_id;
+        // The white player of the top board is forced to play black
+        let fixed = FixedPairings {
+            colors: vec![(top.white_id, Color::Black)],
+            ..Default::default()
+        };
+        let overridden = tournament
+            .generate_next_round_pairings(InactiveScores::new(), &fixed)
+            .unwrap();
+        assert_eq!(overridden.pairings[0].white_id, top.black_id);
+        assert_eq!(overridden.pairings[0].black_id, top.white_id);
+        // Overrides matching the allocated color change nothing
+        let fixed = FixedPairings {
+            colors: vec![(top.black_id, Color::Black)],
+            ..Default::default()
+        };
+        let unchanged = tournament
+            .generate_next_round_pairings(InactiveScores::new(), &fixed)
+            .unwrap();
+        assert_eq!(unchanged.pairings[0].white_id, top.white_id);
+        let fixed = FixedPairings {
+            colors: vec![(bye, Color::White)],
+            ..Default::default()
+        };
+        assert!(matches!(
+            tournament.generate_next_round_pairings(InactiveScores::new(), &fixed),
+            Err(AppError::ColorOverrideNotPaired(id)) if id == bye
+        ));
     }
-    let tournament = get_tournament(pool, tournament_id).await.map_err(|e| {
-        tracing::error!("end_tournament (get_tournament): {:?}", e);
-        AppError::Unknown
-    })?;
-    if tournament.current_round < tournament.num_rounds {
-        return Err(AppError::CannotEndTournament);
+
+    fn player_with_colors(id: u32, colors: &[Color]) -> Player {
+        let history = colors
+            .iter()
+            .map(|color| HistoryItem::Game {
+                opponent_id: 0,
+                color: *color,
+                result: GameResult::Draw,
+            })
+            .collect();
+        player_with_history(id, history)
     }
-    let pairings = select_pairings(pool, tournament_id).await.map_err(|e| {
-        tracing::error!("end_tournament (select_tournament): {:?}", e);
-        AppError::Unknown
-    })?;
-    if pairings
-        .iter()
-        .map(|p| GameResult::from_str(p.result.as_ref().unwrap_or(&"*".to_string())))
-        .any(|r| r == GameResult::Ongoing)
-    {
-        return Err(AppError::RoundNotDone);
+
+    #[test]
+    fn test_assign_colors() {
+        let (w, b) = (Color::White, Color::Black);
+        let colors = |p1: &[Color], p2: &[Color], tpn_cmp| {
+            assign_colors(
+                &player_with_colors(1, p1),
+                &player_with_colors(2, p2),
+                tpn_cmp,
+            )
+        };
+        // Neither player has a game, the pairing order is kept
+        assert_eq!(colors(&[], &[], Ordering::Less), (1, 2));
+        assert_eq!(colors(&[], &[], Ordering::Greater), (1, 2));
+        // Only one player has a history, that player alternates
+        assert_eq!(colors(&[], &[b], Ordering::Less), (2, 1));
+        assert_eq!(colors(&[], &[w], Ordering::Less), (1, 2));
+        assert_eq!(colors(&[w], &[], Ordering::Less), (2, 1));
+        assert_eq!(colors(&[b], &[], Ordering::Less), (1, 2));
+        // Different last colors, both alternate
+        assert_eq!(colors(&[b, w], &[w, b], Ordering::Less), (2, 1));
+        assert_eq!(colors(&[w, b], &[b, w], Ordering::Less), (1, 2));
+        // Same last color, the player with more whites gets black whatever the tpn
+        assert_eq!(colors(&[w, w], &[b, w], Ordering::Greater), (2, 1));
+        assert_eq!(colors(&[b, w], &[w, w], Ordering::Less), (1, 2));
+        // Same last color and balance, the better seeded player gets black
+        assert_eq!(colors(&[b, w], &[b, w], Ordering::Less), (2, 1));
+        assert_eq!(colors(&[b, w], &[b, w], Ordering::Greater), (1, 2));
     }
-    tournament_repo::end_tournament(pool, tournament_id)
-        .await
-        .map_err(|e| {
-            tracing::error!("end_tournament (end_tournament): {:?}", e);
-            AppError::Unknown
-        })
-}
 
-pub async fn generate_next_pairings(
-    pool: &sqlx::Pool<sqlx::Sqlite>,
-    tournament_id: u32,
-    claims: Claims,
-    payload: NextPairings,
-) -> Result<NewPairings, AppError> {
-    let has_permission = check_user_tournament_permissions(pool, tournament_id, claims).await?;
-    if !has_permission {
-        return Err(AppError::InsufficientPermissions);
+    #[test]
+    fn test_color_decisions() {
+        fn candidate(id: u32, colors: &[Color]) -> ColorCandidate<'_> {
+            ColorCandidate { id, colors }
+        }
+        let (w, b) = (Color::White, Color::Black);
+        assert_eq!(
+            allocate_colors(candidate(1, &[w]), candidate(2, &[b]), Ordering::Less),
+            (2, 1, ColorRule::Alternation)
+        );
+        assert_eq!(
+            allocate_colors(candidate(1, &[w, w]), candidate(2, &[b, w]), Ordering::Less),
+            (2, 1, ColorRule::BalanceCorrection)
+        );
+        assert_eq!(
+            allocate_colors(candidate(1, &[b, w]), candidate(2, &[b, w]), Ordering::Less),
+            (2, 1, ColorRule::TpnTiebreak)
+        );
+
+        let mut tournament = test_tournament(numbered_players(4));
+        let round = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            )
+            .unwrap();
+        play_round(&mut tournament, &round);
+        let round = tournament
+            .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+            .unwrap();
+        play_round(&mut tournament, &round);
+        let first = tournament.color_decisions(0).unwrap();
+        assert!(first.iter().all(|d| d.rule == ColorRule::InitialColor));
+        let second = tournament.color_decisions(1).unwrap();
+        assert_eq!(second.len(), 2);
+        for decision in second.iter() {
+            // White won every game, winners meet winners with the same color history
+            assert_eq!(decision.rule, ColorRule::TpnTiebreak);
+            assert_eq!(decision.white_balance, decision.black_balance);
+            assert!(
+                tournament.player_tpn(decision.white_id) > tournament.player_tpn(decision.black_id)
+            );
+        }
+        // A board played the other way around was decided by the arbiter
+        let (white, black) = tournament.pairings[1][0];
+        tournament.pairings[1][0] = (black, white);
+        assert_eq!(
+            tournament.color_decisions(1).unwrap()[0].rule,
+            ColorRule::ArbiterDecision
+        );
+        assert!(matches!(
+            tournament.color_decisions(2),
+            Err(AppError::RoundNotFound(2))
+        ));
     }
-    let scores: InactiveScores = payload.inactive_scores.try_into()?;
-    let tournament = read_tournament(pool, tournament_id).await?;
-    let tournament: Tournament = tournament.into();
-    if tournament.players.len() < 2 {
-        return Err(AppError::InsufficientPlayers);
+
+    #[test]
+    fn test_double_forfeit() {
+        assert_eq!(GameResult::from_str("0F-0F"), GameResult::DoubleForfeit);
+        assert_eq!(GameResult::DoubleForfeit.to_string(), "0F-0F");
+        assert_ne!(GameResult::DoubleForfeit, GameResult::DoubleLoss);
+        let game = |opponent_id, color| HistoryItem::Game {
+            opponent_id,
+            color,
+            result: GameResult::DoubleForfeit,
+        };
+        let mut tournament = test_tournament(vec![
+            player_with_history(1, vec![game(2, Color::White)]),
+            player_with_history(2, vec![game(1, Color::Black)]),
+        ]);
+        tournament.pairings.push(vec![(1, 2)]);
+        tournament.results.push(vec![GameResult::DoubleForfeit]);
+        // Neither player scores nor gets a color
+        let standings = tournament.standings();
+        assert!(standings[0].iter().all(|s| s.score == 0));
+        assert!(tournament.players[&1].color_history().is_empty());
+        assert!(tournament.players[&2].colors_before(1).is_empty());
+        assert_eq!(
+            GameResult::aggregate(&[GameResult::DoubleForfeit, GameResult::DoubleForfeit]),
+            GameResult::DoubleForfeit
+        );
+        assert_eq!(
+            GameResult::aggregate(&[GameResult::DoubleForfeit, GameResult::DoubleLoss]),
+            GameResult::DoubleLoss
+        );
     }
-    if tournament.current_round() == 0 {
-        let color = match payload.first_color.as_ref().map(|s| s.as_str()) {
-            Some("black") => Color::Black,
-            Some("white") => Color::White,
-            _ => Color::White,
+
+    #[test]
+    fn test_deterministic_pairings() {
+        // Identical players only differ by id, every tournament gets its own hash map order
+        let build = || {
+            let mut tournament = test_tournament(
+                (1..=9)
+                    .map(|id| seeded_player(id, "Player, Same", 2000))
+                    .collect(),
+            );
+            let round = tournament
+                .generate_first_round_pairings(
+                    InactiveScores::new(),
+                    Color::White,
+                    &FixedPairings::default(),
+                )
+                .unwrap();
+            play_round(&mut tournament, &round);
+            tournament
         };
-        tournament.generate_first_round_pairings(scores, color)
-    } else {
-        let round_ongoing = tournament
-            .results
-            .last()
-            .unwrap()
-            .iter()
-            .any(|r| *r == GameResult::Ongoing);
-        if round_ongoing {
-            return Err(AppError::RoundNotDone);
+        let next_round = |tournament: &Tournament| {
+            let round = tournament
+                .generate_next_round_pairings(InactiveScores::new(), &FixedPairings::default())
+                .unwrap();
+            format!("{:?}", round)
+        };
+        let tournament = build();
+        let expected = next_round(&tournament);
+        assert_eq!(next_round(&tournament), expected);
+        for _ in 0..10 {
+            assert_eq!(next_round(&build()), expected);
         }
-        tournament.generate_next_round_pairings(scores)
     }
-}
 
-pub async fn update_player_status(
-    pool: &sqlx::Pool<sqlx::Sqlite>,
-    tournament_id: u32,
-    claims: Claims,
-    payload: &PlayerStatusPayload,
-) -> Result<(), AppError> {
-    let has_permission = check_user_tournament_permissions(pool, tournament_id, claims).await?;
-    if !has_permission {
-        return Err(AppError::InsufficientPermissions);
+    #[test]
+    fn test_standings_stable_order() {
+        let not_paired = |score: u32| vec![HistoryItem::NotPaired { score }];
+        let build = || {
+            let mut tournament = test_tournament(
+                [1, 0, 1, 1, 0, 1]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, score)| player_with_history(i as u32 + 1, not_paired(*score)))
+                    .collect(),
+            );
+            tournament.pairings = vec![vec![]];
+            tournament.results = vec![vec![]];
+            tournament
+        };
+        let order = |tournament: &Tournament| {
+            tournament.standings()[0]
+                .iter()
+                .map(|s| s.player_id)
+                .collect::<Vec<_>>()
+        };
+        for _ in 0..10 {
+            assert_eq!(order(&build()), vec![1, 3, 4, 6, 2, 5]);
+        }
     }
-    let status: PlayerStatus = payload.status.as_str().try_into()?;
-    registration_repo::update_registration_status(pool, payload.id, status)
-        .await
-        .map_err(|e| Into::<AppError>::into(e))
-}
 
-pub async fn update_result(
-    pool: &sqlx::Pool<sqlx::Sqlite>,
-    tournament_id: u32,
-    claims: Claims,
-    payload: &RoundResult,
-) -> Result<(), AppError> {
-    let has_permission = check_user_tournament_permissions(pool, tournament_id, claims).await?;
-    if !has_permission {
-        return Err(AppError::InsufficientPermissions);
+    #[test]
+    fn test_player_name() {
+        let name = PlayerName::new("  Bob ", "Smith,  \"Jr\"");
+        assert_eq!(name.to_string(), "Smith, \"Jr\", Bob");
+        assert_eq!(name.to_html(), "Smith, &quot;Jr&quot;, Bob");
+        let name = PlayerName::from("Carlsen,Magnus");
+        assert_eq!(name, PlayerName::new("Magnus", "Carlsen"));
+        assert_eq!(name.to_string(), "Carlsen, Magnus");
+        assert_eq!(PlayerName::from("Mononym").to_string(), "Mononym");
     }
-    let result = GameResult::from_str(payload.result.clone());
-    if result == GameResult::Ongoing {
-        tracing::error!("cannot update result to GameResult::Ongoing");
-        return Err(AppError::Unknown);
+
+    #[test]
+    fn test_standings_html() {
+        let mut players = vec![
+            player_with_history(1, vec![HistoryItem::NotPaired { score: 1 }]),
+            player_with_history(2, vec![HistoryItem::NotPaired { score: 2 }]),
+        ];
+        players[0].name = PlayerName::from("<script>alert('x')</script> & co");
+        players[1].name = PlayerName::from("Carlsen, Magnus");
+        players[1].title = Title::GM;
+        let mut tournament = test_tournament(players);
+        assert_eq!(export::standings_html(&tournament), None);
+        tournament.pairings = vec![vec![]];
+        tournament.results = vec![vec![]];
+        let html = export::standings_html(&tournament).unwrap();
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; co"));
+        assert!(html.contains("<th>Median Buchholz</th>"));
+        // The winner comes first, the half point is printed as such
+        let carlsen = html.find("GM Carlsen, Magnus").unwrap();
+        let other = html.find("&lt;script&gt;").unwrap();
+        assert!(carlsen < other);
+        assert!(html.contains("<td class=\"number\">½</td>"));
+        tournament.updated_at = 1769373667;
+        let html = export::standings_html(&tournament).unwrap();
+        assert!(html.contains("<p>Updated 2026-01-25 20:41 UTC</p>"));
     }
-    let tournament = read_tournament(pool, tournament_id).await?;
-    let tournament: Tournament = tournament.into();
-    if tournament.pairings.is_empty() {
-        return Err(AppError::TournamentNotStarted);
+
+    #[test]
+    fn test_tournament_stats() {
+        let players = numbered_players(5);
+        let mut tournament = test_tournament(players);
+        let stats = TournamentStats::new(&tournament);
+        assert_eq!(stats.games, 0);
+        assert_eq!(stats.decisive_percentage, 0.0);
+        assert_eq!(stats.average_rating, 1970);
+        assert_eq!(stats.rating_spread, 40);
+        // Unrated players do not lower the average nor widen the spread
+        tournament.players.get_mut(&5).unwrap().rating = 0;
+        let stats = TournamentStats::new(&tournament);
+        assert_eq!(stats.average_rating, 1975);
+        assert_eq!(stats.rating_spread, 30);
+        tournament.pairings = vec![vec![(1, 2), (3, 4)], vec![(2, 3), (4, 5)], vec![(1, 3)]];
+        tournament.results = vec![
+            vec![GameResult::WhiteWins, GameResult::Draw],
+            vec![GameResult::BlackWins, GameResult::DoubleForfeit],
+            vec![GameResult::Ongoing],
+        ];
+        tournament.byes = vec![vec![5], vec![1], vec![]];
+        let stats = TournamentStats::new(&tournament);
+        assert_eq!(stats.games, 3);
+        assert_eq!((stats.white_wins, stats.black_wins, stats.draws), (1, 1, 1));
+        assert_eq!(stats.decisive_percentage, 66.7);
+        assert_eq!(stats.white_win_percentage, 33.3);
+        assert_eq!(stats.byes, 2);
+        assert_eq!(stats.forfeits, 1);
     }
-    let round = match tournament.results.get(payload.round_id as usize) {
-        Some(r) => r,
-        None => return Err(AppError::RoundNotFound(payload.round_id as usize)),
-    };
-    if !round.get(payload.board_id as usize).is_some() {
-        tracing::error!("update_result: board id {} not found", payload.board_id);
-        return Err(AppError::Unknown);
+
+    #[test]
+    fn test_requested_bye() {
+        let mut players: Vec<Player> = numbered_players(4);
+        players[1].requested_bye_rounds = vec![0];
+        let tournament = test_tournament(players);
+        let round = tournament
+            .generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            )
+            .unwrap();
+        // Player 2 sits out, the remaining three need a pairing bye for the lowest seed
+        assert_eq!(round.pairings.len(), 1);
+        assert_eq!(
+            (round.pairings[0].white_id, round.pairings[0].black_id),
+            (1, 3)
+        );
+        let gap = |id: u32| round.gaps.iter().find(|g| g.player_id == id).unwrap();
+        assert!(gap(4).is_bye);
+        assert_eq!(gap(4).score, 2);
+        assert!(!gap(2).is_bye);
+        assert_eq!(gap(2).score, tournament.requested_bye_score);
+        // With one player left there is no board to store
+        let mut players: Vec<Player> = numbered_players(2);
+        players[1].requested_bye_rounds = vec![0];
+        let tournament = test_tournament(players);
+        assert!(matches!(
+            tournament.generate_first_round_pairings(
+                InactiveScores::new(),
+                Color::White,
+                &FixedPairings::default(),
+            ),
+            Err(AppError::EmptyPairingsGenerated)
+        ));
     }
-    if (payload.round_id as usize) < tournament.current_round() - 1 {
-        return Err(AppError::InvalidRound(payload.round_id as usize));
+
+    #[test]
+    fn test_cumulative_cut1() {
+        let not_paired = |scores: [u32; 3]| {
+            scores
+                .iter()
+                .map(|score| HistoryItem::NotPaired { score: *score })
+                .collect()
+        };
+        // Everyone finishes on 4 points
+        let mut tournament = test_tournament(vec![
+            player_with_history(1, not_paired([2, 0, 2])),
+            player_with_history(2, not_paired([0, 2, 2])),
+            player_with_history(3, not_paired([1, 2, 1])),
+            player_with_history(4, not_paired([2, 1, 1])),
+        ]);
+        tournament.num_rounds = 3;
+        tournament.pairings = vec![vec![], vec![], vec![]];
+        tournament.results = vec![vec![], vec![], vec![]];
+        tournament.tiebreaks = vec![Tiebreak::CumulativeCut1, Tiebreak::Progressive];
+        let standings = tournament.swiss_standings();
+        let last = standings.last().unwrap();
+        let standing = |id: u32| last.iter().find(|s| s.player_id == id).unwrap();
+        // Running scores of player 4 are 2, 3 and 4
+        assert_eq!(standing(4).progressive, 9);
+        assert_eq!(standing(4).cumulative_cut1, 7);
+        assert_eq!(standing(1).progressive, 8);
+        assert_eq!(standing(1).cumulative_cut1, 6);
+        assert_eq!(standing(2).progressive, 6);
+        assert_eq!(standing(2).cumulative_cut1, 6);
+        assert_eq!(standing(3).progressive, 8);
+        assert_eq!(standing(3).cumulative_cut1, 7);
+        assert_eq!(
+            last.iter().map(|s| s.player_id).collect::<Vec<_>>(),
+            vec![4, 3, 1, 2]
+        );
+        // Right after the first round there is nothing left to count
+        assert!(standings[0].iter().all(|s| s.cumulative_cut1 == 0));
     }
-    update_game_result(
-        pool,
-        tournament_id,
-        payload.round_id,
-        payload.board_id,
-        result,
-    )
-    .await
-    .map_err(|e| Into::<AppError>::into(e))
-}
 
-#[cfg(test)]
-mod tests {
+    #[test]
+    fn test_playoff_bracket() {
+        let not_paired = |scores: [u32; 2]| {
+            scores
+                .iter()
+                .map(|score| HistoryItem::NotPaired { score: *score })
+                .collect()
+        };
+        // 1, 2 and 3 are tied on 2 points, progressive score ranks them 1, 3, 2
+        let mut tournament = test_tournament(vec![
+            player_with_history(1, not_paired([2, 0])),
+            player_with_history(2, not_paired([0, 2])),
+            player_with_history(3, not_paired([1, 1])),
+            player_with_history(4, not_paired([0, 0])),
+        ]);
+        tournament.num_rounds = 2;
+        tournament.pairings = vec![vec![], vec![]];
+        tournament.results = vec![vec![], vec![]];
+        let game = |id, round_number, white_id, black_id, result: Option<&str>| DbPlayoffGame {
+            id,
+            round_number,
+            white_id,
+            black_id,
+            result: result.map(|r| r.to_string()),
+        };
+        // Best seed gets a pass, 3 plays 2
+        assert_eq!(tournament.next_playoff_round().unwrap(), (0, vec![(3, 2)]));
+        tournament.playoff.push(game(1, 0, 3, 2, None));
+        assert!(matches!(
+            tournament.next_playoff_round(),
+            Err(AppError::RoundNotDone)
+        ));
+        // A draw goes to black
+        tournament.playoff[0].result = Some("=-=".to_string());
+        assert_eq!(tournament.next_playoff_round().unwrap(), (1, vec![(1, 2)]));
+        tournament.playoff.push(game(2, 1, 1, 2, Some("0-1")));
+        assert!(matches!(
+            tournament.next_playoff_round(),
+            Err(AppError::PlayoffFinished)
+        ));
+        let standings = tournament.standings();
+        let order: Vec<u32> = standings
+            .last()
+            .unwrap()
+            .iter()
+            .map(|s| s.player_id)
+            .collect();
+        assert_eq!(order, vec![2, 1, 3, 4]);
+        // Playoff games do not count for the score
+        assert_eq!(standings.last().unwrap()[0].score, 2);
+    }
 
-    use std::collections::HashMap;
+    #[test]
+    fn test_edge_weight_max_float_gap() {
+        let winner = Player {
+            id: 1,
+            history: vec![HistoryItem::Game {
+                opponent_id: 3,
+                color: Color::White,
+                result: GameResult::WhiteWins,
+            }],
+            ..Default::default()
+        };
+        let loser = Player {
+            id: 2,
+            history: vec![HistoryItem::Game {
+                opponent_id: 4,
+                color: Color::Black,
+                result: GameResult::WhiteWins,
+            }],
+            ..Default::default()
+        };
+        // Scores are 2 and 0 half points
+        assert!(edge_weight(&winner, &loser, (0, 0), (1, 1), 0, Some(1), 0).is_none());
+        assert!(edge_weight(&winner, &loser, (0, 0), (1, 1), 0, Some(2), 0).is_some());
+        assert!(edge_weight(&winner, &loser, (0, 0), (1, 1), 0, None, 0).is_some());
+    }
 
-    use crate::models::tournament::{
-        Color, GameResult, HistoryItem, Player, PlayerStanding, PlayerStatus, Title, Tournament,
-    };
+    #[test]
+    fn test_recent_opponent_penalty() {
+        let players = numbered_players(4)
+            .into_iter()
+            .map(|player| Player {
+                db_id: player.id + 10,
+                ..player
+            })
+            .collect();
+        let mut tournament = test_tournament(players);
+        let weights = |tournament: &Tournament| -> HashMap<(u32, u32), EdgeWeight> {
+            tournament
+                .pairing_graph()
+                .unwrap()
+                .edges
+                .into_iter()
+                .map(|edge| (edge.players, edge.breakdown))
+                .collect()
+        };
+        let before = weights(&tournament);
+        // Players 1 and 3 met twice in earlier tournaments, keyed by their player ids
+        tournament.recent_meetings = HashMap::from([((11, 13), 2)]);
+        let after = weights(&tournament);
+        assert_eq!(
+            after[&(1, 3)].recent_opponent_penalty,
+            2 * RECENT_OPPONENT_PENALTY
+        );
+        assert_eq!(
+            after[&(1, 3)].total(),
+            before[&(1, 3)].total() - 2 * RECENT_OPPONENT_PENALTY
+        );
+        assert!(
+            after
+                .iter()
+                .filter(|(pair, _)| **pair != (1, 3))
+                .all(|(pair, weight)| weight.total() == before[pair].total())
+        );
+    }
 
     #[test]
     fn test_standings_basic_no_ties() {
@@ -942,7 +4029,7 @@ mod tests {
             Player {
                 id: 1,
                 db_id: 0,
-                name: "Player1".to_string(),
+                name: PlayerName::from("Player1"),
                 rating: 2000,
                 title: Title::Untitled,
                 history: vec![
@@ -961,6 +4048,8 @@ mod tests {
                 fide_id: None,
                 federation: None,
                 status: PlayerStatus::Active,
+                section_id: None,
+                requested_bye_rounds: vec![],
             },
         );
 
@@ -970,7 +4059,7 @@ mod tests {
             Player {
                 id: 2,
                 db_id: 0,
-                name: "Player2".to_string(),
+                name: PlayerName::from("Player2"),
                 rating: 1800,
                 title: Title::Untitled,
                 history: vec![
@@ -989,6 +4078,8 @@ mod tests {
                 fide_id: None,
                 federation: None,
                 status: PlayerStatus::Active,
+                section_id: None,
+                requested_bye_rounds: vec![],
             },
         );
 
@@ -998,7 +4089,7 @@ mod tests {
             Player {
                 id: 3,
                 db_id: 0,
-                name: "Player3".to_string(),
+                name: PlayerName::from("Player3"),
                 rating: 1900,
                 title: Title::Untitled,
                 history: vec![
@@ -1017,6 +4108,8 @@ mod tests {
                 fide_id: None,
                 federation: None,
                 status: PlayerStatus::Active,
+                section_id: None,
+                requested_bye_rounds: vec![],
             },
         );
 
@@ -1026,7 +4119,7 @@ mod tests {
             Player {
                 id: 4,
                 db_id: 0,
-                name: "Player4".to_string(),
+                name: PlayerName::from("Player4"),
                 rating: 1700,
                 title: Title::Untitled,
                 history: vec![
@@ -1045,25 +4138,16 @@ mod tests {
                 fide_id: None,
                 federation: None,
                 status: PlayerStatus::Active,
+                section_id: None,
+                requested_bye_rounds: vec![],
             },
         );
 
         let tournament = Tournament {
-            id: 1,
-            name: "Test Tournament".to_string(),
-            time_category: "Classical".to_string(),
             players,
             pairings: vec![vec![(1, 2), (3, 4)], vec![(1, 3), (2, 4)]], // Dummy pairings, not used in standings
-            byes: vec![],
-            results: vec![],
             num_rounds: 2,
-            start_date: 0,
-            federation: "FIDE".to_string(),
-            user_id: 0,
-            username: "test".to_string(),
-            updated_at: 0,
-            end_date: None,
-            url: None,
+            ..test_tournament(vec![])
         };
 
         let standings = tournament.standings();
@@ -1087,6 +4171,7 @@ mod tests {
                 median_buchholz: 0,
                 cut_one_buchholz: 0,
                 progressive: 0,
+                cumulative_cut1: 0,
             }, // progressive ignored
             PlayerStanding {
                 player_id: 3,
@@ -1095,6 +4180,7 @@ mod tests {
                 median_buchholz: 0,
                 cut_one_buchholz: 0,
                 progressive: 0,
+                cumulative_cut1: 0,
             },
             PlayerStanding {
                 player_id: 2,
@@ -1103,6 +4189,7 @@ mod tests {
                 median_buchholz: 0,
                 cut_one_buchholz: 0,
                 progressive: 0,
+                cumulative_cut1: 0,
             },
             PlayerStanding {
                 player_id: 4,
@@ -1111,6 +4198,7 @@ mod tests {
                 median_buchholz: 0,
                 cut_one_buchholz: 0,
                 progressive: 0,
+                cumulative_cut1: 0,
             },
         ];
 
@@ -1134,6 +4222,7 @@ mod tests {
                 median_buchholz: 0,
                 cut_one_buchholz: 2,
                 progressive: 0,
+                cumulative_cut1: 0,
             },
             PlayerStanding {
                 player_id: 2,
@@ -1142,6 +4231,7 @@ mod tests {
                 median_buchholz: 0,
                 cut_one_buchholz: 4,
                 progressive: 0,
+                cumulative_cut1: 0,
             },
             PlayerStanding {
                 player_id: 3,
@@ -1150,6 +4240,7 @@ mod tests {
                 median_buchholz: 0,
                 cut_one_buchholz: 4,
                 progressive: 0,
+                cumulative_cut1: 0,
             },
             PlayerStanding {
                 player_id: 4,
@@ -1158,6 +4249,7 @@ mod tests {
                 median_buchholz: 0,
                 cut_one_buchholz: 2,
                 progressive: 0,
+                cumulative_cut1: 0,
             },
         ];
 
@@ -1238,7 +4330,7 @@ mod tests {
             Player {
                 id: 1,
                 db_id: 0,
-                name: "Player1".to_string(),
+                name: PlayerName::from("Player1"),
                 rating: 2000,
                 title: Title::Untitled,
                 history: vec![
@@ -1257,6 +4349,8 @@ mod tests {
                 fide_id: None,
                 federation: None,
                 status: PlayerStatus::Active,
+                section_id: None,
+                requested_bye_rounds: vec![],
             },
         );
 
@@ -1266,7 +4360,7 @@ mod tests {
             Player {
                 id: 2,
                 db_id: 0,
-                name: "Player2".to_string(),
+                name: PlayerName::from("Player2"),
                 rating: 1800,
                 title: Title::Untitled,
                 history: vec![
@@ -1285,6 +4379,8 @@ mod tests {
                 fide_id: None,
                 federation: None,
                 status: PlayerStatus::Active,
+                section_id: None,
+                requested_bye_rounds: vec![],
             },
         );
 
@@ -1294,7 +4390,7 @@ mod tests {
             Player {
                 id: 3,
                 db_id: 0,
-                name: "Player3".to_string(),
+                name: PlayerName::from("Player3"),
                 rating: 1900,
                 title: Title::Untitled,
                 history: vec![
@@ -1313,6 +4409,8 @@ mod tests {
                 fide_id: None,
                 federation: None,
                 status: PlayerStatus::Active,
+                section_id: None,
+                requested_bye_rounds: vec![],
             },
         );
 
@@ -1322,7 +4420,7 @@ mod tests {
             Player {
                 id: 4,
                 db_id: 0,
-                name: "Player4".to_string(),
+                name: PlayerName::from("Player4"),
                 rating: 1700,
                 title: Title::Untitled,
                 history: vec![
@@ -1341,25 +4439,16 @@ mod tests {
                 fide_id: None,
                 federation: None,
                 status: PlayerStatus::Active,
+                section_id: None,
+                requested_bye_rounds: vec![],
             },
         );
 
         let tournament = Tournament {
-            id: 1,
-            name: "Test Tournament".to_string(),
-            time_category: "Classical".to_string(),
             players,
             pairings: vec![vec![(1, 3), (2, 4)], vec![(1, 4), (2, 3)]],
-            byes: vec![],
-            results: vec![],
             num_rounds: 2,
-            start_date: 0,
-            federation: "FIDE".to_string(),
-            user_id: 0,
-            username: "test".to_string(),
-            updated_at: 0,
-            end_date: None,
-            url: None,
+            ..test_tournament(vec![])
         };
 
         let standings = tournament.standings();
@@ -1418,7 +4507,7 @@ mod tests {
             Player {
                 id: 1,
                 db_id: 0,
-                name: "Player1".to_string(),
+                name: PlayerName::from("Player1"),
                 rating: 2000,
                 title: Title::Untitled,
                 history: vec![
@@ -1437,6 +4526,8 @@ mod tests {
                 fide_id: None,
                 federation: None,
                 status: PlayerStatus::Active,
+                section_id: None,
+                requested_bye_rounds: vec![],
             },
         );
 
@@ -1446,7 +4537,7 @@ mod tests {
             Player {
                 id: 2,
                 db_id: 0,
-                name: "Player2".to_string(),
+                name: PlayerName::from("Player2"),
                 rating: 1800,
                 title: Title::Untitled,
                 history: vec![
@@ -1461,6 +4552,8 @@ mod tests {
                 fide_id: None,
                 federation: None,
                 status: PlayerStatus::Active,
+                section_id: None,
+                requested_bye_rounds: vec![],
             },
         );
 
@@ -1470,7 +4563,7 @@ mod tests {
             Player {
                 id: 3,
                 db_id: 0,
-                name: "Player3".to_string(),
+                name: PlayerName::from("Player3"),
                 rating: 1900,
                 title: Title::Untitled,
                 history: vec![
@@ -1485,25 +4578,17 @@ mod tests {
                 fide_id: None,
                 federation: None,
                 status: PlayerStatus::Active,
+                section_id: None,
+                requested_bye_rounds: vec![],
             },
         );
 
         let tournament = Tournament {
-            id: 1,
-            name: "Test Tournament".to_string(),
-            time_category: "Classical".to_string(),
             players,
             pairings: vec![vec![(1, 2)], vec![(1, 3)]], // Dummy, ignoring bye pairs
             byes: vec![vec![3], vec![2]],
-            results: vec![],
             num_rounds: 2,
-            start_date: 0,
-            federation: "FIDE".to_string(),
-            user_id: 0,
-            username: "test".to_string(),
-            updated_at: 0,
-            end_date: None,
-            url: None,
+            ..test_tournament(vec![])
         };
 
         let standings = tournament.standings();